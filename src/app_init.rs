@@ -1,5 +1,6 @@
 use crate::config::{app_config::AppConfig, locations::LocationsProvider};
 use crate::migration;
+use crate::permissions::create_private_dir;
 use anyhow::Result;
 use std::{
     fs::{self, File},
@@ -13,13 +14,19 @@ pub struct AppInitializer<'a> {
 }
 
 impl<'a> AppInitializer<'a> {
-    /// Creates config dir and file if they don't exist
+    /// Creates config dir and file if they don't exist. New config and data dirs are only
+    /// accessible to the user.
     pub fn init(&self) -> Result<()> {
+        let base_data_dir = self.locations_provider.get_base_data_path()?;
+        if !base_data_dir.exists() {
+            create_private_dir(&base_data_dir)?;
+        }
+
         migration::migrate_projects_path_if_needed(self.locations_provider)?;
 
         let base_config_dir = self.locations_provider.get_base_config_path()?;
         if !base_config_dir.exists() {
-            fs::create_dir_all(&base_config_dir)?;
+            create_private_dir(&base_config_dir)?;
         }
 
         let config_file_path = self.locations_provider.get_config_file_path();
@@ -32,6 +39,9 @@ impl<'a> AppInitializer<'a> {
             fs::create_dir_all(projects_dir_path)?;
         }
 
+        migration::create_missing_project_metadata(self.locations_provider)?;
+        migration::normalize_project_ids(self.locations_provider)?;
+
         Ok(())
     }
 
@@ -79,6 +89,27 @@ mod tests {
         assert_eq!("{\"projects\":[]}", config_file_content);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn init_creates_config_and_data_dirs_accessible_to_the_user_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let config_path = config_dir.path().join("puff");
+        let data_path = data_dir.path().join("puff");
+        let locations_provider = LocationsProvider::new(config_path.clone(), data_path.clone());
+        let init = AppInitializer {
+            locations_provider: &locations_provider,
+        };
+
+        init.init().unwrap();
+
+        for dir in [config_path, data_path] {
+            let mode = fs::metadata(dir).unwrap().permissions().mode();
+            assert_eq!(0o700, mode & 0o777);
+        }
+    }
+
     fn get_fake_locationsprovider(
         base_dir_should_exist: bool,
     ) -> (
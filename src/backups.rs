@@ -0,0 +1,319 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    config::{locations::LocationsProvider, project_metadata::ProjectMetadata, settings::Settings},
+    fs_utils::copy_dir_recursive,
+    output::format_timestamp,
+};
+
+/// Name of the directory in puff's data directory that holds the backups
+pub(crate) const BACKUPS_DIR: &str = "backups";
+
+/// Name of the index of a project's backups
+pub(crate) const BACKUP_INDEX_FILE: &str = "index.json";
+
+/// A copy of a project's file or directory, made before puff replaced it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Backup {
+    /// Name of the timestamped directory that holds the copy
+    pub id: String,
+    /// Path of the backed up item, relative to the project's root
+    pub path: PathBuf,
+    /// Where the item was when it got backed up
+    pub original_path: PathBuf,
+    /// Name of the project at the time of the backup, which identifies the backups of a
+    /// project that is no longer in puff
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Unix timestamp (seconds) of the backup
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub directory: bool,
+}
+
+/// The backups of a project. Copies are stored in `<data>/backups/<project id>/<id>/<path>`,
+/// where the id is the time of the backup, and are indexed in `index.json`. Keying the
+/// store by the project's id keeps the backups of a renamed project.
+pub struct BackupStore {
+    dir: PathBuf,
+    project_name: Option<String>,
+}
+
+impl BackupStore {
+    pub fn new(backups_path: &Path, project_id: &str) -> Self {
+        BackupStore {
+            dir: backups_path.join(project_id),
+            project_name: None,
+        }
+    }
+
+    /// The store of the project whose managed dir is `managed_dir`. Managed dirs are
+    /// `<data>/projects/<name>`, so the data directory is two levels up.
+    pub fn for_managed_dir(managed_dir: &Path) -> Result<Self> {
+        let Some(data_dir) = managed_dir.parent().and_then(Path::parent) else {
+            return Err(anyhow!(
+                "Could not find the backups of {}",
+                managed_dir.display()
+            ));
+        };
+        let metadata = ProjectMetadata::load(managed_dir)?;
+        Ok(BackupStore {
+            project_name: managed_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned()),
+            ..BackupStore::new(&data_dir.join(BACKUPS_DIR), &metadata.id)
+        })
+    }
+
+    /// Copies the file or directory at `original` (the item at `relative_path` of the
+    /// project) to the store
+    pub fn backup(&self, original: &Path, relative_path: &Path) -> Result<Backup> {
+        let now = SystemTime::now();
+        let base_id = format_timestamp(now);
+        let mut id = base_id.clone();
+        let mut n = 2;
+        while self
+            .dir
+            .join(&id)
+            .join(relative_path)
+            .symlink_metadata()
+            .is_ok()
+        {
+            id = format!("{base_id}-{n}");
+            n += 1;
+        }
+
+        let backup = Backup {
+            id,
+            path: relative_path.to_path_buf(),
+            original_path: original.to_path_buf(),
+            project: self.project_name.clone(),
+            created_at: now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            directory: original.is_dir(),
+        };
+        let stored = self.stored_path(&backup);
+        fs::create_dir_all(stored.parent().unwrap())?;
+        if backup.directory {
+            copy_dir_recursive(original, &stored)?;
+        } else {
+            fs::copy(original, &stored)?;
+        }
+
+        let mut backups = self.list()?;
+        backups.push(backup.clone());
+        self.write_index(&backups)?;
+        Ok(backup)
+    }
+
+    /// All backups of the project, oldest first
+    pub fn list(&self) -> Result<Vec<Backup>> {
+        let path = self.dir.join(BACKUP_INDEX_FILE);
+        if !path.is_file() {
+            return Ok(vec![]);
+        }
+        let mut backups: Vec<Backup> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        backups.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+        Ok(backups)
+    }
+
+    /// Where the copy of `backup` is stored
+    pub fn stored_path(&self, backup: &Backup) -> PathBuf {
+        self.dir.join(&backup.id).join(&backup.path)
+    }
+
+    /// Copies the backed up item to `destination`
+    pub fn restore(&self, backup: &Backup, destination: &Path) -> Result<()> {
+        let stored = self.stored_path(backup);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if backup.directory {
+            copy_dir_recursive(&stored, destination)
+        } else {
+            fs::copy(&stored, destination)?;
+            Ok(())
+        }
+    }
+
+    /// Deletes the backups that match `predicate` and returns them
+    pub fn remove(&self, predicate: impl Fn(&Backup) -> bool) -> Result<Vec<Backup>> {
+        let (removed, kept): (Vec<Backup>, Vec<Backup>) =
+            self.list()?.into_iter().partition(predicate);
+        if removed.is_empty() {
+            return Ok(removed);
+        }
+        if kept.is_empty() {
+            fs::remove_dir_all(&self.dir)?;
+            return Ok(removed);
+        }
+
+        for backup in &removed {
+            let stored = self.stored_path(backup);
+            if backup.directory {
+                fs::remove_dir_all(&stored).ok();
+            } else {
+                fs::remove_file(&stored).ok();
+            }
+            for parent in stored.ancestors().skip(1) {
+                if parent == self.dir || fs::remove_dir(parent).is_err() {
+                    break;
+                }
+            }
+        }
+        self.write_index(&kept)?;
+        Ok(removed)
+    }
+
+    fn write_index(&self, backups: &[Backup]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.dir.join(BACKUP_INDEX_FILE),
+            serde_json::to_string_pretty(backups)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Ids of the projects that have backups
+pub fn projects_with_backups(backups_path: &Path) -> Result<Vec<String>> {
+    if !backups_path.is_dir() {
+        return Ok(vec![]);
+    }
+    let mut ids = vec![];
+    for entry in fs::read_dir(backups_path)? {
+        let entry = entry?;
+        if entry.path().join(BACKUP_INDEX_FILE).is_file() {
+            ids.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    ids.sort();
+    Ok(ids)
+}
+
+/// Id of the project called `name` among the projects that are no longer in puff, taken
+/// from the name recorded in their latest backup
+pub fn find_forgotten_project(backups_path: &Path, name: &str) -> Result<Option<String>> {
+    for id in projects_with_backups(backups_path)? {
+        let latest = BackupStore::new(backups_path, &id).list()?.pop();
+        if latest.and_then(|b| b.project).as_deref() == Some(name) {
+            return Ok(Some(id));
+        }
+    }
+    Ok(None)
+}
+
+/// Deletes the backups that are older than the `backup-retention-days` setting that
+/// applies to their project, and returns how many were deleted. The global setting
+/// applies to the backups of projects that are no longer in puff.
+pub fn remove_expired(locations_provider: &LocationsProvider, global: &Settings) -> Result<usize> {
+    let backups_path = locations_provider.get_backups_path();
+    let projects = ProjectMetadata::read_all(&locations_provider.get_projects_data_path())?;
+    let mut removed = 0;
+    for id in projects_with_backups(&backups_path)? {
+        let settings = match projects.iter().find(|(_, metadata)| metadata.id == id) {
+            Some((_, metadata)) => metadata.settings.apply(global),
+            None => global.clone(),
+        };
+        if settings.backup_retention_days == 0 {
+            continue;
+        }
+        let retention = Duration::from_secs(u64::from(settings.backup_retention_days) * 86_400);
+        let cutoff = SystemTime::now()
+            .checked_sub(retention)
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        removed += BackupStore::new(&backups_path, &id)
+            .remove(|b| b.created_at < cutoff)?
+            .len();
+    }
+    Ok(removed)
+}
+
+/// Runs [`remove_expired`] after a command that may have made backups. Failing to clean up
+/// doesn't fail the command, it only prints a warning.
+pub fn remove_expired_after_command(locations_provider: &LocationsProvider, global: &Settings) {
+    if let Err(e) = remove_expired(locations_provider, global) {
+        eprintln!("Warning: could not remove expired backups: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackupStore, remove_expired};
+    use crate::config::{
+        locations::LocationsProvider, project_metadata::ProjectMetadata, settings::Settings,
+    };
+    use std::{fs, path::Path};
+
+    #[test]
+    fn backup_copies_file_to_store_and_indexes_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join(".env");
+        fs::write(&original, "A=1").unwrap();
+        let store = BackupStore::new(&dir.path().join("backups"), "app");
+
+        let first = store.backup(&original, Path::new(".env")).unwrap();
+        let second = store.backup(&original, Path::new(".env")).unwrap();
+
+        assert_ne!(first.id, second.id);
+        assert_eq!(vec![first.clone(), second], store.list().unwrap());
+        assert_eq!(
+            "A=1",
+            fs::read_to_string(store.stored_path(&first)).unwrap()
+        );
+        assert!(!dir.path().join(".env.bak").exists());
+    }
+
+    #[test]
+    fn for_managed_dir_keys_the_store_by_project_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let managed_dir = dir.path().join("projects/app");
+        fs::create_dir_all(&managed_dir).unwrap();
+        ProjectMetadata::new("1234").write(&managed_dir).unwrap();
+        let original = dir.path().join(".env");
+        fs::write(&original, "A=1").unwrap();
+
+        let backup = BackupStore::for_managed_dir(&managed_dir)
+            .unwrap()
+            .backup(&original, Path::new(".env"))
+            .unwrap();
+
+        // The project's name doesn't matter, so renaming it keeps the backups
+        let store = BackupStore::new(&dir.path().join("backups"), "1234");
+        assert_eq!(vec![backup], store.list().unwrap());
+    }
+
+    #[test]
+    fn remove_expired_keeps_recent_backups() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let original = data_dir.path().join(".env");
+        fs::write(&original, "A=1").unwrap();
+        let store = BackupStore::new(&locations_provider.get_backups_path(), "app");
+        let mut old = store.backup(&original, Path::new(".env")).unwrap();
+        let recent = store.backup(&original, Path::new("config/.env")).unwrap();
+        let mut backups = store.list().unwrap();
+        backups[0].created_at -= 31 * 86_400;
+        store.write_index(&backups).unwrap();
+        old.created_at = backups[0].created_at;
+
+        remove_expired(&locations_provider, &Settings::default()).unwrap();
+
+        assert_eq!(vec![recent], store.list().unwrap());
+        assert!(!store.stored_path(&old).exists());
+    }
+}
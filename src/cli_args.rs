@@ -3,7 +3,11 @@ use clap_complete::Shell;
 use clap_complete::engine::ArgValueCompleter;
 use std::path::PathBuf;
 
+use crate::commands::{list_command::ListSortKey, reconcile_command::MissingDataAction};
 use crate::completions::{complete_project_name, complete_unassociated_project_name};
+use crate::config::settings::SettingKey;
+use crate::conflicts::OnConflict;
+use crate::output::OutputFormat;
 
 #[derive(Parser)]
 #[command(
@@ -36,18 +40,34 @@ pub enum Command {
 
     /// Adds a new file or directory to be tracked by puff in this project.
     /// If the path does not exist, it will be created (as a file by default, or as a directory with --dir).
+    /// Glob patterns (e.g. '**/.env') are expanded by puff, relative to the project root; quote them
+    /// so that the shell doesn't.
     Add {
-        /// Paths to be added
+        /// Paths or glob patterns to be added
         #[arg(num_args = 1.., required = true)]
         files: Vec<PathBuf>,
 
-        /// Adds the new path to .gitignore (.gitignore will be created if it doesn't exist)
+        /// Adds the new path to .gitignore (.gitignore will be created if it doesn't exist).
+        /// Writes to .git/info/exclude instead if the git-ignore-target setting is 'exclude'
         #[arg(short = 'g', long = "git-ignore")]
         git_ignore: bool,
 
+        /// Adds the new path to the repository's .git/info/exclude, which is never committed
+        #[arg(short = 'x', long = "git-exclude", conflicts_with = "git_ignore")]
+        git_exclude: bool,
+
         /// Treat non-existing paths as directories (existing paths are auto-detected)
         #[arg(long = "dir")]
         dir: bool,
+
+        /// Also search .git, node_modules and target directories when expanding glob patterns
+        #[arg(long = "no-default-excludes")]
+        no_default_excludes: bool,
+
+        /// How to resolve files that exist both here and in puff. Asks in a terminal, and
+        /// fails otherwise, by default
+        #[arg(long = "on-conflict", value_enum)]
+        on_conflict: Option<OnConflict>,
     },
 
     /// Removes a file from puff. The file will stay in
@@ -57,16 +77,70 @@ pub enum Command {
         #[arg(num_args = 1.., required = true)]
         files: Vec<PathBuf>,
 
-        /// Removes the file from the host
+        /// Removes the file from the host, moving puff's copy to the trash (see `puff trash`)
         #[arg(short = 'd', long = "delete")]
         delete_file: bool,
+
+        /// Overwrites puff's copy before deleting it instead of moving it to the trash, and
+        /// does the same with its backups and earlier copies in the trash
+        #[arg(long = "shred", requires = "delete_file")]
+        shred: bool,
+    },
+
+    /// Renames or moves a managed file or directory within its project. The symlink (also in
+    /// the repository's other worktrees) and the ignore entry added by puff follow it
+    Mv {
+        /// The managed item to move
+        old: PathBuf,
+
+        /// Its new path
+        new: PathBuf,
+    },
+
+    /// Turns a managed directory into a real directory with a symlink for each of its files,
+    /// so that unmanaged files can be added next to them
+    Split {
+        /// The managed directory
+        dir: PathBuf,
+    },
+
+    /// Turns a directory of individually managed (and unmanaged) files into a single managed
+    /// directory. Shows what happens to each file and asks for a confirmation first
+    Merge {
+        /// The directory to merge
+        dir: PathBuf,
+
+        /// Merges without asking
+        #[arg(short = 'y', long = "yes")]
+        skip_confirmation: bool,
+
+        /// Only shows what would happen
+        #[arg(short = 'n', long = "dry-run")]
+        dry_run: bool,
+    },
+
+    /// Finds files in the project that git ignores and that look like configuration or
+    /// secrets (.env*, *.pem, *.key, appsettings.*.json, credentials*, .npmrc with tokens),
+    /// and lets you pick the ones that puff should manage
+    Scan {
+        /// Adds all found files without asking
+        #[arg(short = 'y', long = "yes")]
+        add_all: bool,
+
+        /// Output format of the found files
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
     },
 
     /// Lists all projects known to puff (both associated and unassociated ones)
     List(ListSubcommand),
 
     /// Shows the puff status of the current directory
-    Status,
+    Status {
+        /// Output format of the status
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
 
     /// Subcommand for managing projects
     Project {
@@ -74,12 +148,114 @@ pub enum Command {
         subcommand: ProjectSubcommand,
     },
 
+    /// Checks all associated projects for missing paths, broken links and managed items
+    /// that git could commit (tracked or not ignored), including in linked worktrees. Also
+    /// checks that other users can't read puff's data directory and the secrets in it.
+    Doctor {
+        /// Removes the permissions that other users have on puff's directories and files
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Rebuilds the associations of a lost config.json from the symlinks puff created.
+    /// Searches the given directories for symlinks into puff's data directory and infers
+    /// each project's directory from them
+    Recover {
+        /// Directories to search
+        #[arg(short = 's', long = "scan", required = true, num_args = 1..)]
+        dirs: Vec<PathBuf>,
+
+        /// Register the found projects without asking for confirmation
+        #[arg(short = 'y', long = "yes")]
+        skip_confirmation: bool,
+    },
+
+    /// Finds symlinks into puff's data directory whose targets no longer exist (e.g. left in
+    /// worktrees after `puff forget` or `puff project forget`) and deletes them or replaces
+    /// them with copies of the restored files. Asks about each of them by default
+    PruneLinks {
+        /// Directories to search (the current directory by default)
+        dirs: Vec<PathBuf>,
+
+        /// Delete all orphaned links without asking
+        #[arg(short = 'd', long = "delete", conflicts_with_all = ["restore", "list_only"])]
+        delete: bool,
+
+        /// Replace all orphaned links with copies of the files restored in the project's
+        /// directory, without asking
+        #[arg(short = 'r', long = "restore", conflicts_with = "list_only")]
+        restore: bool,
+
+        /// Only list the orphaned links
+        #[arg(short = 'l', long = "list")]
+        list_only: bool,
+    },
+
+    /// Fixes mismatches between config.json and puff's data directory: registry entries
+    /// whose data directory is missing, and stored projects that aren't associated with any
+    /// directory. Asks what to do with each of them (recreate, unregister or re-associate)
+    Reconcile {
+        /// Apply this action to all registry entries whose data directory is missing,
+        /// without asking
+        #[arg(long = "missing", value_enum, conflicts_with = "list_only")]
+        missing_action: Option<MissingDataAction>,
+
+        /// Only list the mismatches
+        #[arg(short = 'l', long = "list")]
+        list_only: bool,
+    },
+
+    /// Subcommand for managing git ignore entries of managed items
+    Gitignore {
+        #[command(subcommand)]
+        subcommand: GitignoreSubcommand,
+    },
+
+    /// Subcommand for managing git hooks of the project's repository
+    Hooks {
+        #[command(subcommand)]
+        subcommand: HooksSubcommand,
+    },
+
+    /// Fails if the staged changes contain a managed item, a path inside a managed directory,
+    /// or a value from one of the project's managed dotenv files. Run by the pre-commit hook
+    /// that `puff hooks install` writes
+    CheckStaged,
+
+    /// Lists, restores and removes the backups that puff makes of project files it replaces
+    /// (e.g. when resolving conflicts). Backups are kept in puff's data directory and removed
+    /// after the number of days in the backup-retention-days setting
+    Backups {
+        #[command(subcommand)]
+        subcommand: BackupsSubcommand,
+    },
+
+    /// Lists, restores and permanently deletes the managed items and projects that
+    /// `forget -d` and `project forget` deleted
+    Trash {
+        #[command(subcommand)]
+        subcommand: TrashSubcommand,
+    },
+
+    /// Shows or changes puff's global settings and their per-project overrides
+    Config {
+        #[command(subcommand)]
+        subcommand: ConfigSubcommand,
+    },
+
     /// Creates symlinks for a project's managed files in the current directory.
     /// Useful for git worktrees, jj workspaces, or any secondary working copy.
+    /// Without a project, the .puff marker file of the checkout determines the project
+    /// and the links are created next to it.
     Link {
         /// The project to link
         #[arg(add = ArgValueCompleter::new(complete_project_name))]
-        project_name: String,
+        project_name: Option<String>,
+
+        /// How to resolve files that exist both here and in puff. Asks in a terminal, and
+        /// backs up the local files otherwise, by default
+        #[arg(long = "on-conflict", value_enum)]
+        on_conflict: Option<OnConflict>,
     },
 
     /// Opens a new shell in the puff data directory where managed files are stored.
@@ -113,6 +289,26 @@ pub struct ListSubcommand {
     /// Retrieve only the associated projects
     #[arg(short = 'a', long = "associated")]
     pub only_associated: bool,
+
+    /// Retrieve only the projects whose name contains the given text
+    #[arg(short = 'f', long = "filter")]
+    pub name_filter: Option<String>,
+
+    /// Retrieve only the associated projects with broken links or a missing path
+    #[arg(long = "unhealthy")]
+    pub only_unhealthy: bool,
+
+    /// Column to sort the projects by
+    #[arg(short = 's', long = "sort", value_enum, default_value_t = ListSortKey::Name)]
+    pub sort: ListSortKey,
+
+    /// Reverses the sort order
+    #[arg(short = 'r', long = "reverse")]
+    pub reverse: bool,
+
+    /// Output format of the list
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
 }
 
 #[derive(Args)]
@@ -124,12 +320,74 @@ pub struct InitSubcommand {
     /// Associate with an existing unassociated project (skips the interactive prompt)
     #[arg(short = 'a', long = "associate", conflicts_with = "name", add = ArgValueCompleter::new(complete_unassociated_project_name))]
     pub associate: Option<String>,
+
+    /// Writes a .puff marker file identifying the project, meant to be committed so that
+    /// clones and worktrees of the repository can be initialized without arguments
+    #[arg(short = 'm', long = "marker")]
+    pub marker: bool,
+
+    /// Searches the directory tree for checkouts of unassociated projects (by .puff marker,
+    /// recorded git remote or directory name) and associates all of them after one confirmation
+    #[arg(long = "scan", value_name = "DIR", conflicts_with_all = ["name", "associate", "marker"])]
+    pub scan: Option<PathBuf>,
+
+    /// Skips the confirmation of --scan
+    #[arg(short = 'y', long = "yes", requires = "scan")]
+    pub skip_confirmation: bool,
+
+    /// How to resolve files that exist both in the directory and in puff when a stored
+    /// project gets associated. Asks in a terminal, and backs up the local files otherwise,
+    /// by default
+    #[arg(long = "on-conflict", value_enum)]
+    pub on_conflict: Option<OnConflict>,
 }
 
 #[derive(Subcommand)]
 pub enum ProjectSubcommand {
     /// Removes a project. By default, all project's files managed by puff will be moved into the associated path (if the project is associated with any path)
     Forget(ProjectForgetSubcommand),
+
+    /// Shows details of a project: its path, managed items and their link state.
+    /// Defaults to the project of the current directory
+    Show(ProjectShowSubcommand),
+
+    /// Writes a .puff marker file (project name and id) into the project's directory.
+    /// Once committed, `puff init`, `puff link` and `puff status` recognize the project in
+    /// fresh clones, moved checkouts and worktrees. Defaults to the project of the current directory
+    Mark(ProjectMarkSubcommand),
+
+    /// Sets the description of a project, which is stored with its data and shown by
+    /// `puff project show`. Without a description, removes it.
+    /// Defaults to the project of the current directory
+    Describe(ProjectDescribeSubcommand),
+}
+
+#[derive(Args)]
+pub struct ProjectDescribeSubcommand {
+    /// The new description
+    pub description: Option<String>,
+
+    /// Project to describe
+    #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+    pub project_name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ProjectMarkSubcommand {
+    /// Project to mark
+    #[arg(add = ArgValueCompleter::new(complete_project_name))]
+    pub project_name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ProjectShowSubcommand {
+    /// Project to show
+    #[arg(add = ArgValueCompleter::new(complete_project_name))]
+    pub project_name: Option<String>,
+
+    /// Output format of the details
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
 }
 
 #[derive(Args)]
@@ -138,11 +396,213 @@ pub struct ProjectForgetSubcommand {
     #[arg(add = ArgValueCompleter::new(complete_project_name))]
     pub project_name: String, // TODO: Vec<PathBuf>
 
-    /// Deletes the managed files from the filesystem
+    /// Deletes the managed files from the filesystem, moving puff's copies to the trash
+    /// (see `puff trash`)
     #[arg(short = 'd', long = "delete-files")]
     pub delete_files: bool,
 
+    /// Overwrites puff's copies before deleting them instead of moving them to the trash,
+    /// and does the same with the project's backups and its copies in the trash
+    #[arg(long = "shred", requires = "delete_files")]
+    pub shred: bool,
+
     /// Skips the Y/N question
     #[arg(short = 'y')]
     pub skip_confirmation: bool,
 }
+
+#[derive(Subcommand)]
+pub enum ConfigSubcommand {
+    /// Lists all settings along with their values
+    List {
+        /// List the values that apply to this project
+        #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+        project: Option<String>,
+    },
+
+    /// Prints the value of a setting
+    Get {
+        /// Setting to print
+        #[arg(value_enum)]
+        key: SettingKey,
+
+        /// Print the value that applies to this project
+        #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+        project: Option<String>,
+    },
+
+    /// Changes the value of a setting.
+    /// git-ignore-target: where `puff add -g` writes ignore entries ('gitignore' or 'exclude').
+    /// backup-retention-days: how many days backups are kept (0 keeps them forever)
+    Set {
+        /// Setting to change
+        #[arg(value_enum)]
+        key: SettingKey,
+
+        /// New value of the setting
+        value: String,
+
+        /// Override the setting for this project only. The override is stored with the
+        /// project's data, so it applies on every machine
+        #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+        project: Option<String>,
+    },
+
+    /// Removes a project's override of a setting, so that the global value applies again
+    Unset {
+        /// Setting to reset
+        #[arg(value_enum)]
+        key: SettingKey,
+
+        /// Project whose override is removed
+        #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+        project: String,
+    },
+
+    /// Manages roots: named directories (e.g. code = ~/src) that project paths are stored
+    /// relative to, so that config.json works on machines with different directory layouts
+    Roots {
+        #[command(subcommand)]
+        subcommand: RootsSubcommand,
+    },
+
+    /// Adds the projects registered in a config.json from another machine
+    Import {
+        /// The config.json to import
+        file: PathBuf,
+
+        /// Replaces the FROM prefix of imported paths with TO (e.g. /Users/me/code=/home/me/code).
+        /// Can be given multiple times; the first matching rule wins
+        #[arg(short = 'r', long = "rewrite", value_name = "FROM=TO")]
+        rewrites: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RootsSubcommand {
+    /// Lists the defined roots
+    List,
+
+    /// Defines a root. Paths of projects inside it are stored as {name}/...
+    Add {
+        /// Name of the root
+        name: String,
+
+        /// Directory of the root
+        path: PathBuf,
+    },
+
+    /// Removes a root. Paths of projects inside it are stored in full again
+    Remove {
+        /// Name of the root
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum BackupsSubcommand {
+    /// Lists the backups of the current project, or of all projects outside of a project
+    List {
+        /// List the backups of this project
+        #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+        project: Option<String>,
+    },
+
+    /// Copies a backup to where it was taken from. Existing files are never overwritten
+    Restore {
+        /// Path of the backed up file or directory (relative to the project's root with --project)
+        path: PathBuf,
+
+        /// The backup to restore, as shown by `puff backups list`. Defaults to the latest
+        /// backup of the path
+        #[arg(long = "id")]
+        id: Option<String>,
+
+        /// Restores the backup to this path instead
+        #[arg(long = "to")]
+        to: Option<PathBuf>,
+
+        /// Restore a backup of this project
+        #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+        project: Option<String>,
+    },
+
+    /// Removes the backups of the current project, or of all projects outside of a project
+    Clean {
+        /// Only removes backups older than this many days
+        #[arg(long = "older-than", value_name = "DAYS")]
+        older_than: Option<u32>,
+
+        /// Remove the backups of this project
+        #[arg(short = 'p', long = "project", add = ArgValueCompleter::new(complete_project_name))]
+        project: Option<String>,
+
+        /// Skips the Y/N question
+        #[arg(short = 'y')]
+        skip_confirmation: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum TrashSubcommand {
+    /// Lists the deleted items and projects
+    List,
+
+    /// Moves a deleted item back to where it was deleted from and manages it again. A
+    /// deleted project goes back to puff's data directory, ready for `puff init --associate`
+    Restore {
+        /// The entry to restore, as shown by `puff trash list`
+        id: String,
+
+        /// Restores the item to this path instead
+        #[arg(long = "to")]
+        to: Option<PathBuf>,
+    },
+
+    /// Permanently deletes the content of the trash
+    Empty {
+        /// Only deletes entries that were deleted more than this many days ago
+        #[arg(long = "older-than", value_name = "DAYS")]
+        older_than: Option<u32>,
+
+        /// Overwrites the deleted content before removing it, along with the backups of the
+        /// same items
+        #[arg(long = "shred")]
+        shred: bool,
+
+        /// Skips the Y/N question
+        #[arg(short = 'y')]
+        skip_confirmation: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GitignoreSubcommand {
+    /// Adds ignore entries for the managed items of the current project that git doesn't ignore.
+    /// Writes to .gitignore, or to .git/info/exclude if the git-ignore-target setting is 'exclude'
+    Fix {
+        /// Writes the entries to the repository's .git/info/exclude
+        #[arg(short = 'x', long = "git-exclude")]
+        git_exclude: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HooksSubcommand {
+    /// Installs a pre-commit hook that runs `puff check-staged`, blocking commits of
+    /// puff-managed files and secrets
+    Install {
+        /// Also installs a post-checkout hook that links the project's managed files
+        /// into every new git worktree
+        #[arg(long = "worktrees")]
+        worktrees: bool,
+    },
+
+    /// Handles git's post-checkout hook (called by the hook that `--worktrees` installs)
+    #[command(hide = true)]
+    PostCheckout {
+        previous_head: String,
+        new_head: Option<String>,
+        checkout_type: Option<String>,
+    },
+}
@@ -1,8 +1,25 @@
 pub mod add_command;
+pub mod backups_command;
 pub mod cd_command;
+pub mod check_staged_command;
+pub mod config_command;
+pub mod doctor_command;
 pub mod file_forget_command;
+pub mod gitignore_command;
+pub mod hooks_command;
 pub mod init_command;
 pub mod link_command;
 pub mod list_command;
+pub mod merge_command;
+pub mod mv_command;
+pub mod project_describe_command;
 pub mod project_forget_command;
+pub mod project_mark_command;
+pub mod project_show_command;
+pub mod prune_links_command;
+pub mod reconcile_command;
+pub mod recover_command;
+pub mod scan_command;
+pub mod split_command;
 pub mod status_command;
+pub mod trash_command;
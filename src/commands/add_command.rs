@@ -1,8 +1,12 @@
 use crate::{
-    config::locations::LocationsProvider,
+    config::{locations::LocationsProvider, project_metadata::ProjectMetadata, settings::FileMode},
+    conflicts::{ConflictResolver, OnConflict, Resolution},
     fs_utils::{copy_dir_recursive, symlink_dir, symlink_file},
-    git_ignore::GitIgnoreHandler,
+    git_ignore::{GitIgnoreHandler, IgnoreTarget},
     managed_dirs,
+    patterns::{self, DEFAULT_EXCLUDED_DIRS},
+    permissions::restrict_managed,
+    puff_ignore::PuffIgnore,
 };
 use anyhow::{Result, anyhow, bail};
 use std::{
@@ -13,18 +17,44 @@ use std::{
 /// Handler for the `puff add <path>` command
 pub struct AddCommand<'a> {
     locations_provider: &'a LocationsProvider,
+    conflict_resolver: ConflictResolver,
+    /// Permissions that files get when puff starts managing them
+    file_mode: FileMode,
 }
 
 impl<'a> AddCommand<'a> {
+    /// Files that exist both in the project and in puff fail to be added, unless the user
+    /// resolves the conflict in a terminal
     pub fn new(locations_provider: &LocationsProvider) -> AddCommand<'_> {
-        AddCommand { locations_provider }
+        AddCommand::with_conflict_resolver(
+            locations_provider,
+            ConflictResolver::new(None, OnConflict::Fail),
+        )
+    }
+
+    pub fn with_conflict_resolver(
+        locations_provider: &LocationsProvider,
+        conflict_resolver: ConflictResolver,
+    ) -> AddCommand<'_> {
+        AddCommand {
+            locations_provider,
+            conflict_resolver,
+            file_mode: FileMode::default(),
+        }
+    }
+
+    /// Stores newly managed files with `file_mode` instead of the default 600
+    pub fn with_file_mode(mut self, file_mode: FileMode) -> Self {
+        self.file_mode = file_mode;
+        self.conflict_resolver = self.conflict_resolver.with_file_mode(file_mode);
+        self
     }
 
     pub fn add_file(
         &self,
         mut user_file: PathBuf,
         current_dir: &Path,
-        add_to_git_ignore: bool,
+        git_ignore: Option<IgnoreTarget>,
         force_dir: bool,
     ) -> Result<()> {
         if !user_file.is_absolute() {
@@ -65,10 +95,72 @@ impl<'a> AddCommand<'a> {
         };
 
         if is_dir {
-            self.add_directory(user_file, add_to_git_ignore)
+            self.add_directory(user_file, git_ignore)
+        } else {
+            self.add_single_file(user_file, git_ignore)
+        }
+    }
+
+    /// Expands the glob `pattern` (relative to the root of the project that `current_dir`
+    /// belongs to) and adds every matching path.
+    /// Paths that are already managed, either individually or as part of a managed
+    /// directory, are skipped. Errors of individual paths are printed and counted.
+    pub fn add_glob(
+        &self,
+        pattern: &str,
+        current_dir: &Path,
+        git_ignore: Option<IgnoreTarget>,
+        include_default_excluded: bool,
+    ) -> Result<GlobAddSummary> {
+        let (_, project_root) = self.locations_provider.find_project_for_path(current_dir)?;
+        let full_pattern = project_root.join(pattern);
+        let excluded_dirs: &[&str] = if include_default_excluded {
+            &[]
         } else {
-            self.add_single_file(user_file, add_to_git_ignore)
+            &DEFAULT_EXCLUDED_DIRS
+        };
+        let matches = patterns::expand(&full_pattern.to_string_lossy(), excluded_dirs)?;
+        if matches.is_empty() {
+            bail!("Pattern '{}' did not match any files.", pattern);
+        }
+
+        let mut summary = GlobAddSummary::default();
+        for path in matches {
+            let result = self.is_already_managed(&path).and_then(|managed| {
+                if !managed {
+                    self.add_file(path, current_dir, git_ignore, false)?;
+                }
+                Ok(managed)
+            });
+            match result {
+                Ok(true) => summary.skipped += 1,
+                Ok(false) => summary.added += 1,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    summary.failed += 1;
+                }
+            }
         }
+
+        println!(
+            "Pattern '{pattern}': {} added, {} skipped (already managed), {} failed.",
+            summary.added, summary.skipped, summary.failed
+        );
+
+        Ok(summary)
+    }
+
+    /// Checks whether the path is a puff symlink or lies inside a managed directory
+    fn is_already_managed(&self, path: &Path) -> Result<bool> {
+        let (_, _, managed_dir, relative_path) = self.resolve_project(path)?;
+
+        if let Ok(target) = fs::read_link(path)
+            && target.starts_with(&managed_dir)
+        {
+            return Ok(true);
+        }
+
+        Ok(managed_dirs::is_inside_managed_dir(&managed_dir, &relative_path)?.is_some())
     }
 
     fn resolve_project(&self, path: &Path) -> Result<(String, PathBuf, PathBuf, PathBuf)> {
@@ -89,7 +181,7 @@ impl<'a> AddCommand<'a> {
         Ok((project_name, project_root, managed_dir, relative_path))
     }
 
-    fn add_directory(&self, user_path: PathBuf, add_to_git_ignore: bool) -> Result<()> {
+    fn add_directory(&self, user_path: PathBuf, git_ignore: Option<IgnoreTarget>) -> Result<()> {
         let (project_name, project_root, managed_dir, relative_path) =
             self.resolve_project(&user_path)?;
         if let Some(parent_managed) =
@@ -115,6 +207,7 @@ impl<'a> AddCommand<'a> {
         } else {
             fs::create_dir_all(&managed_target)?;
         }
+        restrict_managed(&managed_target, self.file_mode)?;
 
         // Create directory symlink
         if user_path.exists() || user_path.symlink_metadata().is_ok() {
@@ -127,16 +220,11 @@ impl<'a> AddCommand<'a> {
         symlink_dir(&managed_target, &user_path)?;
 
         managed_dirs::add_managed_dir(&managed_dir, &relative_path)?;
+        ProjectMetadata::record_item(&managed_dir, &relative_path)?;
 
-        if add_to_git_ignore {
+        if let Some(target) = git_ignore {
             let handler = GitIgnoreHandler::new();
-            let dir_name = relative_path.display().to_string();
-            let gitignore_entry = if dir_name.ends_with('/') {
-                dir_name
-            } else {
-                format!("{}/", dir_name)
-            };
-            handler.add_to_git_ignore(&project_root, &gitignore_entry)?;
+            handler.ignore_managed_path(&project_root, &managed_dir, &relative_path, target)?;
         }
 
         println!(
@@ -154,6 +242,21 @@ impl<'a> AddCommand<'a> {
         managed_target: &Path,
         relative_path: &Path,
     ) -> Result<()> {
+        // Excluded entries would be lost when the original directory gets removed below
+        let excludes = PuffIgnore::read(user_path)?;
+        let mut excluded = vec![];
+        find_excluded_entries(user_path, user_path, managed_dir, &excludes, &mut excluded)?;
+        excluded.sort();
+        if let Some(first) = excluded.first() {
+            bail!(
+                "'{}' contains {} entries excluded by its .puffignore (e.g. '{}'). \
+                Move or delete them first so that they don't get lost.",
+                relative_path.display(),
+                excluded.len(),
+                first.display()
+            );
+        }
+
         fs::create_dir_all(managed_target)?;
 
         // Walk the user directory. For each entry:
@@ -236,14 +339,8 @@ impl<'a> AddCommand<'a> {
         Ok(())
     }
 
-    fn add_single_file(&self, user_file: PathBuf, add_to_git_ignore: bool) -> Result<()> {
-        let file_name = user_file
-            .file_name()
-            .ok_or_else(|| anyhow!("Couldn't retrieve file name"))?;
-        let user_dir = user_file
-            .parent()
-            .ok_or_else(|| anyhow!("Could not retrieve user's project directory"))?;
-        let (project_name, _project_root, managed_dir, ref relative_path) =
+    fn add_single_file(&self, user_file: PathBuf, git_ignore: Option<IgnoreTarget>) -> Result<()> {
+        let (project_name, project_root, managed_dir, ref relative_path) =
             self.resolve_project(&user_file)?;
 
         if let Some(parent_managed) =
@@ -261,28 +358,26 @@ impl<'a> AddCommand<'a> {
 
         fs::create_dir_all(managed_file.parent().unwrap())?;
 
-        if user_file.exists() && managed_file.exists() {
-            return AddCommand::handle_two_files(&user_file, &managed_file);
-        }
-
         let mut message = String::from("");
-        if !user_file.exists() && managed_file.exists() {
+        if user_file.exists() && managed_file.exists() {
+            if !self.handle_two_files(&user_file, &managed_dir, relative_path)? {
+                return Ok(());
+            }
+        } else if !user_file.exists() && managed_file.exists() {
             AddCommand::handle_only_managed_exists(&managed_file, &user_file)?;
             message = "It was symlinked to an existing file managed by puff.".to_string();
         } else if user_file.exists() {
             AddCommand::handle_only_user_file_exists(&user_file, &managed_file)?;
+            restrict_managed(&managed_file, self.file_mode)?;
         } else {
             AddCommand::handle_fresh_file(&user_file, &managed_file)?;
+            restrict_managed(&managed_file, self.file_mode)?;
         }
+        ProjectMetadata::record_item(&managed_dir, relative_path)?;
 
-        if add_to_git_ignore {
+        if let Some(target) = git_ignore {
             let handler = GitIgnoreHandler::new();
-            handler.add_to_git_ignore(
-                user_dir,
-                file_name
-                    .to_str()
-                    .ok_or_else(|| anyhow!("File name could not be parsed"))?,
-            )?;
+            handler.ignore_managed_path(&project_root, &managed_dir, relative_path, target)?;
         }
 
         println!(
@@ -298,28 +393,41 @@ impl<'a> AddCommand<'a> {
         Ok(())
     }
 
-    fn handle_two_files(user_file: &Path, managed_file: &Path) -> Result<()> {
+    /// Resolves a file that exists both in the project and in puff. Returns whether the
+    /// file got linked and should be recorded as added.
+    fn handle_two_files(
+        &self,
+        user_file: &Path,
+        managed_dir: &Path,
+        relative_path: &Path,
+    ) -> Result<bool> {
+        let managed_file = managed_dir.join(relative_path);
         if let Ok(symlink_path) = fs::read_link(user_file)
             && symlink_path == managed_file
         {
             println!("{:?} is already managed by puff. Nothing to do.", user_file);
-            return Ok(());
+            return Ok(false);
         }
 
         let metadata = fs::metadata(user_file);
         if let Ok(metadata) = metadata {
             if metadata.file_type().is_dir() {
                 bail!("{:?} is a directory, not a file.", user_file);
-            } else {
-                bail!(
-                    "Conflict: {:?} exists in both the project directory and puff's registry. \
-                    Rename the local file and re-run the command to resolve the conflict.",
-                    user_file
-                );
             }
         } else {
             bail!("Could not access {:?}.", user_file);
         }
+
+        match self
+            .conflict_resolver
+            .resolve(user_file, managed_dir, relative_path)?
+        {
+            Resolution::Link => {
+                symlink_file(&managed_file, user_file)?;
+                Ok(true)
+            }
+            Resolution::Skip => Ok(false),
+        }
     }
 
     fn handle_fresh_file(user_file: &Path, managed_file: &Path) -> Result<()> {
@@ -339,6 +447,38 @@ impl<'a> AddCommand<'a> {
     }
 }
 
+/// Outcome of adding all paths matched by a glob pattern
+#[derive(Default, Debug)]
+pub struct GlobAddSummary {
+    pub added: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Collects the entries of `dir` that the `.puffignore` of the added directory `root` excludes.
+/// Files managed by puff are never excluded, even if they match a pattern.
+fn find_excluded_entries(
+    dir: &Path,
+    root: &Path,
+    managed_dir: &Path,
+    excludes: &PuffIgnore,
+    excluded: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if fs::read_link(&path).is_ok_and(|target| target.starts_with(managed_dir)) {
+            continue;
+        }
+        let relative = path.strip_prefix(root)?;
+        if excludes.is_excluded(relative, path.is_dir()) {
+            excluded.push(relative.to_path_buf());
+        } else if path.is_dir() && !path.is_symlink() {
+            find_excluded_entries(&path, root, managed_dir, excludes, excluded)?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -362,12 +502,12 @@ mod tests {
 
         let user_file = current_dir.path().join("file");
         let config_file = config_dir.path().join("config.json");
-        let config = AppConfig { projects: vec![] };
+        let config = AppConfig::default();
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
         let sut = AddCommand::new(&locations_provider);
 
-        let result = sut.add_file(user_file, current_dir.path(), false, false);
+        let result = sut.add_file(user_file, current_dir.path(), None, false);
 
         assert!(result.is_err());
         let message = result.unwrap_err().to_string();
@@ -394,12 +534,13 @@ mod tests {
                 id: "1".into(),
                 path: current_dir.path().to_path_buf(),
             }],
+            ..Default::default()
         };
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
         let sut = AddCommand::new(&locations_provider);
 
-        sut.add_file(user_file, current_dir.path(), false, false)
+        sut.add_file(user_file, current_dir.path(), None, false)
             .unwrap();
     }
 
@@ -421,6 +562,7 @@ mod tests {
                 id: "1".into(),
                 path: project_root.path().to_path_buf(),
             }],
+            ..Default::default()
         };
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
@@ -429,7 +571,7 @@ mod tests {
         let user_file = subdir.join("secrets.env");
 
         let sut = AddCommand::new(&locations_provider);
-        sut.add_file(user_file, project_root.path(), false, false)
+        sut.add_file(user_file, project_root.path(), None, false)
             .unwrap();
 
         let managed_file = data_dir.path().join("projects/proj1/config/secrets.env");
@@ -454,6 +596,7 @@ mod tests {
                 id: "1".into(),
                 path: project_root.path().to_path_buf(),
             }],
+            ..Default::default()
         };
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
@@ -465,7 +608,7 @@ mod tests {
         sut.add_file(
             std::path::PathBuf::from("secrets.env"),
             &subdir,
-            false,
+            None,
             false,
         )
         .unwrap();
@@ -495,12 +638,13 @@ mod tests {
                 id: "1".into(),
                 path: project_root.path().to_path_buf(),
             }],
+            ..Default::default()
         };
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
         let dir_path = project_root.path().join("secrets");
         let sut = AddCommand::new(&locations_provider);
-        sut.add_file(dir_path.clone(), project_root.path(), false, true)
+        sut.add_file(dir_path.clone(), project_root.path(), None, true)
             .unwrap();
 
         assert!(dir_path.is_symlink());
@@ -526,6 +670,7 @@ mod tests {
                 id: "1".into(),
                 path: project_root.path().to_path_buf(),
             }],
+            ..Default::default()
         };
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
@@ -535,7 +680,7 @@ mod tests {
         fs::write(dir_path.join("app.env"), "APP_KEY=secret").unwrap();
 
         let sut = AddCommand::new(&locations_provider);
-        sut.add_file(dir_path.clone(), project_root.path(), false, false)
+        sut.add_file(dir_path.clone(), project_root.path(), None, false)
             .unwrap();
 
         assert!(dir_path.is_symlink());
@@ -569,6 +714,7 @@ mod tests {
                 id: "1".into(),
                 path: project_root.path().to_path_buf(),
             }],
+            ..Default::default()
         };
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
@@ -576,11 +722,64 @@ mod tests {
         fs::write(&file_path, "content").unwrap();
 
         let sut = AddCommand::new(&locations_provider);
-        let result = sut.add_file(file_path, project_root.path(), false, true);
+        let result = sut.add_file(file_path, project_root.path(), None, true);
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("--dir"));
     }
 
+    #[test]
+    fn add_glob_adds_nested_matches_and_skips_managed_ones() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let project_root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(data_dir.path().join("projects/proj1")).unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+
+        let config_file = config_dir.path().join("config.json");
+        let config = AppConfig {
+            projects: vec![Project {
+                name: "proj1".into(),
+                id: "1".into(),
+                path: project_root.path().to_path_buf(),
+            }],
+            ..Default::default()
+        };
+        fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
+
+        fs::create_dir_all(project_root.path().join("apps/web")).unwrap();
+        fs::create_dir_all(project_root.path().join("node_modules/pkg")).unwrap();
+        fs::write(project_root.path().join(".env"), "ROOT=1").unwrap();
+        fs::write(project_root.path().join("apps/web/.env"), "WEB=1").unwrap();
+        fs::write(project_root.path().join("node_modules/pkg/.env"), "PKG=1").unwrap();
+
+        let sut = AddCommand::new(&locations_provider);
+        sut.add_file(
+            project_root.path().join(".env"),
+            project_root.path(),
+            None,
+            false,
+        )
+        .unwrap();
+
+        let summary = sut
+            .add_glob("**/.env", project_root.path(), None, false)
+            .unwrap();
+
+        assert_eq!(1, summary.added);
+        assert_eq!(1, summary.skipped);
+        assert_eq!(0, summary.failed);
+        assert!(project_root.path().join("apps/web/.env").is_symlink());
+        assert!(
+            !project_root
+                .path()
+                .join("node_modules/pkg/.env")
+                .is_symlink()
+        );
+    }
+
     #[test]
     fn add_file_inside_managed_dir_fails() {
         let config_dir = tempfile::tempdir().unwrap();
@@ -599,6 +798,7 @@ mod tests {
                 id: "1".into(),
                 path: project_root.path().to_path_buf(),
             }],
+            ..Default::default()
         };
         fs::write(&config_file, serde_json::to_string(&config).unwrap()).unwrap();
 
@@ -606,12 +806,12 @@ mod tests {
         let dir_path = project_root.path().join("config");
         fs::create_dir_all(&dir_path).unwrap();
         let sut = AddCommand::new(&locations_provider);
-        sut.add_file(dir_path, project_root.path(), false, false)
+        sut.add_file(dir_path, project_root.path(), None, false)
             .unwrap();
 
         // Now try to add a file inside it
         let file_inside = project_root.path().join("config/db.env");
-        let result = sut.add_file(file_inside, project_root.path(), false, false);
+        let result = sut.add_file(file_inside, project_root.path(), None, false);
         assert!(result.is_err());
         assert!(
             result
@@ -0,0 +1,253 @@
+use anyhow::{Result, bail};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    backups::{Backup, BackupStore, find_forgotten_project, projects_with_backups, remove_expired},
+    config::{locations::LocationsProvider, project_metadata::ProjectMetadata, settings::Settings},
+    io_utils::confirm,
+    output::format_time,
+};
+
+/// Handler for `puff backups`, which manages the backups that puff makes of the files it
+/// replaces
+pub struct BackupsCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    settings: &'a Settings,
+}
+
+impl<'a> BackupsCommand<'a> {
+    pub fn new(locations_provider: &'a LocationsProvider, settings: &'a Settings) -> Self {
+        BackupsCommand {
+            locations_provider,
+            settings,
+        }
+    }
+
+    /// Lists the backups of `project`, of the project of `current_dir` without one, or of
+    /// all projects outside of a project
+    pub fn list(&self, project: Option<&str>, current_dir: &Path) -> Result<()> {
+        let mut any = false;
+        for (name, id) in self.projects(project, current_dir)? {
+            let backups = self.store(&id).list()?;
+            if backups.is_empty() {
+                continue;
+            }
+            any = true;
+            println!("Project '{name}':");
+            for backup in backups {
+                println!(
+                    "  {}  {}{}  ({}, from {})",
+                    backup.id,
+                    backup.path.display(),
+                    if backup.directory { "/" } else { "" },
+                    format_time(created(&backup)),
+                    backup.original_path.display()
+                );
+            }
+        }
+        if !any {
+            println!("No backups.");
+        }
+        Ok(())
+    }
+
+    /// Copies the latest backup of `path` (or the one with `id`) to where it was taken
+    /// from, or to `to`. Without a project, `path` is relative to `current_dir`; with one,
+    /// it's relative to the project's root. Existing files are never overwritten.
+    pub fn restore(
+        &self,
+        path: &Path,
+        id: Option<&str>,
+        to: Option<&Path>,
+        project: Option<&str>,
+        current_dir: &Path,
+    ) -> Result<()> {
+        let (name, relative_path) = match project {
+            Some(name) => (name.to_owned(), path.to_path_buf()),
+            None => {
+                let user_path: PathBuf = current_dir.join(path).components().collect();
+                let (name, root) = self.locations_provider.find_project_for_path(&user_path)?;
+                let relative_path = user_path.strip_prefix(&root)?.to_path_buf();
+                (name, relative_path)
+            }
+        };
+
+        let store = self.store(&self.project_id(&name)?);
+        let Some(backup) = store
+            .list()?
+            .into_iter()
+            .rfind(|b| b.path == relative_path && id.is_none_or(|id| b.id == id))
+        else {
+            bail!(
+                "No backup of '{}' found in project '{name}'. Run 'puff backups list' to see the available backups.",
+                relative_path.display()
+            );
+        };
+
+        let destination = match to {
+            Some(to) => current_dir.join(to),
+            None => backup.original_path.clone(),
+        };
+        if destination.symlink_metadata().is_ok() {
+            bail!(
+                "'{}' exists. Remove it first, or use --to to restore the backup somewhere else.",
+                destination.display()
+            );
+        }
+        store.restore(&backup, &destination)?;
+        println!(
+            "Restored backup {} of '{}' to {}.",
+            backup.id,
+            relative_path.display(),
+            destination.display()
+        );
+        Ok(())
+    }
+
+    /// Removes the backups (only the ones older than `older_than` days, if given) of the
+    /// same projects that `list` shows. Backups of any project that are older than the
+    /// retention setting allows are removed first.
+    pub fn clean(
+        &self,
+        older_than: Option<u32>,
+        project: Option<&str>,
+        current_dir: &Path,
+        skip_confirmation: bool,
+    ) -> Result<()> {
+        let cutoff = older_than.map(|days| {
+            SystemTime::now()
+                .checked_sub(Duration::from_secs(u64::from(days) * 86_400))
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        });
+        let is_removed = |backup: &Backup| cutoff.is_none_or(|cutoff| created(backup) < cutoff);
+
+        let expired = remove_expired(self.locations_provider, self.settings)?;
+        if expired > 0 {
+            println!("Removed {expired} expired backup(s).");
+        }
+
+        let mut stores = vec![];
+        let mut count = 0;
+        for (_, id) in self.projects(project, current_dir)? {
+            let store = self.store(&id);
+            count += store.list()?.iter().filter(|b| is_removed(b)).count();
+            stores.push(store);
+        }
+        if count == 0 {
+            println!("No backups to remove.");
+            return Ok(());
+        }
+        if !skip_confirmation && !confirm(format!("Remove {count} backup(s)?"))? {
+            return Ok(());
+        }
+
+        for store in stores {
+            store.remove(is_removed)?;
+        }
+        println!("Removed {count} backup(s).");
+        Ok(())
+    }
+
+    /// Names and ids of the projects whose backups the commands work with. Backups of
+    /// projects that are no longer in puff are shown under the name recorded in them.
+    fn projects(&self, project: Option<&str>, current_dir: &Path) -> Result<Vec<(String, String)>> {
+        let name = match project {
+            Some(name) => Some(name.to_owned()),
+            None => self
+                .locations_provider
+                .find_project_for_path(current_dir)
+                .ok()
+                .map(|(name, _)| name),
+        };
+        if let Some(name) = name {
+            let id = self.project_id(&name)?;
+            return Ok(vec![(name, id)]);
+        }
+
+        let projects =
+            ProjectMetadata::read_all(&self.locations_provider.get_projects_data_path())?;
+        let backups_path = self.locations_provider.get_backups_path();
+        let mut result = vec![];
+        for id in projects_with_backups(&backups_path)? {
+            let name = match projects.iter().find(|(_, metadata)| metadata.id == id) {
+                Some((name, _)) => name.clone(),
+                None => BackupStore::new(&backups_path, &id)
+                    .list()?
+                    .pop()
+                    .and_then(|b| b.project)
+                    .unwrap_or_else(|| id.clone()),
+            };
+            result.push((name, id));
+        }
+        Ok(result)
+    }
+
+    /// Id of the project called `name`, which may also be a project that is no longer in
+    /// puff. Names that match no project are taken as ids, as `list` shows them.
+    fn project_id(&self, name: &str) -> Result<String> {
+        let managed_dir = self.locations_provider.get_managed_dir(name);
+        if let Some(metadata) = ProjectMetadata::read(&managed_dir)? {
+            return Ok(metadata.id);
+        }
+        let backups_path = self.locations_provider.get_backups_path();
+        Ok(find_forgotten_project(&backups_path, name)?.unwrap_or_else(|| name.to_owned()))
+    }
+
+    fn store(&self, project_id: &str) -> BackupStore {
+        BackupStore::new(&self.locations_provider.get_backups_path(), project_id)
+    }
+}
+
+fn created(backup: &Backup) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(backup.created_at)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BackupsCommand;
+    use crate::{
+        backups::BackupStore,
+        config::{locations::LocationsProvider, settings::Settings},
+    };
+    use std::{fs, path::Path};
+
+    #[test]
+    fn restore_copies_latest_backup_without_overwriting() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        let project_dir = fs::canonicalize(user_dir.path()).unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        fs::write(
+            locations_provider.get_config_file_path(),
+            format!(
+                r#"{{"projects":[{{"name":"app","id":"1","path":"{}"}}]}}"#,
+                project_dir.display()
+            ),
+        )
+        .unwrap();
+        let original = project_dir.join(".env");
+        let store = BackupStore::new(&locations_provider.get_backups_path(), "app");
+        fs::write(&original, "A=old").unwrap();
+        store.backup(&original, Path::new(".env")).unwrap();
+        fs::write(&original, "A=new").unwrap();
+        store.backup(&original, Path::new(".env")).unwrap();
+        let settings = Settings::default();
+        let command = BackupsCommand::new(&locations_provider, &settings);
+
+        let result = command.restore(Path::new(".env"), None, None, None, &project_dir);
+        assert!(result.is_err());
+
+        fs::remove_file(&original).unwrap();
+        command
+            .restore(Path::new(".env"), None, None, None, &project_dir)
+            .unwrap();
+        assert_eq!("A=new", fs::read_to_string(&original).unwrap());
+    }
+}
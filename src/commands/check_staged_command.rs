@@ -0,0 +1,223 @@
+use anyhow::{Result, bail};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::projects::{ProjectDetails, ProjectInfo, ProjectsRetriever},
+    dotenv,
+    git::GitRepository,
+    managed_dirs,
+};
+
+/// Handler for the `puff check-staged` command, run by the pre-commit hook that
+/// `puff hooks install` writes. Fails when staged changes would commit puff-managed data.
+pub struct CheckStagedCommand<'a> {
+    projects_retriever: &'a ProjectsRetriever<'a>,
+}
+
+/// A value from one of a project's managed dotenv files
+struct Secret {
+    key: String,
+    value: String,
+    /// The dotenv file, relative to the managed dir
+    file: PathBuf,
+}
+
+impl<'a> CheckStagedCommand<'a> {
+    pub fn new(projects_retriever: &'a ProjectsRetriever<'a>) -> Self {
+        CheckStagedCommand { projects_retriever }
+    }
+
+    pub fn check(&self, cwd: &Path) -> Result<()> {
+        let Some(repository) = GitRepository::discover(cwd)? else {
+            bail!("'{}' is not inside a git repository.", cwd.display());
+        };
+        let staged = repository.staged_paths()?;
+        if staged.is_empty() {
+            return Ok(());
+        }
+
+        let mut problems = vec![];
+        let mut flagged: Vec<&PathBuf> = vec![];
+        let mut unreadable: Vec<&PathBuf> = vec![];
+        // Submodules have no content to check
+        let gitlinks = repository.staged_gitlinks().unwrap_or_default();
+        for name in self.projects_retriever.get_associated_projects() {
+            let Some(ProjectDetails::Associated(project)) =
+                self.projects_retriever.get_details(&name)?
+            else {
+                continue;
+            };
+            // The project can be checked out in any worktree of the repository
+            let Some(offset) = repository.relative_to_worktrees(&project.user_dir)? else {
+                continue;
+            };
+
+            for path in &staged {
+                let Ok(relative) = path.strip_prefix(&offset) else {
+                    continue;
+                };
+                let Some(item) = project
+                    .info
+                    .items
+                    .iter()
+                    .find(|i| relative.starts_with(i.path()))
+                else {
+                    continue;
+                };
+
+                if item.path() == relative {
+                    problems.push(format!(
+                        "'{}' is managed by puff (project '{name}')",
+                        path.display()
+                    ));
+                } else {
+                    problems.push(format!(
+                        "'{}' is inside directory '{}' managed by puff (project '{name}')",
+                        path.display(),
+                        item.path().display()
+                    ));
+                }
+                flagged.push(path);
+            }
+
+            let secrets = collect_secrets(&project.info)?;
+            if secrets.is_empty() {
+                continue;
+            }
+            for path in &staged {
+                if flagged.contains(&path) || unreadable.contains(&path) || gitlinks.contains(path)
+                {
+                    continue;
+                }
+                let content = match repository.staged_content(path) {
+                    Ok(content) => content,
+                    Err(e) => {
+                        eprintln!(
+                            "puff: could not check '{}' for secrets: {e}",
+                            path.display()
+                        );
+                        unreadable.push(path);
+                        continue;
+                    }
+                };
+                let content = String::from_utf8_lossy(&content);
+                if let Some(secret) = secrets.iter().find(|s| content.contains(&s.value)) {
+                    problems.push(format!(
+                        "'{}' contains the value of {} from '{}' (project '{name}')",
+                        path.display(),
+                        secret.key,
+                        secret.file.display()
+                    ));
+                    flagged.push(path);
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        eprintln!("puff: the staged changes contain data managed by puff:");
+        for problem in &problems {
+            eprintln!("  {problem}");
+        }
+        bail!(
+            "Commit blocked ({} problem(s)). Unstage the listed paths, or use `git commit --no-verify` to skip this check.",
+            problems.len()
+        );
+    }
+}
+
+/// Reads the values of all dotenv files in the project's managed dir, including the ones
+/// inside managed directories. Short values are skipped, as they would match too often.
+fn collect_secrets(info: &ProjectInfo) -> Result<Vec<Secret>> {
+    let mut secrets = vec![];
+    let mut stack = vec![info.managed_dir.clone()];
+    while let Some(current) = stack.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if managed_dirs::is_metadata_file(&info.managed_dir, &path) {
+                continue;
+            }
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            if !dotenv::is_dotenv_file(&path) {
+                continue;
+            }
+
+            let file = path.strip_prefix(&info.managed_dir)?.to_owned();
+            let content = fs::read(&path)?;
+            for (key, value) in dotenv::parse(&String::from_utf8_lossy(&content)) {
+                if value.len() >= dotenv::MIN_SECRET_LENGTH {
+                    secrets.push(Secret {
+                        key,
+                        value,
+                        file: file.clone(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(secrets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::collect_secrets;
+    use crate::config::{
+        app_config::{AppConfig, Project},
+        locations::LocationsProvider,
+        projects::ProjectsRetriever,
+    };
+    use std::fs;
+
+    #[test]
+    fn collect_secrets_reads_long_values_of_dotenv_files() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        let managed_dir = data_dir.path().join("projects/proj");
+        fs::create_dir_all(managed_dir.join("config")).unwrap();
+        fs::write(
+            managed_dir.join(".env"),
+            "API_KEY=abcdef123456\nPORT=3000\n",
+        )
+        .unwrap();
+        fs::write(managed_dir.join("config/prod.env"), "TOKEN='0123456789'\n").unwrap();
+        fs::write(managed_dir.join("settings.json"), "SECRET=not-a-dotenv\n").unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let app_config = AppConfig {
+            projects: vec![Project {
+                name: "proj".to_owned(),
+                id: "1".to_owned(),
+                path: user_dir.path().to_path_buf(),
+            }],
+            ..Default::default()
+        };
+        let retriever = ProjectsRetriever::new(app_config, &locations_provider);
+        let details = retriever.get_details("proj").unwrap().unwrap();
+
+        let mut result: Vec<(String, String)> = collect_secrets(details.info())
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.key, s.value))
+            .collect();
+        result.sort();
+
+        assert_eq!(
+            vec![
+                ("API_KEY".to_owned(), "abcdef123456".to_owned()),
+                ("TOKEN".to_owned(), "0123456789".to_owned()),
+            ],
+            result
+        );
+    }
+}
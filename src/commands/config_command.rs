@@ -0,0 +1,239 @@
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::config::{
+    app_config::{AppConfig, AppConfigManager, Project, is_valid_id},
+    locations::LocationsProvider,
+    project_metadata::ProjectMetadata,
+    roots::{PathMapper, PathRewrite, validate_root_name},
+    settings::SettingKey,
+};
+
+/// Handler for the `puff config` command
+pub struct ConfigCommand<'a> {
+    app_config_manager: &'a AppConfigManager,
+    locations_provider: &'a LocationsProvider,
+}
+
+impl<'a> ConfigCommand<'a> {
+    pub fn new(
+        app_config_manager: &'a AppConfigManager,
+        locations_provider: &'a LocationsProvider,
+    ) -> Self {
+        ConfigCommand {
+            app_config_manager,
+            locations_provider,
+        }
+    }
+
+    /// Lists the settings. With a project, lists the values that apply to it and marks
+    /// the ones it overrides.
+    pub fn list(&self, project: Option<&str>) -> Result<()> {
+        let settings = self.app_config_manager.get_config()?.settings;
+        let project_settings = match project {
+            Some(name) => Some(ProjectMetadata::load(&self.managed_dir(name)?)?.settings),
+            None => None,
+        };
+        for key in SettingKey::value_variants() {
+            match project_settings.as_ref().and_then(|s| s.get(*key)) {
+                Some(value) => println!("{} = {value} (project)", key.name()),
+                None => println!("{} = {}", key.name(), settings.get(*key)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Prints the value of a setting, the one that applies to `project` if given
+    pub fn get(&self, key: SettingKey, project: Option<&str>) -> Result<()> {
+        let mut settings = self.app_config_manager.get_config()?.settings;
+        if let Some(name) = project {
+            settings = ProjectMetadata::load(&self.managed_dir(name)?)?
+                .settings
+                .apply(&settings);
+        }
+        println!("{}", settings.get(key));
+        Ok(())
+    }
+
+    /// Changes a global setting or, with a project, overrides it for that project only
+    pub fn set(&self, key: SettingKey, value: &str, project: Option<&str>) -> Result<()> {
+        if let Some(name) = project {
+            let managed_dir = self.managed_dir(name)?;
+            let mut metadata = ProjectMetadata::load(&managed_dir)?;
+            metadata.settings.set(key, value)?;
+            metadata.write(&managed_dir)?;
+            println!(
+                "{} = {} (project '{name}')",
+                key.name(),
+                metadata.settings.get(key).unwrap_or_default()
+            );
+            return Ok(());
+        }
+
+        let mut settings = self.app_config_manager.get_config()?.settings;
+        settings.set(key, value)?;
+        self.app_config_manager.save_settings(&settings)?;
+        println!("{} = {}", key.name(), settings.get(key));
+        Ok(())
+    }
+
+    /// Removes a project's override of a setting
+    pub fn unset(&self, key: SettingKey, project: &str) -> Result<()> {
+        ProjectMetadata::update(&self.managed_dir(project)?, |m| m.settings.unset(key))?;
+        let settings = self.app_config_manager.get_config()?.settings;
+        println!(
+            "{} = {} (global setting applies to project '{project}')",
+            key.name(),
+            settings.get(key)
+        );
+        Ok(())
+    }
+
+    fn managed_dir(&self, project: &str) -> Result<PathBuf> {
+        let managed_dir = self.locations_provider.get_managed_dir(project);
+        if !managed_dir.is_dir() {
+            bail!(
+                "Project '{}' was not found. Check 'puff list' for available projects.",
+                project
+            );
+        }
+        Ok(managed_dir)
+    }
+
+    pub fn list_roots(&self) -> Result<()> {
+        let roots = self.app_config_manager.get_config()?.roots;
+        if roots.is_empty() {
+            println!("No roots defined.");
+        }
+        for (name, path) in roots {
+            println!("{name} = {path}");
+        }
+        Ok(())
+    }
+
+    /// Defines (or redefines) a root. Paths of projects inside it get stored relative to it.
+    pub fn add_root(&self, name: &str, path: &Path) -> Result<()> {
+        validate_root_name(name)?;
+        let mut config = self.app_config_manager.get_config()?;
+        let value = PathMapper::new(&config.roots).root_value(path)?;
+        config.roots.insert(name.to_owned(), value.clone());
+        self.app_config_manager.save_config(&config)?;
+        println!("{name} = {value}");
+        Ok(())
+    }
+
+    pub fn remove_root(&self, name: &str) -> Result<()> {
+        let mut config = self.app_config_manager.get_config()?;
+        if config.roots.remove(name).is_none() {
+            bail!("Root '{}' is not defined.", name);
+        }
+        self.app_config_manager.save_config(&config)?;
+        println!("Removed root '{name}'.");
+        Ok(())
+    }
+
+    /// Adds the projects of a config.json from another machine to the local one. Their
+    /// paths go through the rewrite rules first and are then resolved against the local
+    /// home directory and roots. Roots missing locally are taken over as well. Projects
+    /// are matched with their data by id (by name for placeholder ids of old versions);
+    /// the ones whose data hasn't been copied to this machine are skipped.
+    pub fn import(&self, file: &Path, rewrites: &[PathRewrite]) -> Result<()> {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Could not read '{}'.", file.display()))?;
+        let imported: AppConfig = serde_json::from_str(&content)
+            .with_context(|| format!("'{}' is not a valid puff config.json.", file.display()))?;
+
+        let mut config = self.app_config_manager.get_config()?;
+        let local_mapper = PathMapper::new(&config.roots);
+        for (name, value) in imported.roots {
+            let rewritten = PathRewrite::apply_all(rewrites, Path::new(&value));
+            let value = local_mapper
+                .root_value(&rewritten)
+                .unwrap_or_else(|_| rewritten.to_string_lossy().into_owned());
+            match config.roots.get(&name) {
+                None => {
+                    println!("Added root {name} = {value}");
+                    config.roots.insert(name, value);
+                }
+                Some(local) if *local != value => {
+                    println!("Keeping the local root {name} = {local} (imported: {value})")
+                }
+                Some(_) => {}
+            }
+        }
+
+        let mapper = PathMapper::new(&config.roots);
+        let projects_path = self.locations_provider.get_projects_data_path();
+        let mut imported_count = 0;
+        let mut missing = vec![];
+        for project in imported.projects {
+            if config.projects.iter().any(|p| p.name == project.name) {
+                println!(
+                    "Skipped '{}', a project with this name is already registered.",
+                    project.name
+                );
+                continue;
+            }
+            let stored_name = if is_valid_id(&project.id) {
+                ProjectMetadata::find_name(&projects_path, &project.id)?
+            } else {
+                let managed_dir = self.locations_provider.get_managed_dir(&project.name);
+                managed_dir.is_dir().then(|| project.name.clone())
+            };
+            let Some(name) = stored_name else {
+                println!(
+                    "Skipped '{}', its data is missing in puff's data directory. Copy it from the other machine first.",
+                    project.name
+                );
+                continue;
+            };
+            let id = ProjectMetadata::load(&self.locations_provider.get_managed_dir(&name))?.id;
+            if let Some(existing) = config.projects.iter().find(|p| p.id == id) {
+                println!(
+                    "Skipped '{}', it is already registered as '{}'.",
+                    project.name, existing.name
+                );
+                continue;
+            }
+            if config.projects.iter().any(|p| p.name == name) {
+                println!(
+                    "Skipped '{}', it was renamed to '{}', which is the name of another registered project.",
+                    project.name, name
+                );
+                continue;
+            }
+
+            let path = mapper.expand(&PathRewrite::apply_all(rewrites, &project.path));
+            if name == project.name {
+                println!("Imported '{}' ({}).", name, path.display());
+            } else {
+                println!(
+                    "Imported '{}' as '{}', its current name ({}).",
+                    project.name,
+                    name,
+                    path.display()
+                );
+            }
+            if !path.exists() {
+                missing.push(name.clone());
+            }
+            config.projects.push(Project::with_id(&name, &id, &path));
+            imported_count += 1;
+        }
+
+        self.app_config_manager.save_config(&config)?;
+        println!("Imported {imported_count} project(s).");
+        if !missing.is_empty() {
+            println!(
+                "WARNING: the paths of these projects don't exist on this machine: {}. Use --rewrite FROM=TO or `puff config roots add` to map them.",
+                missing.join(", ")
+            );
+        }
+
+        Ok(())
+    }
+}
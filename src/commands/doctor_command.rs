@@ -0,0 +1,82 @@
+use anyhow::{Result, bail};
+
+use crate::{
+    config::{
+        locations::LocationsProvider,
+        projects::{ProjectDetails, ProjectHealth, ProjectsRetriever},
+        settings::FileMode,
+    },
+    ignore_coverage, permissions,
+};
+
+/// Handler for the `puff doctor` command. Checks every associated project for broken
+/// links and for managed items that git could commit, and puff's data for permissions
+/// that let other users read it.
+pub struct DoctorCommand<'a> {
+    projects_retriever: &'a ProjectsRetriever<'a>,
+    locations_provider: &'a LocationsProvider,
+    file_mode: FileMode,
+}
+
+impl<'a> DoctorCommand<'a> {
+    pub fn new(
+        projects_retriever: &'a ProjectsRetriever<'a>,
+        locations_provider: &'a LocationsProvider,
+        file_mode: FileMode,
+    ) -> Self {
+        DoctorCommand {
+            projects_retriever,
+            locations_provider,
+            file_mode,
+        }
+    }
+
+    /// With `fix`, permissions that let others access puff's data get removed instead of
+    /// being reported
+    pub fn check(&self, fix: bool) -> Result<()> {
+        let mut unhealthy = 0;
+        for name in self.projects_retriever.get_associated_projects() {
+            let Some(details) = self.projects_retriever.get_details(&name)? else {
+                println!("{name}: data directory missing (run 'puff reconcile' to fix it)");
+                unhealthy += 1;
+                continue;
+            };
+            let ProjectDetails::Associated(associated) = &details else {
+                continue;
+            };
+
+            let health = details.health().unwrap_or(ProjectHealth::Ok);
+            let uncovered = if associated.user_dir.is_dir() {
+                ignore_coverage::find_uncovered_items(&associated.user_dir, &details.info().items)?
+            } else {
+                vec![]
+            };
+
+            println!("{name}: {health}");
+            ignore_coverage::print_warning(&uncovered, &associated.user_dir);
+            if health != ProjectHealth::Ok || !uncovered.is_empty() {
+                unhealthy += 1;
+            }
+        }
+
+        let exposed = permissions::find_exposed_paths(self.locations_provider, self.file_mode)?;
+        if fix && !exposed.is_empty() {
+            permissions::fix(&exposed)?;
+            println!("Restricted the permissions of {} path(s).", exposed.len());
+        } else {
+            permissions::print_warning(&exposed);
+        }
+
+        if unhealthy > 0 {
+            bail!("Found problems in {unhealthy} project(s).");
+        }
+        if !fix && !exposed.is_empty() {
+            bail!(
+                "Other users can access {} of puff's path(s).",
+                exposed.len()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -5,15 +5,28 @@ use std::{
 };
 
 use crate::{
-    config::locations::LocationsProvider,
+    config::{locations::LocationsProvider, project_metadata::ProjectMetadata},
     fs_utils::{copy_dir_recursive, is_symlink, remove_dir_symlink},
+    git_ignore::GitIgnoreHandler,
     managed_dirs::{self, PathClassification},
+    shred::{ShredReport, shred_copies, shred_path},
+    trash::Trash,
 };
 
 pub struct ForgetCommand<'a> {
     locations_provider: &'a LocationsProvider,
 }
 
+/// What happened to puff's copy of a forgotten item
+enum Removal {
+    /// The copy was restored to the project directory
+    Restored,
+    /// The copy was moved to the trash, as the entry with this id
+    Trashed(String),
+    /// The copy was shredded, along with puff's other copies of the item
+    Shredded(ShredReport),
+}
+
 impl<'a> ForgetCommand<'a> {
     pub fn new(locations_provider: &'a LocationsProvider) -> ForgetCommand<'a> {
         ForgetCommand { locations_provider }
@@ -24,6 +37,7 @@ impl<'a> ForgetCommand<'a> {
         mut user_file: PathBuf,
         current_dir: &Path,
         delete_file: bool,
+        shred: bool,
     ) -> Result<()> {
         if !user_file.is_absolute() {
             user_file = current_dir.join(user_file);
@@ -39,13 +53,16 @@ impl<'a> ForgetCommand<'a> {
 
         match managed_dirs::classify_path(&managed_dir, relative_path)? {
             PathClassification::IsManaged => {
-                return self.forget_directory(
+                let removal = self.forget_directory(
                     &user_file,
                     &project_name,
                     &managed_dir,
                     relative_path,
                     delete_file,
-                );
+                    shred,
+                )?;
+                self.remove_git_ignore_entry(&project_root, &managed_dir, relative_path)?;
+                return removal.finish();
             }
             PathClassification::InsideManaged(parent_managed) => {
                 bail!(
@@ -85,9 +102,42 @@ impl<'a> ForgetCommand<'a> {
             self.copy_file(&user_file, &project_name, relative_path)?;
         }
 
-        self.remove_managed_file(&project_name, relative_path)?;
+        let removal =
+            self.remove_managed_item(&project_name, relative_path, &user_file, delete_file, shred)?;
+        // clean up empty subdirectory (ignore errors — dir may be non-empty or be the root)
+        if let Some(parent) = managed_dir.join(relative_path).parent()
+            && parent != managed_dir
+        {
+            let _ = fs::remove_dir(parent);
+        }
+        ProjectMetadata::forget_item(&managed_dir, relative_path)?;
 
-        println!("Restored {file_name:?} in project '{project_name}'.");
+        match &removal {
+            Removal::Restored => println!("Restored {file_name:?} in project '{project_name}'."),
+            Removal::Trashed(id) => println!(
+                "Moved {file_name:?} of project '{project_name}' to the trash. \
+                Run 'puff trash restore {id}' to get it back."
+            ),
+            Removal::Shredded(_) => println!("Deleted {file_name:?} of project '{project_name}'."),
+        }
+
+        self.remove_git_ignore_entry(&project_root, &managed_dir, relative_path)?;
+        removal.finish()
+    }
+
+    /// Removes the .gitignore (or .git/info/exclude) entry that puff added for the path, if there is one
+    fn remove_git_ignore_entry(
+        &self,
+        project_root: &Path,
+        managed_dir: &Path,
+        relative_path: &Path,
+    ) -> Result<()> {
+        let handler = GitIgnoreHandler::new();
+        if let Some(target) =
+            handler.unignore_managed_path(project_root, managed_dir, relative_path)?
+        {
+            println!("Removed '{}' from {target}.", relative_path.display());
+        }
         Ok(())
     }
 
@@ -98,7 +148,8 @@ impl<'a> ForgetCommand<'a> {
         managed_dir: &Path,
         relative_path: &Path,
         delete_file: bool,
-    ) -> Result<()> {
+        shred: bool,
+    ) -> Result<Removal> {
         let managed_target = managed_dir.join(relative_path);
 
         if !managed_target.exists() {
@@ -111,23 +162,31 @@ impl<'a> ForgetCommand<'a> {
         }
 
         if !delete_file {
-            // Restore: copy directory back from managed store
+            // Restore: copy directory back from managed store, including the entries that
+            // its .puffignore excludes, as puff's copy gets removed
             copy_dir_recursive(&managed_target, user_path)?;
         }
 
         // Remove from data store
-        fs::remove_dir_all(&managed_target)?;
+        let removal =
+            self.remove_managed_item(project_name, relative_path, user_path, delete_file, shred)?;
 
         // Remove from .puff_managed_dirs
         managed_dirs::remove_managed_dir(managed_dir, relative_path)?;
+        ProjectMetadata::forget_item(managed_dir, relative_path)?;
 
         let dir_name = relative_path.display();
-        if delete_file {
-            println!("Removed '{dir_name}/' from project '{project_name}'.");
-        } else {
-            println!("Restored '{dir_name}/' in project '{project_name}'.");
+        match &removal {
+            Removal::Restored => println!("Restored '{dir_name}/' in project '{project_name}'."),
+            Removal::Trashed(id) => println!(
+                "Moved '{dir_name}/' of project '{project_name}' to the trash. \
+                Run 'puff trash restore {id}' to get it back."
+            ),
+            Removal::Shredded(_) => {
+                println!("Deleted '{dir_name}/' of project '{project_name}'.")
+            }
         }
-        Ok(())
+        Ok(removal)
     }
 
     fn is_file_added(&self, project_name: &str, relative_path: &Path) -> Result<bool> {
@@ -144,18 +203,60 @@ impl<'a> ForgetCommand<'a> {
         Ok(())
     }
 
-    fn remove_managed_file(&self, project_name: &str, relative_path: &Path) -> Result<()> {
-        let managed_dir = self.locations_provider.get_managed_dir(project_name);
-        let managed_file = managed_dir.join(relative_path);
-        fs::remove_file(&managed_file)?;
+    /// Removes puff's copy of the item at `relative_path`. A deleted item's copy is moved to
+    /// the trash, or with `shred`, shredded along with its backups and trash entries.
+    fn remove_managed_item(
+        &self,
+        project_name: &str,
+        relative_path: &Path,
+        user_path: &Path,
+        delete_file: bool,
+        shred: bool,
+    ) -> Result<Removal> {
+        let managed_path = self
+            .locations_provider
+            .get_managed_dir(project_name)
+            .join(relative_path);
+        if !delete_file {
+            if managed_path.is_dir() {
+                fs::remove_dir_all(&managed_path)?;
+            } else {
+                fs::remove_file(&managed_path)?;
+            }
+            return Ok(Removal::Restored);
+        }
 
-        // clean up empty subdirectory (ignore errors — dir may be non-empty or be the root)
-        if let Some(parent) = managed_file.parent()
-            && parent != managed_dir
-        {
-            let _ = fs::remove_dir(parent);
+        let managed_dir = self.locations_provider.get_managed_dir(project_name);
+        let project_id = ProjectMetadata::load(&managed_dir)?.id;
+        if shred {
+            let mut report = ShredReport::default();
+            shred_path(&managed_path, &mut report)?;
+            shred_copies(
+                self.locations_provider,
+                &project_id,
+                Some(relative_path),
+                &mut report,
+            )?;
+            return Ok(Removal::Shredded(report));
         }
 
-        Ok(())
+        let entry = Trash::new(self.locations_provider.get_trash_path()).trash_item(
+            project_name,
+            &project_id,
+            &managed_path,
+            relative_path,
+            Some(user_path),
+        )?;
+        Ok(Removal::Trashed(entry.id))
+    }
+}
+
+impl Removal {
+    /// Reports what could not be shredded securely, if the copy was shredded
+    fn finish(self) -> Result<()> {
+        match self {
+            Removal::Shredded(report) => report.finish(),
+            _ => Ok(()),
+        }
     }
 }
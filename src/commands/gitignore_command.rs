@@ -0,0 +1,65 @@
+use anyhow::{Result, bail};
+use std::path::Path;
+
+use crate::{
+    config::{locations::LocationsProvider, projects::ProjectsRetriever},
+    git_ignore::{GitIgnoreHandler, GitIgnoreResult, IgnoreTarget},
+    ignore_coverage::{self, CoverageProblem},
+};
+
+/// Handler for the `puff gitignore` commands
+pub struct GitIgnoreCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    projects_retriever: &'a ProjectsRetriever<'a>,
+}
+
+impl<'a> GitIgnoreCommand<'a> {
+    pub fn new(
+        locations_provider: &'a LocationsProvider,
+        projects_retriever: &'a ProjectsRetriever<'a>,
+    ) -> Self {
+        GitIgnoreCommand {
+            locations_provider,
+            projects_retriever,
+        }
+    }
+
+    /// Adds ignore entries for the managed items that git doesn't ignore yet. With
+    /// .git/info/exclude, which all worktrees share, items of every worktree get fixed;
+    /// otherwise only the current checkout's .gitignore is changed. Problems that can't be
+    /// fixed this way (e.g. tracked items) are reported afterwards.
+    pub fn fix(&self, cwd: &Path, target: IgnoreTarget) -> Result<()> {
+        let (project_name, project_root) = self.locations_provider.find_project_for_path(cwd)?;
+        let managed_dir = self.locations_provider.get_managed_dir(&project_name);
+        let Some(details) = self.projects_retriever.get_details(&project_name)? else {
+            bail!(
+                "Corrupted state: project '{}' is registered in config.json but its directory is missing.",
+                project_name
+            );
+        };
+        let items = &details.info().items;
+
+        let handler = GitIgnoreHandler::new();
+        let mut fixed = 0;
+        for item in ignore_coverage::find_uncovered_items(&project_root, items)? {
+            let fixable = item.checkout == project_root || target == IgnoreTarget::InfoExclude;
+            if !fixable || item.problem != CoverageProblem::NotIgnored {
+                continue;
+            }
+            let result =
+                handler.ignore_managed_path(&item.checkout, &managed_dir, &item.path, target)?;
+            if result != GitIgnoreResult::AlreadyIgnored {
+                println!("Added '{}' to {target}.", item.path.display());
+                fixed += 1;
+            }
+        }
+
+        let remaining = ignore_coverage::find_uncovered_items(&project_root, items)?;
+        if fixed == 0 && remaining.is_empty() {
+            println!("All managed items of project '{project_name}' are ignored by git.");
+        }
+        ignore_coverage::print_warning(&remaining, &project_root);
+
+        Ok(())
+    }
+}
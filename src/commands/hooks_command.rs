@@ -0,0 +1,190 @@
+use anyhow::{Result, bail};
+use std::{env, fs, path::Path};
+
+use crate::{
+    commands::link_command::LinkCommand,
+    config::{
+        locations::LocationsProvider,
+        projects::{ProjectDetails, ProjectsRetriever},
+    },
+    conflicts::{ConflictResolver, OnConflict},
+    git::GitRepository,
+};
+
+/// Marks hooks written by puff, so that they can be updated but foreign hooks are left alone
+const HOOK_MARKER: &str = "# Installed by `puff hooks install`";
+
+/// Handler for the `puff hooks` commands
+pub struct HooksCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    projects_retriever: &'a ProjectsRetriever<'a>,
+}
+
+impl<'a> HooksCommand<'a> {
+    pub fn new(
+        locations_provider: &'a LocationsProvider,
+        projects_retriever: &'a ProjectsRetriever<'a>,
+    ) -> Self {
+        HooksCommand {
+            locations_provider,
+            projects_retriever,
+        }
+    }
+
+    /// Installs a pre-commit hook running `puff check-staged` into the repository of the
+    /// current project. With `worktrees`, a post-checkout hook that links new worktrees
+    /// gets installed as well.
+    pub fn install(&self, cwd: &Path, worktrees: bool) -> Result<()> {
+        let (_, project_root) = self.locations_provider.find_project_for_path(cwd)?;
+        let Some(repository) = GitRepository::discover(&project_root)? else {
+            bail!(
+                "Project directory '{}' is not inside a git repository.",
+                project_root.display()
+            );
+        };
+
+        let hooks_dir = repository.hooks_dir();
+        let mut hooks = vec![("pre-commit", "check-staged")];
+        if worktrees {
+            hooks.push(("post-checkout", "hooks post-checkout"));
+        }
+
+        // A foreign hook blocking one of them shouldn't prevent installing the other
+        let mut failed = 0;
+        for (name, arguments) in hooks {
+            if let Err(e) = write_hook(&hooks_dir, name, arguments) {
+                eprintln!("Error: {e}");
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            bail!("{failed} hook(s) could not be installed.");
+        }
+
+        Ok(())
+    }
+
+    /// Handles git's post-checkout hook. Git runs it in a freshly created worktree with
+    /// the null commit as the previous HEAD; in that case every registered project located
+    /// in the repository gets linked into its counterpart directory in the new worktree.
+    /// Failures are reported as warnings but never fail the checkout.
+    pub fn post_checkout(&self, cwd: &Path, previous_head: &str) -> Result<()> {
+        let is_new_worktree = !previous_head.is_empty() && previous_head.bytes().all(|b| b == b'0');
+        if !is_new_worktree {
+            return Ok(());
+        }
+        if let Err(e) = self.link_into_worktree(cwd) {
+            eprintln!("puff: could not link projects into the new worktree: {e}");
+        }
+        Ok(())
+    }
+
+    fn link_into_worktree(&self, cwd: &Path) -> Result<()> {
+        let Some(repository) = GitRepository::discover(cwd)? else {
+            return Ok(());
+        };
+
+        // Git hooks can't ask anything, local files get backed up like before
+        let conflict_resolver = ConflictResolver::new(Some(OnConflict::Backup), OnConflict::Backup);
+        let link_command = LinkCommand::new(
+            self.projects_retriever,
+            self.locations_provider,
+            conflict_resolver,
+        );
+        for name in self.projects_retriever.get_associated_projects() {
+            if let Err(e) = self.link_project(&link_command, &repository, &name) {
+                eprintln!("puff: could not link project '{name}': {e}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Links the project into its counterpart directory in the worktree of `repository`,
+    /// if the project is located in the repository
+    fn link_project(
+        &self,
+        link_command: &LinkCommand,
+        repository: &GitRepository,
+        name: &str,
+    ) -> Result<()> {
+        let Some(ProjectDetails::Associated(project)) =
+            self.projects_retriever.get_details(name)?
+        else {
+            return Ok(());
+        };
+        let Some(offset) = repository.relative_to_worktrees(&project.user_dir)? else {
+            return Ok(());
+        };
+
+        let target = repository.work_tree.join(&offset);
+        let is_main_checkout =
+            fs::canonicalize(&target).ok() == fs::canonicalize(&project.user_dir).ok();
+        if is_main_checkout || !target.is_dir() {
+            return Ok(());
+        }
+
+        link_command.link(name, &target)
+    }
+}
+
+/// Writes a hook script that runs puff with `arguments`. Puff is looked up in PATH, falling
+/// back to the currently running executable, since git GUIs often run hooks with a limited PATH.
+fn write_hook(hooks_dir: &Path, name: &str, arguments: &str) -> Result<()> {
+    let path = hooks_dir.join(name);
+    if path.exists() && !fs::read_to_string(&path)?.contains(HOOK_MARKER) {
+        bail!(
+            "A {name} hook already exists at '{}'. Add `puff {arguments}` to it, or remove it and run this command again.",
+            path.display()
+        );
+    }
+
+    let executable = env::current_exe()?.to_string_lossy().replace('\'', r"'\''");
+    let script = format!(
+        "#!/bin/sh\n{HOOK_MARKER}\npuff=$(command -v puff || echo '{executable}')\nexec \"$puff\" {arguments} \"$@\"\n"
+    );
+
+    fs::create_dir_all(hooks_dir)?;
+    fs::write(&path, script)?;
+    make_executable(&path)?;
+
+    println!("Installed {name} hook at '{}'.", path.display());
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    // Git for Windows runs hooks through its bundled shell regardless of file permissions
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_hook;
+    use std::fs;
+
+    #[test]
+    fn write_hook_replaces_own_hook_but_not_foreign_ones() {
+        let hooks = tempfile::tempdir().unwrap();
+
+        write_hook(hooks.path(), "pre-commit", "check-staged").unwrap();
+        write_hook(hooks.path(), "pre-commit", "check-staged").unwrap();
+        let content = fs::read_to_string(hooks.path().join("pre-commit")).unwrap();
+        assert!(content.contains("check-staged"));
+
+        fs::write(hooks.path().join("pre-push"), "#!/bin/sh\nmake test\n").unwrap();
+        let result = write_hook(hooks.path(), "pre-push", "check-staged");
+        assert!(result.is_err());
+        assert_eq!(
+            "#!/bin/sh\nmake test\n",
+            fs::read_to_string(hooks.path().join("pre-push")).unwrap()
+        );
+    }
+}
@@ -1,9 +1,19 @@
 use crate::{
+    commands::{link_command::LinkCommand, project_mark_command::ProjectMarkCommand},
     config::{
-        app_config::AppConfigManager, locations::LocationsProvider, projects::ProjectsRetriever,
+        app_config::{AppConfigManager, Project},
+        locations::LocationsProvider,
+        project_metadata::ProjectMetadata,
+        projects::ProjectsRetriever,
     },
-    io_utils::{prompt_input, prompt_select},
-    project_init::existing::ExistingProjectInitializer,
+    conflicts::ConflictResolver,
+    io_utils::{confirm, prompt_input, prompt_select},
+    marker::ProjectMarker,
+    project_init::{
+        existing::{ExistingProjectInitializer, create_symlinks_for_managed_files},
+        scan::{find_candidate_dirs, match_projects},
+    },
+    remotes,
 };
 use anyhow::{Result, bail};
 use std::{fs, path::Path};
@@ -12,68 +22,211 @@ pub struct InitCommand<'a> {
     pub projects_retriever: &'a ProjectsRetriever<'a>,
     pub app_config_manager: &'a AppConfigManager,
     pub locations_provider: &'a LocationsProvider,
+    /// Resolves conflicts between existing files and managed ones when a stored project
+    /// gets associated or linked
+    pub conflict_resolver: ConflictResolver,
 }
 
 impl<'a> InitCommand<'a> {
-    pub fn init(&self, cwd: &Path, name: Option<String>, associate: Option<String>) -> Result<()> {
+    pub fn init(
+        &self,
+        cwd: &Path,
+        name: Option<String>,
+        associate: Option<String>,
+        write_marker: bool,
+    ) -> Result<()> {
         if self.projects_retriever.is_associated(cwd)? {
             bail!("This directory is already initialized with puff.");
         }
 
-        if let Some(project_name) = associate {
+        let project_name = if let Some(project_name) = associate {
             let unassociated = self.projects_retriever.get_unassociated_projects()?;
             if !unassociated.contains(&project_name) {
                 bail!("Project '{}' is not an unassociated project.", project_name);
             }
             self.associate_project(&project_name, cwd)?;
+            project_name
         } else if let Some(project_name) = name {
-            self.init_fresh_project(&project_name, cwd)?;
+            self.init_fresh_project(Project::new(&project_name, cwd))?;
+            project_name
+        } else if let Some(marker) = ProjectMarker::read(cwd)? {
+            return self.init_from_marker(&marker, cwd);
         } else {
             let unassociated = self.projects_retriever.get_unassociated_projects()?;
             if !unassociated.is_empty() {
-                self.handle_with_unassociated(unassociated, cwd)?;
+                self.handle_with_unassociated(unassociated, cwd)?
             } else {
                 let project_name = self.prompt_project_name(cwd)?;
-                self.init_fresh_project(&project_name, cwd)?;
+                self.init_fresh_project(Project::new(&project_name, cwd))?;
+                project_name
             }
-        }
+        };
 
         println!("Project initialized.");
 
+        if write_marker {
+            ProjectMarkCommand::new(self.locations_provider, self.app_config_manager)
+                .mark(Some(project_name), cwd)?;
+        }
+
         Ok(())
     }
 
-    fn init_fresh_project(&self, name: &str, user_dir: &Path) -> Result<()> {
-        let managed_dir = self.locations_provider.get_managed_dir(name);
+    fn init_fresh_project(&self, project: Project) -> Result<()> {
+        let managed_dir = self.locations_provider.get_managed_dir(&project.name);
         if managed_dir.exists() {
             bail!("A project with this name already exists in puff's registry.");
         }
 
-        fs::create_dir_all(managed_dir)?;
-        self.app_config_manager.add_project(name, user_dir)?;
+        fs::create_dir_all(&managed_dir)?;
+        ProjectMetadata::new(&project.id).write(&managed_dir)?;
+        let user_dir = project.path.clone();
+        self.app_config_manager.register_project(project)?;
+        remotes::record(&managed_dir, &user_dir)?;
 
         Ok(())
     }
 
     fn associate_project(&self, name: &str, cwd: &Path) -> Result<()> {
-        let existing_initializer = ExistingProjectInitializer::new(self.app_config_manager);
+        let existing_initializer =
+            ExistingProjectInitializer::new(self.app_config_manager, self.conflict_resolver);
         existing_initializer.init_project(name, cwd, &self.locations_provider.get_managed_dir(name))
     }
 
-    fn handle_with_unassociated(&self, unassociated: Vec<String>, cwd: &Path) -> Result<()> {
+    /// Initializes a directory containing a .puff marker file without asking anything:
+    /// - a project associated with another existing directory gets linked (worktree, second clone)
+    /// - a project associated with a directory that no longer exists gets moved here
+    /// - a project known only in puff's data directory gets associated (fresh clone on a new machine)
+    /// - otherwise a new project with the marker's name and id is created
+    fn init_from_marker(&self, marker: &ProjectMarker, cwd: &Path) -> Result<()> {
+        let config = self.app_config_manager.get_config()?;
+        if let Some(project) = marker.find_project(&config) {
+            if project.path.exists() {
+                println!(
+                    "Project '{}' is already associated with '{}'. Linking its managed files here.",
+                    project.name,
+                    project.path.display()
+                );
+                let link_command = LinkCommand::new(
+                    self.projects_retriever,
+                    self.locations_provider,
+                    self.conflict_resolver,
+                );
+                return link_command.link(&project.name, cwd);
+            }
+
+            println!(
+                "Project '{}' was associated with '{}', which no longer exists. Moving it here.",
+                project.name,
+                project.path.display()
+            );
+            self.app_config_manager
+                .update_project_path(&project.id, cwd)?;
+            let managed_dir = self.locations_provider.get_managed_dir(&project.name);
+            create_symlinks_for_managed_files(cwd, &managed_dir, self.conflict_resolver)?;
+            remotes::record(&managed_dir, cwd)?;
+        } else {
+            // The project's data might be stored under a different name (renamed project)
+            let projects_path = self.locations_provider.get_projects_data_path();
+            let unassociated = self.projects_retriever.get_unassociated_projects()?;
+            let stored_name = ProjectMetadata::find_name(&projects_path, &marker.id)?
+                .or_else(|| Some(marker.name.clone()))
+                .filter(|name| unassociated.contains(name));
+            match stored_name {
+                Some(name) => self.associate_project(&name, cwd)?,
+                None => self.init_fresh_project(Project::with_id(&marker.name, &marker.id, cwd))?,
+            }
+        }
+
+        println!("Project '{}' initialized from its .puff file.", marker.name);
+
+        Ok(())
+    }
+
+    /// Looks for checkouts of unassociated projects in `root` (matching .puff markers, recorded
+    /// git remotes or directory names) and associates all of them after one confirmation
+    pub fn init_scan(&self, root: &Path, skip_confirmation: bool) -> Result<()> {
+        let unassociated = self.projects_retriever.get_unassociated_projects()?;
+        if unassociated.is_empty() {
+            println!("All projects are already associated.");
+            return Ok(());
+        }
+
+        let mut projects = vec![];
+        for name in unassociated {
+            let recorded = remotes::read(&self.locations_provider.get_managed_dir(&name))?;
+            projects.push((name, recorded));
+        }
+        let associated_paths: Vec<_> = self
+            .app_config_manager
+            .get_config()?
+            .projects
+            .into_iter()
+            .map(|p| p.path)
+            .collect();
+        let root = fs::canonicalize(root)?;
+        let candidates = find_candidate_dirs(&root, &associated_paths)?;
+        let result = match_projects(&projects, &candidates);
+
+        for ambiguous in &result.ambiguous {
+            println!(
+                "Skipping '{}', it matches more than one directory:",
+                ambiguous.name
+            );
+            for path in &ambiguous.paths {
+                println!("  {}", path.display());
+            }
+        }
+        if result.matches.is_empty() {
+            println!(
+                "No checkouts of unassociated projects found in '{}'.",
+                root.display()
+            );
+            return Ok(());
+        }
+
+        println!("Found {} project(s) to associate:", result.matches.len());
+        for project_match in &result.matches {
+            println!(
+                "  {} -> {} ({})",
+                project_match.name,
+                project_match.path.display(),
+                project_match.reason
+            );
+        }
+        if !skip_confirmation && !confirm("Associate these projects?".to_owned())? {
+            return Ok(());
+        }
+
+        let mut failed = 0;
+        for project_match in &result.matches {
+            if let Err(e) = self.associate_project(&project_match.name, &project_match.path) {
+                eprintln!("Error: could not associate '{}': {e}", project_match.name);
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            bail!("{failed} project(s) could not be associated.");
+        }
+        println!("Associated {} project(s).", result.matches.len());
+
+        Ok(())
+    }
+
+    fn handle_with_unassociated(&self, unassociated: Vec<String>, cwd: &Path) -> Result<String> {
         println!("Some projects in puff are not yet associated with a path on this machine.");
         let choice = self.ask_about_unassociated(&unassociated)?;
         match choice {
             UserChoice::Fresh => {
                 let name = self.prompt_project_name(cwd)?;
-                self.init_fresh_project(&name, cwd)?;
+                self.init_fresh_project(Project::new(&name, cwd))?;
+                Ok(name)
             }
             UserChoice::Existing(name) => {
                 self.associate_project(name, cwd)?;
+                Ok(name.to_owned())
             }
         }
-
-        Ok(())
     }
 
     fn prompt_project_name(&self, cwd: &Path) -> Result<String> {
@@ -6,25 +6,36 @@ use crate::{
         locations::LocationsProvider,
         projects::{ProjectDetails, ProjectsRetriever},
     },
+    conflicts::ConflictResolver,
     project_init::existing::create_symlinks_for_managed_files,
 };
 
 pub struct LinkCommand<'a> {
     projects_retriever: &'a ProjectsRetriever<'a>,
     locations_provider: &'a LocationsProvider,
+    conflict_resolver: ConflictResolver,
 }
 
 impl<'a> LinkCommand<'a> {
     pub fn new(
         projects_retriever: &'a ProjectsRetriever<'a>,
         locations_provider: &'a LocationsProvider,
+        conflict_resolver: ConflictResolver,
     ) -> Self {
         LinkCommand {
             projects_retriever,
             locations_provider,
+            conflict_resolver,
         }
     }
 
+    /// Links the project that the current directory belongs to according to its .puff
+    /// marker file into the directory containing the marker
+    pub fn link_current(&self, cwd: &Path) -> Result<()> {
+        let (project_name, project_root) = self.locations_provider.find_project_for_path(cwd)?;
+        self.link(&project_name, &project_root)
+    }
+
     pub fn link(&self, project_name: &str, cwd: &Path) -> Result<()> {
         let details = self.projects_retriever.get_details(project_name)?;
 
@@ -52,7 +63,7 @@ impl<'a> LinkCommand<'a> {
         }
 
         let managed_dir = self.locations_provider.get_managed_dir(project_name);
-        create_symlinks_for_managed_files(cwd, &managed_dir)?;
+        create_symlinks_for_managed_files(cwd, &managed_dir, self.conflict_resolver)?;
 
         let count = associated.info.items.len();
         println!(
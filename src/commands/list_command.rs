@@ -1,6 +1,36 @@
 use anyhow::{Result, bail};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{cmp::Ordering, time::SystemTime};
 
-use crate::config::projects::ProjectsRetriever;
+use crate::{
+    config::projects::{ProjectDetails, ProjectHealth, ProjectStats, ProjectsRetriever},
+    output::{self, OutputFormat, ProjectView, format_size, format_time, print_json},
+};
+
+/// Column by which `puff list` sorts projects
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ListSortKey {
+    #[default]
+    Name,
+    Path,
+    Items,
+    Size,
+    Modified,
+    Health,
+}
+
+/// Filtering and sorting options of `puff list`
+pub struct ListOptions {
+    pub only_associated: bool,
+    pub only_unassociated: bool,
+    /// Show only projects whose name contains this text
+    pub name_filter: Option<String>,
+    /// Show only associated projects with broken links or a missing path
+    pub only_unhealthy: bool,
+    pub sort: ListSortKey,
+    pub reverse: bool,
+}
 
 pub struct ListCommand<'a> {
     projects_retriever: &'a ProjectsRetriever<'a>,
@@ -11,39 +41,86 @@ impl<'a> ListCommand<'a> {
         ListCommand { projects_retriever }
     }
 
-    pub fn list(&self, only_associated: bool, only_unassociated: bool) -> Result<()> {
-        if only_associated && only_unassociated {
+    pub fn list(&self, options: &ListOptions, format: OutputFormat) -> Result<()> {
+        if options.only_associated && options.only_unassociated {
             bail!("Flags --associated (-a) and --unassociated (-u) are mutually exclusive.");
         }
 
-        let mut print_newline = false;
+        let entries = self.collect_entries(options)?;
 
-        if only_associated || !only_unassociated {
-            let associated = self.get_associated_proj();
-            if !associated.is_empty() {
-                print_newline = true;
-                println!("ASSOCIATED PROJECTS:");
-                for proj in associated {
-                    println!("{proj}");
-                }
-            }
+        let without_data = self.projects_retriever.get_entries_without_data();
+        if !without_data.is_empty() {
+            let names: Vec<_> = without_data.iter().map(|p| p.name.as_str()).collect();
+            eprintln!(
+                "WARNING: the data directory of these registered projects is missing: {}. Run 'puff reconcile' to fix them.",
+                names.join(", ")
+            );
         }
 
-        if only_unassociated || !only_associated {
-            let unassociated = self.get_unassociationed_proj()?;
-            if !unassociated.is_empty() {
-                if print_newline {
-                    println!()
-                }
+        if format == OutputFormat::Json {
+            let views: Vec<_> = entries.iter().map(ListEntryView::from).collect();
+            return print_json(&views);
+        }
+
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        print_table(&entries);
 
-                println!("UNASSOCIATED PROJECTS:");
-                for proj in unassociated {
-                    println!("{proj}");
+        Ok(())
+    }
+
+    fn collect_entries(&self, options: &ListOptions) -> Result<Vec<ListEntry>> {
+        let mut names = vec![];
+        if !options.only_unassociated {
+            names.extend(self.get_associated_proj());
+        }
+        if !options.only_associated {
+            names.extend(self.get_unassociationed_proj()?);
+        }
+
+        let mut entries = vec![];
+        for name in names {
+            if let Some(filter) = &options.name_filter
+                && !name.contains(filter.as_str())
+            {
+                continue;
+            }
+
+            let Some(details) = self.projects_retriever.get_details(&name)? else {
+                continue;
+            };
+            let mut health = details.health();
+            let stats = match details.info().stats() {
+                Ok(stats) => stats,
+                Err(e) => {
+                    eprintln!("Warning: could not read the data of project '{name}': {e}");
+                    health = Some(ProjectHealth::UnreadableData);
+                    ProjectStats::default()
                 }
+            };
+            if options.only_unhealthy && matches!(health, None | Some(ProjectHealth::Ok)) {
+                continue;
             }
+
+            entries.push(ListEntry {
+                details,
+                stats,
+                health,
+            });
         }
 
-        Ok(())
+        entries.sort_by(|a, b| {
+            let ordering = compare_entries(a, b, options.sort);
+            if options.reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        Ok(entries)
     }
 
     fn get_associated_proj(&self) -> Vec<String> {
@@ -54,3 +131,103 @@ impl<'a> ListCommand<'a> {
         self.projects_retriever.get_unassociated_projects()
     }
 }
+
+struct ListEntry {
+    details: ProjectDetails,
+    stats: ProjectStats,
+    health: Option<ProjectHealth>,
+}
+
+impl ListEntry {
+    fn path_column(&self) -> String {
+        match &self.details {
+            ProjectDetails::Associated(associated) => associated.user_dir.display().to_string(),
+            ProjectDetails::Unassociated(_) => "unassociated".to_owned(),
+        }
+    }
+}
+
+fn compare_entries(a: &ListEntry, b: &ListEntry, key: ListSortKey) -> Ordering {
+    let by_name = a.details.info().name.cmp(&b.details.info().name);
+    let ordering = match key {
+        ListSortKey::Name => by_name,
+        ListSortKey::Path => a.path_column().cmp(&b.path_column()),
+        ListSortKey::Items => (a.stats.files + a.stats.directories)
+            .cmp(&(b.stats.files + b.stats.directories))
+            .reverse(),
+        ListSortKey::Size => a.stats.size.cmp(&b.stats.size).reverse(),
+        ListSortKey::Modified => a.stats.modified.cmp(&b.stats.modified).reverse(),
+        ListSortKey::Health => health_rank(a.health).cmp(&health_rank(b.health)),
+    };
+    ordering.then(by_name)
+}
+
+/// Orders unhealthy projects first so that they stand out at the top of the list
+fn health_rank(health: Option<ProjectHealth>) -> u8 {
+    match health {
+        Some(ProjectHealth::MissingPath) => 0,
+        Some(ProjectHealth::UnreadableData) => 1,
+        Some(ProjectHealth::BrokenLinks) => 2,
+        Some(ProjectHealth::Ok) => 3,
+        None => 4,
+    }
+}
+
+fn print_table(entries: &[ListEntry]) {
+    let header = [
+        "NAME", "PATH", "FILES", "DIRS", "SIZE", "MODIFIED", "HEALTH",
+    ];
+    let rows: Vec<[String; 7]> = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.details.info().name.clone(),
+                entry.path_column(),
+                entry.stats.files.to_string(),
+                entry.stats.directories.to_string(),
+                format_size(entry.stats.size),
+                entry
+                    .stats
+                    .modified
+                    .map(format_time)
+                    .unwrap_or_else(|| "-".to_owned()),
+                entry
+                    .health
+                    .map(|h| h.to_string())
+                    .unwrap_or_else(|| "-".to_owned()),
+            ]
+        })
+        .collect();
+
+    output::print_table(header, &rows);
+}
+
+/// JSON representation of a `puff list` row
+#[derive(Serialize)]
+struct ListEntryView {
+    #[serde(flatten)]
+    project: ProjectView,
+    files: usize,
+    directories: usize,
+    size: u64,
+    /// Unix timestamp (seconds) of the most recent modification in the data store
+    modified: Option<u64>,
+    health: Option<ProjectHealth>,
+}
+
+impl From<&ListEntry> for ListEntryView {
+    fn from(entry: &ListEntry) -> Self {
+        ListEntryView {
+            project: ProjectView::from(&entry.details),
+            files: entry.stats.files,
+            directories: entry.stats.directories,
+            size: entry.stats.size,
+            modified: entry
+                .stats
+                .modified
+                .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            health: entry.health,
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::{Result, anyhow, bail};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    commands::add_command::AddCommand,
+    config::{locations::LocationsProvider, settings::FileMode},
+    git_ignore::GitIgnoreHandler,
+    io_utils::confirm,
+    managed_dirs::{self, PathClassification},
+    puff_ignore::PuffIgnore,
+};
+
+/// Handler for `puff merge <dir>`, which turns a directory of individually managed (and
+/// unmanaged) files into a single managed directory
+pub struct MergeCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    file_mode: FileMode,
+}
+
+/// What happens to an entry of the merged directory
+enum MergeEntry {
+    /// A symlink to an individually managed file, which becomes part of the directory
+    Managed(PathBuf),
+    /// A file that puff doesn't manage yet, which gets copied to puff's data directory
+    Unmanaged(PathBuf),
+    /// An entry excluded by the directory's `.puffignore`, which blocks the merge
+    Excluded(PathBuf),
+}
+
+impl<'a> MergeCommand<'a> {
+    pub fn new(locations_provider: &'a LocationsProvider, file_mode: FileMode) -> MergeCommand<'a> {
+        MergeCommand {
+            locations_provider,
+            file_mode,
+        }
+    }
+
+    /// Shows what merging `dir` into a managed directory does to the files in it and, after
+    /// a confirmation, does it the same way as `puff add` of an existing directory does.
+    /// Ignore entries that puff added for the merged files are replaced with one for the
+    /// directory. With `dry_run`, only the preview gets printed.
+    pub fn merge(
+        &self,
+        dir: &Path,
+        current_dir: &Path,
+        skip_confirmation: bool,
+        dry_run: bool,
+    ) -> Result<()> {
+        let user_path: PathBuf = current_dir.join(dir).components().collect();
+        let parent = user_path
+            .parent()
+            .ok_or_else(|| anyhow!("Could not retrieve user's project directory"))?;
+        let (project_name, project_root) = self.locations_provider.find_project_for_path(parent)?;
+        let managed_dir = self.locations_provider.get_managed_dir(&project_name);
+        let relative_dir = user_path.strip_prefix(&project_root)?.to_path_buf();
+
+        match managed_dirs::classify_path(&managed_dir, &relative_dir)? {
+            PathClassification::IsManaged => bail!(
+                "'{}' is already a managed directory.",
+                relative_dir.display()
+            ),
+            PathClassification::InsideManaged(parent_managed) => bail!(
+                "'{}' is part of managed directory '{}/'.",
+                relative_dir.display(),
+                parent_managed.display()
+            ),
+            PathClassification::Unmanaged => {}
+        }
+        if user_path.is_symlink() || !user_path.is_dir() {
+            bail!("'{}' is not a directory.", relative_dir.display());
+        }
+
+        let mut entries = vec![];
+        let excludes = PuffIgnore::read(&user_path)?;
+        collect_entries(
+            &user_path,
+            &user_path,
+            &excludes,
+            &project_root,
+            &managed_dir,
+            &mut entries,
+        )?;
+
+        println!(
+            "Merging '{}/' into a managed directory of project '{project_name}':",
+            relative_dir.display()
+        );
+        for entry in &entries {
+            match entry {
+                MergeEntry::Managed(path) => {
+                    println!(
+                        "  {} (managed, becomes part of the directory)",
+                        path.display()
+                    )
+                }
+                MergeEntry::Unmanaged(path) => {
+                    println!("  {} (not managed yet, gets added)", path.display())
+                }
+                MergeEntry::Excluded(path) => {
+                    println!(
+                        "  {} (excluded by .puffignore, move or delete it first)",
+                        path.display()
+                    )
+                }
+            }
+        }
+        if dry_run {
+            return Ok(());
+        }
+        let excluded = entries
+            .iter()
+            .filter(|entry| matches!(entry, MergeEntry::Excluded(_)))
+            .count();
+        if excluded > 0 {
+            bail!(
+                "'{}' contains {excluded} entries excluded by its .puffignore. \
+                Move or delete them first so that they don't get lost.",
+                relative_dir.display()
+            );
+        }
+        if !skip_confirmation && !confirm(format!("Merge '{}/'?", relative_dir.display()))? {
+            return Ok(());
+        }
+
+        AddCommand::new(self.locations_provider)
+            .with_file_mode(self.file_mode)
+            .add_file(user_path, current_dir, None, true)?;
+
+        let handler = GitIgnoreHandler::new();
+        let mut ignore_target = None;
+        for entry in &entries {
+            if let MergeEntry::Managed(path) = entry {
+                let target = handler.unignore_managed_path(&project_root, &managed_dir, path)?;
+                ignore_target = ignore_target.or(target);
+            }
+        }
+        if let Some(target) = ignore_target {
+            handler.ignore_managed_path(&project_root, &managed_dir, &relative_dir, target)?;
+            println!(
+                "Replaced the entries of the merged files with '{}' in {target}.",
+                relative_dir.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Collects the files in `dir`, relative to `project_root`, telling individually managed
+/// ones (symlinks into `managed_dir`) from the others and from the ones that the
+/// `.puffignore` of the merged directory `root` excludes
+fn collect_entries(
+    dir: &Path,
+    root: &Path,
+    excludes: &PuffIgnore,
+    project_root: &Path,
+    managed_dir: &Path,
+    entries: &mut Vec<MergeEntry>,
+) -> Result<()> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    for path in paths {
+        let relative = path.strip_prefix(project_root)?.to_path_buf();
+        if fs::read_link(&path).is_ok_and(|target| target.starts_with(managed_dir)) {
+            entries.push(MergeEntry::Managed(relative));
+        } else if excludes.is_excluded(path.strip_prefix(root)?, path.is_dir()) {
+            entries.push(MergeEntry::Excluded(relative));
+        } else if path.is_dir() && !path.is_symlink() {
+            collect_entries(&path, root, excludes, project_root, managed_dir, entries)?;
+        } else {
+            entries.push(MergeEntry::Unmanaged(relative));
+        }
+    }
+    Ok(())
+}
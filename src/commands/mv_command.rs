@@ -0,0 +1,243 @@
+use anyhow::{Result, anyhow, bail};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    config::{locations::LocationsProvider, project_metadata::ProjectMetadata},
+    fs_utils::{is_symlink, remove_dir_symlink, symlink_dir, symlink_file},
+    git::GitRepository,
+    git_ignore::GitIgnoreHandler,
+    managed_dirs::{self, PathClassification},
+};
+
+/// Handler for `puff mv <old> <new>`, which renames or moves a managed item within its project
+pub struct MvCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+}
+
+impl<'a> MvCommand<'a> {
+    pub fn new(locations_provider: &'a LocationsProvider) -> MvCommand<'a> {
+        MvCommand { locations_provider }
+    }
+
+    /// Moves the managed item at `old` to `new` (both relative to `current_dir`): the data
+    /// in the managed dir, its entry in `.puff_managed_dirs` and the item's metadata. The
+    /// symlink gets replaced, in the repository's other worktrees too, and the ignore
+    /// entry that puff added for the item is replaced with one for the new path.
+    pub fn mv(&self, old: &Path, new: &Path, current_dir: &Path) -> Result<()> {
+        let old_path = normalize(&current_dir.join(old));
+        let new_path = normalize(&current_dir.join(new));
+
+        let parent = old_path
+            .parent()
+            .ok_or_else(|| anyhow!("Could not retrieve user's project directory"))?;
+        let (project_name, project_root) = self.locations_provider.find_project_for_path(parent)?;
+        let managed_dir = self.locations_provider.get_managed_dir(&project_name);
+        let old_relative = old_path.strip_prefix(&project_root)?.to_path_buf();
+        let Ok(new_relative) = new_path.strip_prefix(&project_root) else {
+            bail!(
+                "'{}' is outside of project '{project_name}' ({}). Managed items can only be moved within their project.",
+                new.display(),
+                project_root.display()
+            );
+        };
+        let new_relative = new_relative.to_path_buf();
+        if new_relative.as_os_str().is_empty() || new_relative.starts_with(&old_relative) {
+            bail!(
+                "Can't move '{}' to '{}'.",
+                old_relative.display(),
+                new_relative.display()
+            );
+        }
+
+        let is_dir = match managed_dirs::classify_path(&managed_dir, &old_relative)? {
+            PathClassification::IsManaged => true,
+            PathClassification::InsideManaged(parent_managed) => bail!(
+                "'{0}' is part of managed directory '{1}/'. Move the file inside it directly, or use 'puff mv {1}' to move the entire directory.",
+                old_relative.display(),
+                parent_managed.display()
+            ),
+            PathClassification::Unmanaged => {
+                if !managed_dir.join(&old_relative).is_file() {
+                    bail!(
+                        "'{}' is not managed by project '{project_name}'.",
+                        old_relative.display()
+                    );
+                }
+                false
+            }
+        };
+
+        if let Some(parent_managed) =
+            managed_dirs::is_inside_managed_dir(&managed_dir, &new_relative)?
+        {
+            bail!(
+                "'{}' is inside managed directory '{}/'.",
+                new_relative.display(),
+                parent_managed.display()
+            );
+        }
+        let old_managed = managed_dir.join(&old_relative);
+        let new_managed = managed_dir.join(&new_relative);
+        if new_path.symlink_metadata().is_ok() || new_managed.symlink_metadata().is_ok() {
+            bail!("'{}' already exists.", new_relative.display());
+        }
+        if old_path.symlink_metadata().is_ok() && !is_symlink(&old_path)? {
+            bail!(
+                "'{}' exists but is not a puff symlink. Resolve the local file first, then re-run the command.",
+                old_relative.display()
+            );
+        }
+
+        fs::create_dir_all(new_managed.parent().unwrap())?;
+        fs::rename(&old_managed, &new_managed)?;
+        remove_empty_parents(&old_managed, &managed_dir);
+        if is_dir {
+            managed_dirs::remove_managed_dir(&managed_dir, &old_relative)?;
+            managed_dirs::add_managed_dir(&managed_dir, &new_relative)?;
+        }
+        ProjectMetadata::move_item(&managed_dir, &old_relative, &new_relative)?;
+
+        replace_link(&old_path, &new_path, &new_managed, is_dir)?;
+        for root in other_worktree_roots(&project_root)? {
+            let link = root.join(&old_relative);
+            if fs::read_link(&link).is_ok_and(|target| target == old_managed) {
+                replace_link(&link, &root.join(&new_relative), &new_managed, is_dir)?;
+                println!("Moved the link in '{}'.", root.display());
+            }
+        }
+
+        let handler = GitIgnoreHandler::new();
+        if let Some(target) =
+            handler.unignore_managed_path(&project_root, &managed_dir, &old_relative)?
+        {
+            handler.ignore_managed_path(&project_root, &managed_dir, &new_relative, target)?;
+            println!(
+                "Replaced '{}' with '{}' in {target}.",
+                old_relative.display(),
+                new_relative.display()
+            );
+        }
+
+        println!(
+            "Moved '{}' to '{}' in project '{project_name}'.",
+            old_relative.display(),
+            new_relative.display()
+        );
+        Ok(())
+    }
+}
+
+/// Removes the symlink at `old_link` (if there is one) and creates one at `new_link`
+fn replace_link(old_link: &Path, new_link: &Path, target: &Path, is_dir: bool) -> Result<()> {
+    if old_link.symlink_metadata().is_ok() {
+        if is_dir {
+            remove_dir_symlink(old_link)?;
+        } else {
+            fs::remove_file(old_link)?;
+        }
+    }
+    fs::create_dir_all(new_link.parent().unwrap())?;
+    if is_dir {
+        symlink_dir(target, new_link)
+    } else {
+        symlink_file(target, new_link)
+    }
+}
+
+/// The counterparts of `project_root` in the other worktrees of its repository
+fn other_worktree_roots(project_root: &Path) -> Result<Vec<PathBuf>> {
+    let Some(repository) = GitRepository::discover(project_root)? else {
+        return Ok(vec![]);
+    };
+    let Some(offset) = repository.relative_to_worktrees(project_root)? else {
+        return Ok(vec![]);
+    };
+    let project_root = fs::canonicalize(project_root)?;
+    Ok(repository
+        .worktrees()?
+        .into_iter()
+        .map(|worktree| worktree.join(&offset))
+        .filter(|root| root.is_dir() && fs::canonicalize(root).ok().as_ref() != Some(&project_root))
+        .collect())
+}
+
+/// Removes the directories that became empty when `path` was moved away, up to `managed_dir`
+fn remove_empty_parents(path: &Path, managed_dir: &Path) {
+    for parent in path.ancestors().skip(1) {
+        if parent == managed_dir || fs::remove_dir(parent).is_err() {
+            break;
+        }
+    }
+}
+
+/// Resolves `.` and `..` without touching the file system, since `new` doesn't exist yet
+/// and `old` is usually a symlink
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            component => result.push(component),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MvCommand;
+    use crate::{
+        config::{locations::LocationsProvider, project_metadata::ProjectMetadata},
+        managed_dirs,
+    };
+    use std::{fs, path::Path};
+
+    #[cfg(unix)]
+    #[test]
+    fn mv_moves_managed_directory_and_its_link() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        let project_dir = fs::canonicalize(user_dir.path()).unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        fs::write(
+            locations_provider.get_config_file_path(),
+            format!(
+                r#"{{"projects":[{{"name":"app","id":"1","path":"{}"}}]}}"#,
+                project_dir.display()
+            ),
+        )
+        .unwrap();
+        let managed_dir = locations_provider.get_managed_dir("app");
+        fs::create_dir_all(managed_dir.join("config")).unwrap();
+        fs::write(managed_dir.join("config/dev.env"), "KEY=1").unwrap();
+        managed_dirs::add_managed_dir(&managed_dir, Path::new("config")).unwrap();
+        ProjectMetadata::record_item(&managed_dir, Path::new("config")).unwrap();
+        std::os::unix::fs::symlink(managed_dir.join("config"), project_dir.join("config")).unwrap();
+
+        MvCommand::new(&locations_provider)
+            .mv(Path::new("config"), Path::new("settings"), &project_dir)
+            .unwrap();
+
+        assert_eq!(
+            "KEY=1",
+            fs::read_to_string(project_dir.join("settings/dev.env")).unwrap()
+        );
+        assert!(project_dir.join("config").symlink_metadata().is_err());
+        assert_eq!(
+            vec![Path::new("settings").to_path_buf()],
+            managed_dirs::read_managed_dirs(&managed_dir).unwrap()
+        );
+        let metadata = ProjectMetadata::read(&managed_dir).unwrap().unwrap();
+        assert!(metadata.item(Path::new("settings")).is_some());
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::{Result, bail};
+use std::path::Path;
+
+use crate::config::{locations::LocationsProvider, project_metadata::ProjectMetadata};
+
+/// Handler for `puff project describe`, which sets the description stored in the
+/// project's metadata
+pub struct ProjectDescribeCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+}
+
+impl<'a> ProjectDescribeCommand<'a> {
+    pub fn new(locations_provider: &'a LocationsProvider) -> Self {
+        ProjectDescribeCommand { locations_provider }
+    }
+
+    /// Sets the description of the project (by default, the project of the current
+    /// directory). An empty or missing description removes it.
+    pub fn describe(
+        &self,
+        description: Option<String>,
+        project_name: Option<String>,
+        cwd: &Path,
+    ) -> Result<()> {
+        let project_name = match project_name {
+            Some(name) => name,
+            None => self.locations_provider.find_project_for_path(cwd)?.0,
+        };
+
+        let managed_dir = self.locations_provider.get_managed_dir(&project_name);
+        if !managed_dir.is_dir() {
+            bail!(
+                "Project '{}' was not found. Check 'puff list' for available projects.",
+                project_name
+            );
+        }
+
+        let description = description
+            .map(|d| d.trim().to_owned())
+            .filter(|d| !d.is_empty());
+        match &description {
+            Some(description) => println!("Project '{project_name}': {description}"),
+            None => println!("Removed the description of project '{project_name}'."),
+        }
+        ProjectMetadata::update(&managed_dir, |m| m.description = description)
+    }
+}
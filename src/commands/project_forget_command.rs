@@ -2,27 +2,34 @@ use anyhow::{Result, bail};
 use std::fs;
 
 use crate::{
+    backups::BackupStore,
     config::{
         app_config::AppConfigManager,
+        locations::LocationsProvider,
         projects::{AssociatedProject, ManagedItem, ProjectDetails, ProjectsRetriever},
     },
-    fs_utils::{copy_dir_recursive, get_backup_path, is_symlink, remove_dir_symlink},
+    fs_utils::{copy_dir_recursive, is_symlink, remove_dir_symlink},
     io_utils::confirm,
+    shred::{ShredReport, shred_copies, shred_path},
+    trash::Trash,
 };
 
 pub struct ProjectForgetCommand<'a> {
     projects_retriever: &'a ProjectsRetriever<'a>,
     app_config_manager: &'a AppConfigManager,
+    locations_provider: &'a LocationsProvider,
 }
 
 impl<'a> ProjectForgetCommand<'a> {
     pub fn new(
         projects_retriever: &'a ProjectsRetriever,
         app_config_manager: &'a AppConfigManager,
+        locations_provider: &'a LocationsProvider,
     ) -> ProjectForgetCommand<'a> {
         ProjectForgetCommand {
             projects_retriever,
             app_config_manager,
+            locations_provider,
         }
     }
 
@@ -30,6 +37,7 @@ impl<'a> ProjectForgetCommand<'a> {
         &self,
         name: String,
         delete_files: bool,
+        shred: bool,
         skip_confirmation: bool,
     ) -> Result<()> {
         let project_details = self.projects_retriever.get_details(&name)?;
@@ -57,10 +65,30 @@ impl<'a> ProjectForgetCommand<'a> {
             }
         }
 
-        self.remove_managed_dir(&project_details)?;
+        if shred {
+            let info = project_details.info();
+            let mut report = ShredReport::default();
+            shred_path(&info.managed_dir, &mut report)?;
+            shred_copies(
+                self.locations_provider,
+                &info.metadata.id,
+                None,
+                &mut report,
+            )?;
+            self.update_config(&project_details)?;
+            println!("Project '{name}' removed.");
+            return report.finish();
+        }
+
+        let restored = matches!(project_details, ProjectDetails::Associated(_)) && !delete_files;
+        let trash_id = self.remove_managed_dir(&project_details, !restored)?;
         self.update_config(&project_details)?;
 
-        if delete_files || project_details.info().items.is_empty() {
+        if let Some(id) = trash_id {
+            println!(
+                "Project '{name}' removed. Its files were moved to the trash; run 'puff trash restore {id}' to get them back."
+            );
+        } else if project_details.info().items.is_empty() {
             println!("Project '{name}' removed.");
         } else {
             println!(
@@ -70,9 +98,30 @@ impl<'a> ProjectForgetCommand<'a> {
         Ok(())
     }
 
-    fn remove_managed_dir(&self, project_details: &ProjectDetails) -> Result<()> {
-        fs::remove_dir_all(&project_details.info().managed_dir)?;
-        Ok(())
+    /// Removes the project's managed dir, or moves it to the trash when its files weren't
+    /// restored to the project directory. Returns the id of the trash entry.
+    fn remove_managed_dir(
+        &self,
+        project_details: &ProjectDetails,
+        to_trash: bool,
+    ) -> Result<Option<String>> {
+        let info = project_details.info();
+        if !to_trash || info.items.is_empty() {
+            fs::remove_dir_all(&info.managed_dir)?;
+            return Ok(None);
+        }
+
+        let project_root = match project_details {
+            ProjectDetails::Associated(associated) => Some(associated.user_dir.as_path()),
+            _ => None,
+        };
+        let entry = Trash::new(self.locations_provider.get_trash_path()).trash_project(
+            &info.name,
+            &info.metadata.id,
+            &info.managed_dir,
+            project_root,
+        )?;
+        Ok(Some(entry.id))
     }
 
     fn remove_symlinks(&self, associated: &AssociatedProject) -> Result<()> {
@@ -94,17 +143,24 @@ impl<'a> ProjectForgetCommand<'a> {
 
     fn replace_symlinks(&self, associated: &AssociatedProject) -> Result<()> {
         for item in &associated.info.items {
-            let mut target_path = associated.user_dir.join(item.path());
+            let target_path = associated.user_dir.join(item.path());
             fs::create_dir_all(target_path.parent().unwrap())?;
 
             match item {
                 ManagedItem::File(_) => {
+                    let managed_file = associated.info.managed_dir.join(item.path());
                     if !is_symlink(&target_path)? {
-                        target_path = get_backup_path(&target_path)?;
-                    } else {
-                        fs::remove_file(&target_path)?;
+                        let backup = BackupStore::for_managed_dir(&associated.info.managed_dir)?
+                            .backup(&managed_file, item.path())?;
+                        println!(
+                            "{:?} exists in the project directory, so puff's copy was backed up as {} instead of restored.",
+                            item.path(),
+                            backup.id
+                        );
+                        continue;
                     }
-                    fs::copy(associated.info.managed_dir.join(item.path()), target_path)?;
+                    fs::remove_file(&target_path)?;
+                    fs::copy(managed_file, target_path)?;
                 }
                 ManagedItem::Directory(_) => {
                     if is_symlink(&target_path)? {
@@ -122,7 +178,7 @@ impl<'a> ProjectForgetCommand<'a> {
 
     fn update_config(&self, project_details: &ProjectDetails) -> Result<()> {
         self.app_config_manager
-            .remove_project(&project_details.info().name)?;
+            .remove_project(&project_details.info().metadata.id)?;
         Ok(())
     }
 }
@@ -0,0 +1,51 @@
+use anyhow::{Result, bail};
+use std::path::Path;
+
+use crate::{
+    config::{app_config::AppConfigManager, locations::LocationsProvider},
+    marker::{MARKER_FILE, ProjectMarker},
+};
+
+/// Handler for `puff project mark`, which writes the project's .puff marker file
+pub struct ProjectMarkCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    app_config_manager: &'a AppConfigManager,
+}
+
+impl<'a> ProjectMarkCommand<'a> {
+    pub fn new(
+        locations_provider: &'a LocationsProvider,
+        app_config_manager: &'a AppConfigManager,
+    ) -> Self {
+        ProjectMarkCommand {
+            locations_provider,
+            app_config_manager,
+        }
+    }
+
+    /// Writes the marker into the associated directory of the project (by default, the
+    /// project of the current directory)
+    pub fn mark(&self, project_name: Option<String>, cwd: &Path) -> Result<()> {
+        let project_name = match project_name {
+            Some(name) => name,
+            None => self.locations_provider.find_project_for_path(cwd)?.0,
+        };
+
+        let config = self.app_config_manager.get_config()?;
+        let Some(project) = config.projects.iter().find(|p| p.name == project_name) else {
+            bail!(
+                "Project '{}' is not associated with any directory on this machine.",
+                project_name
+            );
+        };
+
+        ProjectMarker::new(&project.name, &project.id).write(&project.path)?;
+        println!(
+            "Created '{}'. Commit it so that clones and worktrees of the repository are recognized as project '{}'.",
+            project.path.join(MARKER_FILE).display(),
+            project.name
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use anyhow::{Result, bail};
+use std::path::Path;
+
+use crate::{
+    config::{
+        locations::LocationsProvider,
+        projects::{ProjectDetails, ProjectsRetriever},
+    },
+    output::{OutputFormat, ProjectView, format_time, print_json},
+};
+
+pub struct ProjectShowCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    projects_retriever: &'a ProjectsRetriever<'a>,
+}
+
+impl<'a> ProjectShowCommand<'a> {
+    pub fn new(
+        locations_provider: &'a LocationsProvider,
+        projects_retriever: &'a ProjectsRetriever<'a>,
+    ) -> Self {
+        ProjectShowCommand {
+            locations_provider,
+            projects_retriever,
+        }
+    }
+
+    pub fn show(
+        &self,
+        project_name: Option<String>,
+        cwd: &Path,
+        format: OutputFormat,
+    ) -> Result<()> {
+        let project_name = match project_name {
+            Some(name) => name,
+            None => self.locations_provider.find_project_for_path(cwd)?.0,
+        };
+
+        let Some(details) = self.projects_retriever.get_details(&project_name)? else {
+            bail!(
+                "Project '{}' was not found. Check 'puff list' for available projects.",
+                project_name
+            );
+        };
+
+        let view = ProjectView::from(&details);
+        if format == OutputFormat::Json {
+            return print_json(&view);
+        }
+
+        let metadata = &details.info().metadata;
+        println!("Project: {}", view.name);
+        if let Some(description) = &metadata.description {
+            println!("Description: {description}");
+        }
+        println!("Id: {}", view.id);
+        if let Some(created) = metadata.created() {
+            println!("Created: {}", format_time(created));
+        }
+        match &details {
+            ProjectDetails::Associated(associated) => {
+                println!("Path: {}", associated.user_dir.display())
+            }
+            ProjectDetails::Unassociated(_) => println!("Path: (unassociated)"),
+        }
+        println!("Data: {}", view.managed_dir.display());
+        println!("Managed items:");
+        if details.info().items.is_empty() {
+            println!("  (none)");
+        }
+        for (item, item_view) in details.info().items.iter().zip(&view.items) {
+            let mut line = match item_view.link_state {
+                Some(state) => format!("  {} ({state})", item.display_name()),
+                None => format!("  {}", item.display_name()),
+            };
+            if let Some(item_metadata) = metadata.item(item.path()) {
+                line += &format!(", added {}", format_time(item_metadata.added()));
+            }
+            println!("{line}");
+        }
+
+        Ok(())
+    }
+}
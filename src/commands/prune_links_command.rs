@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{app_config::AppConfigManager, locations::LocationsProvider},
+    fs_utils::copy_dir_recursive,
+    io_utils::prompt_select,
+    puff_links::{LinkTarget, PuffLink, find_puff_links},
+};
+
+/// What `puff prune-links` does with orphaned links without asking
+#[derive(Clone, Copy, PartialEq)]
+pub enum PruneAction {
+    /// Remove the links
+    Delete,
+    /// Replace the links with copies of the files restored in the project's directory
+    Restore,
+}
+
+/// Handler for `puff prune-links`, which cleans up symlinks into the data directory whose
+/// targets are gone (left behind in worktrees and other linked copies by `forget` and
+/// `project forget`)
+pub struct PruneLinksCommand<'a> {
+    app_config_manager: &'a AppConfigManager,
+    locations_provider: &'a LocationsProvider,
+}
+
+impl<'a> PruneLinksCommand<'a> {
+    pub fn new(
+        app_config_manager: &'a AppConfigManager,
+        locations_provider: &'a LocationsProvider,
+    ) -> Self {
+        PruneLinksCommand {
+            app_config_manager,
+            locations_provider,
+        }
+    }
+
+    /// Finds orphaned links in `dirs` (the current directory by default) and deletes or
+    /// restores them, asking about each one unless `action` is given. With `list_only`,
+    /// nothing is changed.
+    pub fn prune(
+        &self,
+        dirs: &[PathBuf],
+        cwd: &Path,
+        action: Option<PruneAction>,
+        list_only: bool,
+    ) -> Result<()> {
+        let projects_path = self.locations_provider.get_projects_data_path();
+        let dirs = if dirs.is_empty() {
+            vec![cwd.to_owned()]
+        } else {
+            dirs.to_vec()
+        };
+
+        let mut orphaned = vec![];
+        let mut unknown = vec![];
+        for dir in &dirs {
+            let dir = fs::canonicalize(dir)
+                .with_context(|| format!("Could not scan '{}'.", dir.display()))?;
+            for link in find_puff_links(&dir, &projects_path)? {
+                match link.classify(&projects_path)? {
+                    LinkTarget::Managed => {}
+                    LinkTarget::Unknown => unknown.push(link),
+                    target => orphaned.push((link, target)),
+                }
+            }
+        }
+
+        for link in &unknown {
+            println!(
+                "Could not classify '{}': it points into puff's data directory, but not at a managed item. Left untouched.",
+                link.path.display()
+            );
+        }
+        if orphaned.is_empty() {
+            println!("No orphaned links found.");
+            return Ok(());
+        }
+
+        let mut deleted = 0;
+        let mut restored = 0;
+        for (link, target) in &orphaned {
+            let reason = match target {
+                LinkTarget::RemovedProject => {
+                    format!("project '{}' was removed from puff", link.project)
+                }
+                _ => format!(
+                    "'{}' is no longer managed by project '{}'",
+                    link.item.display(),
+                    link.project
+                ),
+            };
+            println!("{}: {reason}", link.path.display());
+            if list_only {
+                continue;
+            }
+
+            let source = self.restore_source(link)?;
+            let action = match action {
+                Some(action) => Some(action),
+                None => ask(link, source.as_deref())?,
+            };
+            match (action, &source) {
+                (Some(PruneAction::Delete), _) => {
+                    remove_link(&link.path)?;
+                    deleted += 1;
+                }
+                (Some(PruneAction::Restore), Some(source)) => {
+                    restore(&link.path, source)?;
+                    restored += 1;
+                }
+                (Some(PruneAction::Restore), None) => println!(
+                    "  Nothing to restore it from, left untouched. Use --delete to remove it."
+                ),
+                (None, _) => {}
+            }
+        }
+
+        if !list_only {
+            println!("Deleted {deleted} and restored {restored} link(s).");
+        }
+        Ok(())
+    }
+
+    /// The copy of a forgotten item: the file that `forget` restored in the associated
+    /// directory of the project
+    fn restore_source(&self, link: &PuffLink) -> Result<Option<PathBuf>> {
+        let config = self.app_config_manager.get_config()?;
+        let Some(project) = config.projects.iter().find(|p| p.name == link.project) else {
+            return Ok(None);
+        };
+        let source = project.path.join(&link.item);
+        let is_copy = source != link.path
+            && source
+                .symlink_metadata()
+                .is_ok_and(|m| !m.file_type().is_symlink());
+        Ok(is_copy.then_some(source))
+    }
+}
+
+fn ask(link: &PuffLink, source: Option<&Path>) -> Result<Option<PruneAction>> {
+    let mut items = vec!["Delete the link".to_owned()];
+    if let Some(source) = source {
+        items.push(format!("Restore a copy of '{}'", source.display()));
+    }
+    items.push("Skip".to_owned());
+
+    let selection = prompt_select(
+        &format!("What should happen to '{}'?", link.path.display()),
+        &items,
+    )?;
+    Ok(match selection {
+        0 => Some(PruneAction::Delete),
+        1 if source.is_some() => Some(PruneAction::Restore),
+        _ => None,
+    })
+}
+
+/// Removes a dangling symlink, which might point to a file or a directory
+fn remove_link(path: &Path) -> Result<()> {
+    if fs::remove_file(path).is_err() {
+        fs::remove_dir(path)?;
+    }
+    Ok(())
+}
+
+fn restore(link: &Path, source: &Path) -> Result<()> {
+    remove_link(link)?;
+    if source.is_dir() {
+        copy_dir_recursive(source, link)?;
+    } else {
+        fs::copy(source, link)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use std::{fs, path::PathBuf};
+
+use crate::{
+    config::{
+        app_config::{AppConfigManager, Project},
+        locations::LocationsProvider,
+        project_metadata::ProjectMetadata,
+        projects::ProjectsRetriever,
+    },
+    conflicts::{ConflictResolver, OnConflict},
+    io_utils::{prompt_input, prompt_select},
+    project_init::existing::ExistingProjectInitializer,
+};
+
+/// What `puff reconcile` does with registry entries whose data directory is missing
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum MissingDataAction {
+    /// Create an empty data directory, keeping the association
+    Recreate,
+    /// Remove the entry from config.json
+    Unregister,
+}
+
+/// Handler for `puff reconcile`, which fixes mismatches between config.json and the data
+/// directory: registry entries without data, and data without registry entries
+pub struct ReconcileCommand<'a> {
+    projects_retriever: &'a ProjectsRetriever<'a>,
+    app_config_manager: &'a AppConfigManager,
+    locations_provider: &'a LocationsProvider,
+}
+
+impl<'a> ReconcileCommand<'a> {
+    pub fn new(
+        projects_retriever: &'a ProjectsRetriever<'a>,
+        app_config_manager: &'a AppConfigManager,
+        locations_provider: &'a LocationsProvider,
+    ) -> Self {
+        ReconcileCommand {
+            projects_retriever,
+            app_config_manager,
+            locations_provider,
+        }
+    }
+
+    /// Lists the mismatches and asks what to do with each of them. With `missing_action`,
+    /// it's applied to all entries without data instead, and nothing is asked. With
+    /// `list_only`, nothing is changed.
+    pub fn reconcile(
+        &self,
+        missing_action: Option<MissingDataAction>,
+        list_only: bool,
+    ) -> Result<()> {
+        let without_data = self.projects_retriever.get_entries_without_data();
+        let mut unassociated = self.projects_retriever.get_unassociated_projects()?;
+        if without_data.is_empty() && unassociated.is_empty() {
+            println!("The registry and the data directory are consistent.");
+            return Ok(());
+        }
+
+        for entry in &without_data {
+            println!(
+                "Project '{}' ({}) is registered, but its data directory is missing.",
+                entry.name,
+                entry.path.display()
+            );
+            if list_only {
+                continue;
+            }
+
+            match missing_action {
+                Some(MissingDataAction::Recreate) => self.recreate(entry)?,
+                Some(MissingDataAction::Unregister) => self.unregister(entry)?,
+                None => {
+                    if let Some(stored_name) = self.ask_about_entry(entry, &unassociated)? {
+                        unassociated.retain(|name| *name != stored_name);
+                    }
+                }
+            }
+        }
+
+        for name in &unassociated {
+            println!(
+                "Project '{}' is stored in puff, but not associated with any directory on this machine.",
+                name
+            );
+            if list_only || missing_action.is_some() {
+                continue;
+            }
+            self.ask_about_unassociated(name)?;
+        }
+
+        Ok(())
+    }
+
+    /// Asks what to do with an entry without data. Returns the name of the stored
+    /// project the entry got re-associated with, if any.
+    fn ask_about_entry(&self, entry: &Project, unassociated: &[String]) -> Result<Option<String>> {
+        let mut items = vec![
+            "Recreate an empty data directory".to_owned(),
+            "Unregister it".to_owned(),
+        ];
+        for name in unassociated {
+            items.push(format!("Re-associate the directory with project '{name}'"));
+        }
+        items.push("Skip".to_owned());
+
+        let selection = prompt_select(&format!("What should happen to '{}'?", entry.name), &items)?;
+        match selection {
+            0 => self.recreate(entry)?,
+            1 => self.unregister(entry)?,
+            i if i - 2 < unassociated.len() => {
+                let name = &unassociated[i - 2];
+                self.reassociate(entry, name)?;
+                return Ok(Some(name.clone()));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn ask_about_unassociated(&self, name: &str) -> Result<()> {
+        let items = vec![
+            "Associate it with a directory".to_owned(),
+            "Skip (keep it unassociated)".to_owned(),
+        ];
+        if prompt_select(&format!("What should happen to '{name}'?"), &items)? != 0 {
+            return Ok(());
+        }
+
+        let path = PathBuf::from(prompt_input("Directory", None)?);
+        if !path.is_dir() {
+            bail!("'{}' is not a directory.", path.display());
+        }
+        let path = fs::canonicalize(path)?;
+        ExistingProjectInitializer::new(self.app_config_manager, conflict_resolver())
+            .init_project(name, &path, &self.locations_provider.get_managed_dir(name))?;
+        println!("Associated '{}' with '{}'.", name, path.display());
+        Ok(())
+    }
+
+    /// Creates an empty data directory for the entry, keeping its id
+    fn recreate(&self, entry: &Project) -> Result<()> {
+        let managed_dir = self.locations_provider.get_managed_dir(&entry.name);
+        fs::create_dir_all(&managed_dir)?;
+        ProjectMetadata::new(&entry.id).write(&managed_dir)?;
+        println!("Recreated an empty data directory for '{}'.", entry.name);
+        Ok(())
+    }
+
+    fn unregister(&self, entry: &Project) -> Result<()> {
+        self.app_config_manager.remove_project(&entry.id)?;
+        println!("Unregistered '{}'.", entry.name);
+        Ok(())
+    }
+
+    /// Replaces the entry with an association of its directory with a stored project
+    /// (e.g. one whose data was renamed or copied from another machine)
+    fn reassociate(&self, entry: &Project, stored_name: &str) -> Result<()> {
+        self.app_config_manager.remove_project(&entry.id)?;
+        let managed_dir = self.locations_provider.get_managed_dir(stored_name);
+        let initializer =
+            ExistingProjectInitializer::new(self.app_config_manager, conflict_resolver());
+        if entry.path.is_dir() {
+            initializer.init_project(stored_name, &entry.path, &managed_dir)?;
+        } else {
+            let id = ProjectMetadata::load(&managed_dir)?.id;
+            self.app_config_manager.register_project(Project::with_id(
+                stored_name,
+                &id,
+                &entry.path,
+            ))?;
+        }
+        println!(
+            "Associated '{}' with '{}'.",
+            stored_name,
+            entry.path.display()
+        );
+        Ok(())
+    }
+}
+
+/// Conflicts of associated directories are resolved interactively, like `puff init` does
+fn conflict_resolver() -> ConflictResolver {
+    ConflictResolver::new(None, OnConflict::Backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReconcileCommand;
+    use crate::config::{
+        app_config::{AppConfigManager, Project},
+        locations::LocationsProvider,
+        projects::ProjectsRetriever,
+    };
+    use std::fs;
+
+    #[cfg(unix)]
+    #[test]
+    fn reassociate_replaces_entry_with_stored_project() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let stored = locations_provider.get_managed_dir("renamed");
+        fs::create_dir_all(&stored).unwrap();
+        fs::write(stored.join(".env"), "A=1").unwrap();
+        let entry = Project::with_id("gone", "1", user_dir.path());
+        fs::write(
+            locations_provider.get_config_file_path(),
+            format!(
+                r#"{{"projects":[{{"name":"gone","id":"1","path":"{}"}}]}}"#,
+                user_dir.path().display()
+            ),
+        )
+        .unwrap();
+        let app_config_manager =
+            AppConfigManager::new(locations_provider.get_config_file_path()).unwrap();
+        let retriever = ProjectsRetriever::new(
+            app_config_manager.get_config().unwrap(),
+            &locations_provider,
+        );
+        let sut = ReconcileCommand::new(&retriever, &app_config_manager, &locations_provider);
+
+        sut.reassociate(&entry, "renamed").unwrap();
+
+        let projects = app_config_manager.get_config().unwrap().projects;
+        assert_eq!(1, projects.len());
+        assert_eq!("renamed", projects[0].name);
+        assert_ne!("1", projects[0].id);
+        assert!(user_dir.path().join(".env").is_symlink());
+    }
+}
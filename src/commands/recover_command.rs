@@ -0,0 +1,121 @@
+use anyhow::{Context, Result, bail};
+use std::{fs, path::PathBuf};
+
+use crate::{
+    config::{
+        app_config::{AppConfigManager, Project},
+        locations::LocationsProvider,
+        project_metadata::ProjectMetadata,
+    },
+    io_utils::confirm,
+    project_init::recover::infer_projects,
+    puff_links::{PuffLink, find_puff_links},
+    remotes,
+};
+
+/// Handler for `puff recover`, which rebuilds the associations of config.json from the
+/// symlinks that puff left in project directories
+pub struct RecoverCommand<'a> {
+    app_config_manager: &'a AppConfigManager,
+    locations_provider: &'a LocationsProvider,
+}
+
+impl<'a> RecoverCommand<'a> {
+    pub fn new(
+        app_config_manager: &'a AppConfigManager,
+        locations_provider: &'a LocationsProvider,
+    ) -> Self {
+        RecoverCommand {
+            app_config_manager,
+            locations_provider,
+        }
+    }
+
+    /// Finds symlinks into puff's data directory in `dirs`, infers the project roots from
+    /// them and registers the projects that aren't associated yet, after one confirmation
+    pub fn recover(&self, dirs: &[PathBuf], skip_confirmation: bool) -> Result<()> {
+        let projects_path = self.locations_provider.get_projects_data_path();
+        let mut links: Vec<PuffLink> = vec![];
+        for dir in dirs {
+            let dir = fs::canonicalize(dir)
+                .with_context(|| format!("Could not scan '{}'.", dir.display()))?;
+            links.extend(find_puff_links(&dir, &projects_path)?);
+        }
+        let result = infer_projects(&links);
+
+        for ambiguous in &result.ambiguous {
+            println!(
+                "Skipping '{}', its links are spread over several directories:",
+                ambiguous.name
+            );
+            for path in &ambiguous.paths {
+                println!("  {}", path.display());
+            }
+        }
+
+        let config = self.app_config_manager.get_config()?;
+        let mut recovered = vec![];
+        for project in result.projects {
+            let managed_dir = self.locations_provider.get_managed_dir(&project.name);
+            if !managed_dir.is_dir() {
+                println!(
+                    "Skipping '{}', its data is missing in puff's data directory.",
+                    project.name
+                );
+                continue;
+            }
+            let id = ProjectMetadata::load(&managed_dir)?.id;
+            if let Some(existing) = config.projects.iter().find(|p| p.id == id) {
+                if existing.path != project.path {
+                    println!(
+                        "Skipping '{}', it's already associated with '{}'.",
+                        project.name,
+                        existing.path.display()
+                    );
+                }
+                continue;
+            }
+            recovered.push((project, id));
+        }
+
+        if recovered.is_empty() {
+            println!("No associations to recover.");
+            return Ok(());
+        }
+
+        println!("Found {} project(s) to recover:", recovered.len());
+        for (project, _) in &recovered {
+            println!(
+                "  {} -> {} ({} link(s))",
+                project.name,
+                project.path.display(),
+                project.links
+            );
+        }
+        if !skip_confirmation && !confirm("Register these projects?".to_owned())? {
+            return Ok(());
+        }
+
+        let mut failed = 0;
+        for (project, id) in &recovered {
+            let managed_dir = self.locations_provider.get_managed_dir(&project.name);
+            let registered = self.app_config_manager.register_project(Project::with_id(
+                &project.name,
+                id,
+                &project.path,
+            ));
+            if let Err(e) = registered {
+                eprintln!("Error: could not register '{}': {e}", project.name);
+                failed += 1;
+                continue;
+            }
+            remotes::record(&managed_dir, &project.path)?;
+        }
+        if failed > 0 {
+            bail!("{failed} project(s) could not be registered.");
+        }
+        println!("Recovered {} project(s).", recovered.len());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::Result;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    commands::add_command::AddCommand,
+    config::{locations::LocationsProvider, settings::FileMode},
+    git::GitRepository,
+    git_ignore::GitIgnoreRules,
+    io_utils::prompt_multi_select,
+    output::{OutputFormat, print_json},
+    patterns::{DEFAULT_EXCLUDED_DIRS, GlobPattern},
+};
+
+/// File name patterns of files that usually hold configuration or secrets
+const SECRET_FILE_PATTERNS: [&str; 5] = [
+    ".env*",
+    "*.pem",
+    "*.key",
+    "appsettings.*.json",
+    "credentials*",
+];
+
+/// Settings that make an .npmrc file hold credentials
+const NPMRC_TOKEN_KEYS: [&str; 3] = ["_authToken", "_auth", "_password"];
+
+/// Handler for the `puff scan` command. Finds files that are ignored by git, present in
+/// the project, and look like configuration or secrets, so that puff can manage them.
+pub struct ScanCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    file_mode: FileMode,
+}
+
+impl<'a> ScanCommand<'a> {
+    pub fn new(locations_provider: &'a LocationsProvider, file_mode: FileMode) -> Self {
+        ScanCommand {
+            locations_provider,
+            file_mode,
+        }
+    }
+
+    pub fn scan(&self, cwd: &Path, add_all: bool, format: OutputFormat) -> Result<()> {
+        let (project_name, project_root) = self.locations_provider.find_project_for_path(cwd)?;
+        let managed_dir = self.locations_provider.get_managed_dir(&project_name);
+        let candidates = find_candidates(&project_root, &managed_dir)?;
+
+        if format == OutputFormat::Json {
+            return print_json(&candidates);
+        }
+
+        if candidates.is_empty() {
+            println!("No unmanaged secret files found in project '{project_name}'.");
+            return Ok(());
+        }
+
+        let items: Vec<String> = candidates.iter().map(|c| c.display().to_string()).collect();
+        let chosen = if add_all {
+            (0..candidates.len()).collect()
+        } else {
+            prompt_multi_select("Select the files that puff should manage", &items)?
+        };
+
+        if chosen.is_empty() {
+            println!("Nothing selected.");
+            return Ok(());
+        }
+
+        let add_command = AddCommand::new(self.locations_provider).with_file_mode(self.file_mode);
+        for index in chosen {
+            let path = project_root.join(&candidates[index]);
+            if let Err(e) = add_command.add_file(path, &project_root, None, false) {
+                eprintln!("Error: {e}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns paths (relative to `project_root`) of regular files that git ignores, that
+/// look like configuration or secrets, and that puff doesn't manage yet
+pub fn find_candidates(project_root: &Path, managed_dir: &Path) -> Result<Vec<PathBuf>> {
+    let patterns: Vec<GlobPattern> = SECRET_FILE_PATTERNS
+        .iter()
+        .map(|p| GlobPattern::new(p))
+        .collect();
+
+    let mut candidates = vec![];
+    let mut stack = vec![project_root.to_owned()];
+    while let Some(current) = stack.pop() {
+        for entry in fs::read_dir(&current)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            let relative = path.strip_prefix(project_root)?.to_owned();
+
+            if file_type.is_dir() {
+                let excluded = DEFAULT_EXCLUDED_DIRS
+                    .iter()
+                    .any(|e| entry.file_name() == *e);
+                if !excluded {
+                    stack.push(path);
+                }
+                continue;
+            }
+
+            // Symlinks are skipped: puff-managed files are symlinks already
+            if !file_type.is_file() || managed_dir.join(&relative).exists() {
+                continue;
+            }
+
+            let name = Path::new(relative.file_name().unwrap_or_default());
+            let looks_secret =
+                patterns.iter().any(|p| p.matches(name)) || is_npmrc_with_token(&path);
+            if looks_secret {
+                candidates.push(relative);
+            }
+        }
+    }
+
+    let repository = GitRepository::discover(project_root)?;
+    let mut ignored =
+        GitIgnoreRules::ignored_paths(repository.as_ref(), project_root, &candidates)?;
+    ignored.sort();
+    Ok(ignored)
+}
+
+fn is_npmrc_with_token(path: &Path) -> bool {
+    if path.file_name().is_none_or(|n| n != ".npmrc") {
+        return false;
+    }
+    fs::read_to_string(path)
+        .map(|content| NPMRC_TOKEN_KEYS.iter().any(|key| content.contains(key)))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_candidates;
+    use std::{fs, path::PathBuf};
+
+    #[test]
+    fn find_candidates_returns_ignored_secret_files_only() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+        let root = project.path();
+        fs::create_dir_all(root.join("certs")).unwrap();
+        fs::create_dir_all(root.join("node_modules/pkg")).unwrap();
+        fs::write(
+            root.join(".gitignore"),
+            ".env*\n!.env.example\ncerts/\n.npmrc\nnotes.txt\n",
+        )
+        .unwrap();
+        fs::write(root.join(".env"), "A=1").unwrap();
+        fs::write(root.join(".env.example"), "A=").unwrap();
+        fs::write(root.join("certs/server.pem"), "pem").unwrap();
+        fs::write(root.join("notes.txt"), "ignored, but no secret").unwrap();
+        fs::write(root.join("tracked.key"), "not ignored").unwrap();
+        fs::write(root.join(".npmrc"), "//registry/:_authToken=abc").unwrap();
+        fs::write(root.join("node_modules/pkg/.env"), "B=1").unwrap();
+
+        let result = find_candidates(root, managed.path()).unwrap();
+
+        assert_eq!(
+            vec![
+                PathBuf::from(".env"),
+                PathBuf::from(".npmrc"),
+                PathBuf::from("certs/server.pem"),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn find_candidates_skips_files_already_in_puff() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".gitignore"), ".env\n").unwrap();
+        fs::write(project.path().join(".env"), "A=1").unwrap();
+        fs::write(managed.path().join(".env"), "A=1").unwrap();
+
+        let result = find_candidates(project.path(), managed.path()).unwrap();
+
+        assert!(result.is_empty());
+    }
+}
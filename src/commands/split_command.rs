@@ -0,0 +1,254 @@
+use anyhow::{Result, anyhow, bail};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::{locations::LocationsProvider, project_metadata::ProjectMetadata},
+    fs_utils::{move_path, remove_dir_symlink, symlink_file},
+    git_ignore::GitIgnoreHandler,
+    managed_dirs::{self, PathClassification},
+    puff_ignore::{PUFF_IGNORE_FILE, PuffIgnore},
+};
+
+/// Handler for `puff split <dir>`, which turns a managed directory into a real directory
+/// of individually managed files
+pub struct SplitCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+}
+
+impl<'a> SplitCommand<'a> {
+    pub fn new(locations_provider: &'a LocationsProvider) -> SplitCommand<'a> {
+        SplitCommand { locations_provider }
+    }
+
+    /// Replaces the symlink of the managed directory `dir` with a real directory that
+    /// contains a symlink for every file in it, so that unmanaged files can live next to
+    /// them. The data stays where it is; only the bookkeeping changes from one directory
+    /// to its files, including the ignore entry that puff added for the directory. The
+    /// directory's `.puffignore` and the entries it excludes move into the real directory
+    /// without getting managed.
+    pub fn split(&self, dir: &Path, current_dir: &Path) -> Result<()> {
+        let user_path: PathBuf = current_dir.join(dir).components().collect();
+        let parent = user_path
+            .parent()
+            .ok_or_else(|| anyhow!("Could not retrieve user's project directory"))?;
+        let (project_name, project_root) = self.locations_provider.find_project_for_path(parent)?;
+        let managed_dir = self.locations_provider.get_managed_dir(&project_name);
+        let relative_dir = user_path.strip_prefix(&project_root)?.to_path_buf();
+
+        match managed_dirs::classify_path(&managed_dir, &relative_dir)? {
+            PathClassification::IsManaged => {}
+            PathClassification::InsideManaged(parent_managed) => bail!(
+                "'{}' is part of managed directory '{}/'. Split that directory first.",
+                relative_dir.display(),
+                parent_managed.display()
+            ),
+            PathClassification::Unmanaged => bail!(
+                "'{}' is not a managed directory of project '{project_name}'.",
+                relative_dir.display()
+            ),
+        }
+        if user_path.symlink_metadata().is_ok() && !user_path.is_symlink() {
+            bail!(
+                "'{}' exists but is not a puff symlink. Resolve it first, then re-run the command.",
+                relative_dir.display()
+            );
+        }
+
+        let managed_target = managed_dir.join(&relative_dir);
+        let excludes = PuffIgnore::read(&managed_target)?;
+        let mut entries = SplitEntries::default();
+        collect_entries(&managed_target, &managed_target, &excludes, &mut entries)?;
+        let SplitEntries {
+            files,
+            dirs,
+            unmanaged,
+        } = entries;
+
+        if user_path.symlink_metadata().is_ok() {
+            remove_dir_symlink(&user_path)?;
+        }
+        fs::create_dir_all(&user_path)?;
+        for dir in &dirs {
+            fs::create_dir_all(user_path.join(dir))?;
+        }
+        for file in &files {
+            symlink_file(managed_target.join(file), user_path.join(file))?;
+        }
+        for path in &unmanaged {
+            move_path(&managed_target.join(path), &user_path.join(path))?;
+        }
+
+        let items: Vec<PathBuf> = files.iter().map(|f| relative_dir.join(f)).collect();
+        managed_dirs::remove_managed_dir(&managed_dir, &relative_dir)?;
+        ProjectMetadata::split_item(&managed_dir, &relative_dir, &items)?;
+
+        let handler = GitIgnoreHandler::new();
+        if let Some(target) =
+            handler.unignore_managed_path(&project_root, &managed_dir, &relative_dir)?
+        {
+            for item in &items {
+                handler.ignore_managed_path(&project_root, &managed_dir, item, target)?;
+            }
+            println!(
+                "Replaced '{}' with entries of its files in {target}.",
+                relative_dir.display()
+            );
+        }
+
+        println!(
+            "Split '{}/' into {} individually managed file(s) in project '{project_name}'.",
+            relative_dir.display(),
+            items.len()
+        );
+        if !unmanaged.is_empty() {
+            println!(
+                "Moved {} entries that aren't managed (.puffignore and the entries it excludes) into '{}/'.",
+                unmanaged.len(),
+                relative_dir.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Entries of a split directory, relative to it
+#[derive(Default)]
+struct SplitEntries {
+    /// Files that become individually managed
+    files: Vec<PathBuf>,
+    /// Subdirectories that get created in the real directory
+    dirs: Vec<PathBuf>,
+    /// The `.puffignore` and the entries it excludes, which move into the real directory
+    unmanaged: Vec<PathBuf>,
+}
+
+/// Collects the entries of `dir`, relative to `base`, whose `.puffignore` is `excludes`
+fn collect_entries(
+    base: &Path,
+    dir: &Path,
+    excludes: &PuffIgnore,
+    entries: &mut SplitEntries,
+) -> Result<()> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    for path in paths {
+        let relative = path.strip_prefix(base)?.to_path_buf();
+        let is_dir = path.is_dir() && !path.is_symlink();
+        if relative == Path::new(PUFF_IGNORE_FILE) || excludes.is_excluded(&relative, is_dir) {
+            entries.unmanaged.push(relative);
+        } else if is_dir {
+            entries.dirs.push(relative);
+            collect_entries(base, &path, excludes, entries)?;
+        } else {
+            entries.files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitCommand;
+    use crate::{
+        config::{locations::LocationsProvider, project_metadata::ProjectMetadata},
+        managed_dirs,
+    };
+    use std::{fs, path::Path};
+
+    #[cfg(unix)]
+    #[test]
+    fn split_links_every_file_of_managed_directory() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        let project_dir = fs::canonicalize(user_dir.path()).unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        fs::write(
+            locations_provider.get_config_file_path(),
+            format!(
+                r#"{{"projects":[{{"name":"app","id":"1","path":"{}"}}]}}"#,
+                project_dir.display()
+            ),
+        )
+        .unwrap();
+        let managed_dir = locations_provider.get_managed_dir("app");
+        fs::create_dir_all(managed_dir.join("config/nested")).unwrap();
+        fs::write(managed_dir.join("config/dev.env"), "KEY=1").unwrap();
+        fs::write(managed_dir.join("config/nested/db.env"), "DB=1").unwrap();
+        managed_dirs::add_managed_dir(&managed_dir, Path::new("config")).unwrap();
+        ProjectMetadata::record_item(&managed_dir, Path::new("config")).unwrap();
+        std::os::unix::fs::symlink(managed_dir.join("config"), project_dir.join("config")).unwrap();
+
+        SplitCommand::new(&locations_provider)
+            .split(Path::new("config"), &project_dir)
+            .unwrap();
+
+        assert!(!project_dir.join("config").is_symlink());
+        assert!(project_dir.join("config/dev.env").is_symlink());
+        assert_eq!(
+            "DB=1",
+            fs::read_to_string(project_dir.join("config/nested/db.env")).unwrap()
+        );
+        assert!(
+            managed_dirs::read_managed_dirs(&managed_dir)
+                .unwrap()
+                .is_empty()
+        );
+        let metadata = ProjectMetadata::read(&managed_dir).unwrap().unwrap();
+        assert!(metadata.item(Path::new("config")).is_none());
+        assert!(metadata.item(Path::new("config/nested/db.env")).is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn split_leaves_puffignore_and_excluded_entries_unmanaged() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        let project_dir = fs::canonicalize(user_dir.path()).unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        fs::write(
+            locations_provider.get_config_file_path(),
+            format!(
+                r#"{{"projects":[{{"name":"app","id":"1","path":"{}"}}]}}"#,
+                project_dir.display()
+            ),
+        )
+        .unwrap();
+        let managed_dir = locations_provider.get_managed_dir("app");
+        fs::create_dir_all(managed_dir.join("config/cache")).unwrap();
+        fs::write(managed_dir.join("config/dev.env"), "KEY=1").unwrap();
+        fs::write(managed_dir.join("config/cache/blob"), "x").unwrap();
+        fs::write(managed_dir.join("config/.puffignore"), "cache/\n").unwrap();
+        managed_dirs::add_managed_dir(&managed_dir, Path::new("config")).unwrap();
+        ProjectMetadata::record_item(&managed_dir, Path::new("config")).unwrap();
+        std::os::unix::fs::symlink(managed_dir.join("config"), project_dir.join("config")).unwrap();
+
+        SplitCommand::new(&locations_provider)
+            .split(Path::new("config"), &project_dir)
+            .unwrap();
+
+        assert!(project_dir.join("config/dev.env").is_symlink());
+        assert!(!project_dir.join("config/.puffignore").is_symlink());
+        assert!(!project_dir.join("config/cache").is_symlink());
+        assert_eq!(
+            "x",
+            fs::read_to_string(project_dir.join("config/cache/blob")).unwrap()
+        );
+        let metadata = ProjectMetadata::read(&managed_dir).unwrap().unwrap();
+        assert!(metadata.item(Path::new("config/dev.env")).is_some());
+        assert!(metadata.item(Path::new("config/.puffignore")).is_none());
+        assert!(metadata.item(Path::new("config/cache/blob")).is_none());
+    }
+}
@@ -1,7 +1,12 @@
 use anyhow::Result;
+use serde::Serialize;
 use std::path::Path;
 
-use crate::config::{locations::LocationsProvider, projects::ProjectsRetriever};
+use crate::{
+    config::{locations::LocationsProvider, projects::ProjectsRetriever},
+    ignore_coverage::{self, UncoveredItem},
+    output::{OutputFormat, ProjectView, print_json},
+};
 
 pub struct StatusCommand<'a> {
     locations_provider: &'a LocationsProvider,
@@ -19,19 +24,37 @@ impl<'a> StatusCommand<'a> {
         }
     }
 
-    pub fn status(&self, cwd: &Path) -> Result<()> {
+    pub fn status(&self, cwd: &Path, format: OutputFormat) -> Result<()> {
         let project = self.locations_provider.find_project_for_path(cwd);
 
-        match project {
-            Err(_) => {
-                println!("Current directory is not managed by any puff project.");
+        let (details, project_root) = match project {
+            Err(_) => (None, None),
+            Ok((project_name, project_root)) => (
+                self.projects_retriever.get_details(&project_name)?,
+                Some(project_root),
+            ),
+        };
+
+        let uncovered = match (&details, &project_root) {
+            (Some(details), Some(project_root)) => {
+                ignore_coverage::find_uncovered_items(project_root, &details.info().items)?
             }
-            Ok((project_name, _)) => {
-                let Some(details) = self.projects_retriever.get_details(&project_name)? else {
-                    println!("Current directory is not managed by any puff project.");
-                    return Ok(());
-                };
+            _ => vec![],
+        };
 
+        if format == OutputFormat::Json {
+            return print_json(&StatusView {
+                managed: details.is_some(),
+                project: details.as_ref().map(ProjectView::from),
+                uncovered,
+            });
+        }
+
+        match details {
+            None => {
+                println!("Current directory is not managed by any puff project.");
+            }
+            Some(details) => {
                 println!("Project: {}", details.info().name);
                 println!("Managed items:");
                 if details.info().items.is_empty() {
@@ -41,9 +64,20 @@ impl<'a> StatusCommand<'a> {
                         println!("  {}", item.display_name());
                     }
                 }
+                if let Some(project_root) = &project_root {
+                    ignore_coverage::print_warning(&uncovered, project_root);
+                }
             }
         }
 
         Ok(())
     }
 }
+
+#[derive(Serialize)]
+struct StatusView {
+    managed: bool,
+    project: Option<ProjectView>,
+    /// Managed items that git could commit
+    uncovered: Vec<UncoveredItem>,
+}
@@ -0,0 +1,216 @@
+use anyhow::{Context, Result, bail};
+use std::{
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    commands::add_command::AddCommand,
+    config::{locations::LocationsProvider, settings::Settings},
+    git::GitRepository,
+    io_utils::confirm,
+    output::{format_time, print_table},
+    shred::{ShredReport, shred_copies, shred_path},
+    trash::{Trash, TrashEntry},
+};
+
+/// Handler for `puff trash`, which manages the managed items and projects that were deleted
+pub struct TrashCommand<'a> {
+    locations_provider: &'a LocationsProvider,
+    settings: &'a Settings,
+    trash: Trash,
+}
+
+impl<'a> TrashCommand<'a> {
+    pub fn new(
+        locations_provider: &'a LocationsProvider,
+        settings: &'a Settings,
+    ) -> TrashCommand<'a> {
+        TrashCommand {
+            locations_provider,
+            settings,
+            trash: Trash::new(locations_provider.get_trash_path()),
+        }
+    }
+
+    pub fn list(&self) -> Result<()> {
+        let entries = self.trash.list()?;
+        if entries.is_empty() {
+            println!("The trash is empty.");
+            return Ok(());
+        }
+        let rows: Vec<[String; 5]> = entries
+            .iter()
+            .map(|entry| {
+                [
+                    entry.id.clone(),
+                    entry.project.clone(),
+                    match &entry.item {
+                        Some(item) => {
+                            format!(
+                                "{}{}",
+                                item.display(),
+                                if entry.directory { "/" } else { "" }
+                            )
+                        }
+                        None => "(whole project)".to_owned(),
+                    },
+                    format_time(deleted(entry)),
+                    entry
+                        .original_path
+                        .as_ref()
+                        .map(|p| p.display().to_string())
+                        .unwrap_or_else(|| "-".to_owned()),
+                ]
+            })
+            .collect();
+        print_table(["ID", "PROJECT", "ITEM", "DELETED", "FROM"], &rows);
+        Ok(())
+    }
+
+    /// Moves a deleted item back to where it was deleted from (or to `to`, relative to
+    /// `current_dir`) and adds it to the project there again, along with an ignore entry
+    /// (in a git repository) unless git already ignores it. Items are not restored into a git repository outside
+    /// of any project, where nothing would keep git from committing them. A deleted
+    /// project goes back to puff's data directory as an unassociated project.
+    pub fn restore(&self, id: &str, to: Option<&Path>, current_dir: &Path) -> Result<()> {
+        let Some(entry) = self.trash.list()?.into_iter().find(|e| e.id == id) else {
+            bail!("No entry '{id}' in the trash. Run 'puff trash list' to see the deleted items.");
+        };
+
+        let destination = match (&entry.item, to) {
+            (Some(_), Some(to)) => current_dir.join(to),
+            (Some(item), None) => match &entry.original_path {
+                Some(original_path) => original_path.clone(),
+                None => bail!(
+                    "It's not known where '{}' was deleted from. Use --to to choose where to restore it.",
+                    item.display()
+                ),
+            },
+            (None, Some(_)) => bail!(
+                "Deleted projects are restored to puff's data directory, --to only applies to items."
+            ),
+            (None, None) => {
+                let managed_dir = self.locations_provider.get_managed_dir(&entry.project);
+                if managed_dir.exists() {
+                    bail!(
+                        "Project '{}' exists in puff. Forget or rename it before restoring the deleted one.",
+                        entry.project
+                    );
+                }
+                managed_dir
+            }
+        };
+
+        let in_project = entry.item.is_some()
+            && self
+                .locations_provider
+                .find_project_for_path(&destination)
+                .is_ok();
+        let in_repository = GitRepository::discover(&destination)?.is_some();
+        if entry.item.is_some() && !in_project && in_repository {
+            bail!(
+                "'{}' is inside a git repository but not inside a puff project, so git could commit the restored item. Restore it into a project or elsewhere with --to.",
+                destination.display()
+            );
+        }
+
+        let entry = self.trash.restore(id, &destination)?;
+        let Some(item) = &entry.item else {
+            println!(
+                "Restored project '{0}'. Run 'puff init --associate {0}' in its directory to use it again.",
+                entry.project
+            );
+            return Ok(());
+        };
+        if !in_project {
+            println!(
+                "Restored '{}' of project '{}' to {}.",
+                item.display(),
+                entry.project,
+                destination.display()
+            );
+            return Ok(());
+        }
+
+        let settings = self
+            .locations_provider
+            .settings_for_path(&destination, self.settings)?;
+        AddCommand::new(self.locations_provider)
+            .with_file_mode(settings.managed_file_mode)
+            .add_file(
+                destination.clone(),
+                current_dir,
+                in_repository.then_some(settings.git_ignore_target),
+                entry.directory,
+            )
+            .with_context(|| {
+                format!(
+                    "'{}' was restored to {} but could not be added to puff again",
+                    item.display(),
+                    destination.display()
+                )
+            })?;
+        println!(
+            "Restored '{}' of project '{}' to {}.",
+            item.display(),
+            entry.project,
+            destination.display()
+        );
+        Ok(())
+    }
+
+    /// Deletes the entries (only the ones deleted more than `older_than` days ago, if
+    /// given) for good. With `shred`, their content and the backups of the same items get
+    /// shredded.
+    pub fn empty(
+        &self,
+        older_than: Option<u32>,
+        shred: bool,
+        skip_confirmation: bool,
+    ) -> Result<()> {
+        let cutoff = older_than.map(|days| {
+            SystemTime::now()
+                .checked_sub(Duration::from_secs(u64::from(days) * 86_400))
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        });
+        let is_removed = |entry: &TrashEntry| cutoff.is_none_or(|cutoff| deleted(entry) < cutoff);
+
+        let count = self.trash.list()?.iter().filter(|e| is_removed(e)).count();
+        if count == 0 {
+            println!("Nothing to remove from the trash.");
+            return Ok(());
+        }
+        if !skip_confirmation
+            && !confirm(format!(
+                "Permanently delete {count} item(s) from the trash?"
+            ))?
+        {
+            return Ok(());
+        }
+
+        if !shred {
+            self.trash.empty(is_removed)?;
+            println!("Permanently deleted {count} item(s).");
+            return Ok(());
+        }
+
+        let mut report = ShredReport::default();
+        for entry in self.trash.list()?.iter().filter(|e| is_removed(e)) {
+            shred_path(&self.trash.stored_path(entry), &mut report)?;
+            shred_copies(
+                self.locations_provider,
+                &entry.project_id,
+                entry.item.as_deref(),
+                &mut report,
+            )?;
+        }
+        self.trash.empty(is_removed)?;
+        println!("Permanently deleted {count} item(s).");
+        report.finish()
+    }
+}
+
+fn deleted(entry: &TrashEntry) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(entry.deleted_at)
+}
@@ -1,3 +1,6 @@
 pub mod app_config;
 pub mod locations;
+pub mod project_metadata;
 pub mod projects;
+pub mod roots;
+pub mod settings;
@@ -1,15 +1,23 @@
 use anyhow::{Result, bail};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fs::File,
     io::{BufReader, BufWriter},
     path::{Path, PathBuf},
 };
 use uuid::Uuid;
 
+use crate::config::{roots::PathMapper, settings::Settings};
+
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
     pub projects: Vec<Project>,
+    #[serde(default, skip_serializing_if = "Settings::is_default")]
+    pub settings: Settings,
+    /// User-defined roots (name -> path) that project paths are stored relative to
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub roots: BTreeMap<String, String>,
 }
 
 impl AppConfig {
@@ -28,14 +36,25 @@ pub struct Project {
 impl Project {
     /// Creates a new instance of Project
     pub fn new(name: &str, user_dir: &Path) -> Project {
+        Project::with_id(name, &Uuid::new_v4().to_string(), user_dir)
+    }
+
+    /// Creates an instance of Project with a known id (e.g. from a .puff marker file)
+    pub fn with_id(name: &str, id: &str, user_dir: &Path) -> Project {
         Project {
             name: name.to_owned(),
             path: user_dir.to_owned(),
-            id: Uuid::new_v4().to_string(),
+            id: id.to_owned(),
         }
     }
 }
 
+/// Checks whether `id` is a proper project id. Old versions of puff stored placeholders
+/// like "1" instead, which the startup migration replaces.
+pub fn is_valid_id(id: &str) -> bool {
+    Uuid::parse_str(id).is_ok()
+}
+
 /// A manager of puff's configuration file config.json.
 /// Any modifications of that file should go through this
 /// struct's functions.
@@ -52,38 +71,80 @@ impl AppConfigManager {
         Ok(AppConfigManager { config_file_path })
     }
 
-    /// Returns the current content of the config.json file
+    /// Returns the current content of the config.json file, with project paths
+    /// expanded to absolute ones
     pub fn get_config(&self) -> Result<AppConfig> {
         let file = File::open(&self.config_file_path)?;
         let reader = BufReader::new(file);
-        let config: AppConfig = serde_json::from_reader(reader)?;
+        let mut config: AppConfig = serde_json::from_reader(reader)?;
+
+        let mapper = PathMapper::new(&config.roots);
+        for project in &mut config.projects {
+            project.path = mapper.expand(&project.path);
+        }
 
         Ok(config)
     }
 
-    /// Adds a new entry to the file. If an entry with the same 'name' already
+    /// Adds a new entry to the file. If an entry with the same 'name' or 'id' already
     /// exists, an error will be returned.
     ///
     /// WARNING: The is function modifies the config.json file, even though function's
     /// signature does not have any 'mut'.
-    pub fn add_project(&self, project_name: &str, user_dir: &Path) -> Result<()> {
+    pub fn register_project(&self, project: Project) -> Result<()> {
         let mut config = self.get_config()?;
 
-        if config.projects.iter().any(|p| p.name == project_name) {
+        if config.projects.iter().any(|p| p.name == project.name) {
             bail!(
                 "A project named '{}' already exists in puff's registry.",
-                project_name
+                project.name
+            );
+        }
+        if let Some(existing) = config.projects.iter().find(|p| p.id == project.id) {
+            bail!(
+                "Project '{}' is already registered in puff's registry as '{}'.",
+                project.name,
+                existing.name
             );
         }
 
-        config.projects.push(Project::new(project_name, user_dir));
+        config.projects.push(project);
         self.save_config(&config)?;
 
         Ok(())
     }
 
-    /// Saves provided config to the config.json file
-    fn save_config(&self, config: &AppConfig) -> Result<()> {
+    /// Associates an already registered project (identified by its id) with a different
+    /// directory
+    pub fn update_project_path(&self, id: &str, user_dir: &Path) -> Result<()> {
+        let mut config = self.get_config()?;
+
+        let Some(project) = config.projects.iter_mut().find(|p| p.id == id) else {
+            bail!(
+                "Project with id '{}' is not associated with any directory.",
+                id
+            );
+        };
+        project.path = user_dir.to_owned();
+        self.save_config(&config)
+    }
+
+    /// Replaces the global settings stored in the file
+    pub fn save_settings(&self, settings: &Settings) -> Result<()> {
+        let mut config = self.get_config()?;
+        config.settings = settings.clone();
+        self.save_config(&config)
+    }
+
+    /// Saves provided config to the config.json file. Project paths are stored relative
+    /// to the home directory or to one of the roots when possible.
+    pub(crate) fn save_config(&self, config: &AppConfig) -> Result<()> {
+        let mut config = config.clone();
+        let mapper = PathMapper::new(&config.roots);
+        for project in &mut config.projects {
+            project.path = mapper.contract(&project.path);
+        }
+
         let file = File::create(&self.config_file_path)?;
         let writer = BufWriter::new(file);
         serde_json::to_writer_pretty(writer, &config)?;
@@ -91,10 +152,11 @@ impl AppConfigManager {
         Ok(())
     }
 
-    pub(crate) fn remove_project(&self, name: &str) -> Result<()> {
+    /// Removes the entry of the project with the given id
+    pub(crate) fn remove_project(&self, id: &str) -> Result<()> {
         let mut config = self.get_config()?;
 
-        let index = config.projects.iter().position(|p| p.name == name);
+        let index = config.projects.iter().position(|p| p.id == id);
         if index.is_none() {
             return Ok(());
         }
@@ -140,7 +202,7 @@ mod tests {
     }
 
     #[test]
-    fn add_project_project_gets_added_to_file() {
+    fn register_project_project_gets_added_to_file() {
         let base_dir = tempfile::tempdir().unwrap();
         let config_file = base_dir.path().join("config.json");
         let mut file = File::create(&config_file).unwrap();
@@ -153,7 +215,7 @@ mod tests {
 
         let new_proj_dir = tempfile::tempdir().unwrap();
         config_manager
-            .add_project("new_proj", new_proj_dir.path())
+            .register_project(Project::new("new_proj", new_proj_dir.path()))
             .unwrap();
 
         let file = config_manager.get_config().unwrap();
@@ -166,6 +228,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn register_project_with_registered_id_fails() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config_file = base_dir.path().join("config.json");
+        let mut file = File::create(&config_file).unwrap();
+        write!(
+            file,
+            "{{\"projects\":[{{\"name\":\"name1\", \"path\":\"path1\", \"id\":\"1\"}}]}}"
+        )
+        .unwrap();
+        let config_manager = AppConfigManager::new(config_file.clone()).unwrap();
+
+        let result =
+            config_manager.register_project(Project::with_id("renamed", "1", Path::new("path2")));
+
+        assert!(result.is_err());
+        assert_eq!(1, config_manager.get_config().unwrap().projects.len());
+    }
+
+    #[test]
+    fn update_project_path_only_the_path_of_the_project_changes() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let config_file = base_dir.path().join("config.json");
+        let mut file = File::create(&config_file).unwrap();
+        write!(
+            file,
+            "{{\"projects\":[{{\"name\":\"name1\", \"path\":\"path1\", \"id\":\"1\"}}]}}"
+        )
+        .unwrap();
+        let config_manager = AppConfigManager::new(config_file.clone()).unwrap();
+
+        config_manager
+            .update_project_path("1", Path::new("path2"))
+            .unwrap();
+
+        let file = config_manager.get_config().unwrap();
+        assert_eq!(1, file.projects.len());
+        assert_eq!(Path::new("path2"), file.projects[0].path);
+        assert_eq!("1", file.projects[0].id);
+    }
+
     #[test]
     fn save_config_config_gets_saved() {
         let base_dir = tempfile::tempdir().unwrap();
@@ -191,6 +294,7 @@ mod tests {
                     path: Path::new(base_dir.path().to_str().unwrap()).to_path_buf(),
                 },
             ],
+            ..Default::default()
         };
 
         config_manager.save_config(&app_config).unwrap();
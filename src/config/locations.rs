@@ -2,7 +2,10 @@ use anyhow::{Result, anyhow};
 use directories::ProjectDirs;
 use std::path::{Path, PathBuf};
 
-use super::app_config::AppConfigManager;
+use super::{app_config::AppConfigManager, project_metadata::ProjectMetadata, settings::Settings};
+use crate::backups::BACKUPS_DIR;
+use crate::marker::ProjectMarker;
+use crate::trash::TRASH_DIR;
 
 const APP_NAME: &str = "puff";
 
@@ -31,6 +34,16 @@ impl LocationsProvider {
         self.data_base_path.join(Path::new("projects"))
     }
 
+    /// Where backups of files that puff replaced are kept, see [`crate::backups::BackupStore`]
+    pub fn get_backups_path(&self) -> PathBuf {
+        self.data_base_path.join(Path::new(BACKUPS_DIR))
+    }
+
+    /// Where deleted managed items and projects are kept, see [`crate::trash::Trash`]
+    pub fn get_trash_path(&self) -> PathBuf {
+        self.data_base_path.join(Path::new(TRASH_DIR))
+    }
+
     /// Legacy path used before the config/data split. Used for migration.
     pub fn get_legacy_configs_path(&self) -> PathBuf {
         self.config_base_path.join(Path::new("configs"))
@@ -47,19 +60,47 @@ impl LocationsProvider {
     /// Walks up from `path` through its ancestors and returns the first
     /// (deepest/most-specific) registered project root found, along with the
     /// project name. Mirrors how git handles nested repos.
+    /// A directory with a `.puff` marker file of a registered project counts as that
+    /// project's root too, which is how worktrees, clones and moved checkouts are recognized.
     pub fn find_project_for_path(&self, path: &Path) -> Result<(String, PathBuf)> {
         let config = AppConfigManager::new(self.get_config_file_path())?.get_config()?;
 
+        let mut unregistered_marker = None;
         for ancestor in path.ancestors() {
             if let Some(project) = config.projects.iter().find(|p| p.path == ancestor) {
                 return Ok((project.name.clone(), project.path.clone()));
             }
+
+            if let Some(marker) = ProjectMarker::read(ancestor)? {
+                match marker.find_project(&config) {
+                    Some(project) => return Ok((project.name.clone(), ancestor.to_owned())),
+                    None => unregistered_marker = unregistered_marker.or(Some(marker.name)),
+                }
+            }
+        }
+
+        if let Some(name) = unregistered_marker {
+            return Err(anyhow!(
+                "The current directory belongs to project '{name}' (according to its .puff file), but the project is not associated on this machine. Run 'puff init' to associate it."
+            ));
         }
 
         Err(anyhow!(
             "The current directory is not associated with any puff project. Run 'puff init' to initialize it."
         ))
     }
+
+    /// Returns the settings that apply in `path`: the global ones, overridden by the
+    /// settings of the project that `path` belongs to (if any)
+    pub fn settings_for_path(&self, path: &Path, global: &Settings) -> Result<Settings> {
+        let Ok((name, _)) = self.find_project_for_path(path) else {
+            return Ok(global.clone());
+        };
+        Ok(match ProjectMetadata::read(&self.get_managed_dir(&name))? {
+            Some(metadata) => metadata.settings.apply(global),
+            None => global.clone(),
+        })
+    }
 }
 
 impl Default for LocationsProvider {
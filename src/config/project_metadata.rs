@@ -0,0 +1,258 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use uuid::Uuid;
+
+use super::settings::ProjectSettings;
+
+/// File in the root of a project's managed dir that describes the project. Unlike
+/// config.json, which only maps projects to paths on this machine, it travels with the
+/// data directory.
+pub(crate) const PROJECT_METADATA_FILE: &str = ".puff_project.json";
+
+/// Machine-independent information about a project
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProjectMetadata {
+    pub id: String,
+    /// Unix time (in seconds) of the project's creation. Unknown for projects created
+    /// before puff recorded it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "ProjectSettings::is_default")]
+    pub settings: ProjectSettings,
+    /// Metadata of managed items, keyed by their path relative to the project ('/'-separated)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub items: BTreeMap<String, ItemMetadata>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ItemMetadata {
+    /// Unix time (in seconds) when the item was added to puff
+    pub added_at: u64,
+}
+
+impl ProjectMetadata {
+    /// Metadata of a project created just now
+    pub fn new(id: &str) -> Self {
+        ProjectMetadata {
+            id: id.to_owned(),
+            created_at: Some(now()),
+            description: None,
+            settings: ProjectSettings::default(),
+            items: BTreeMap::new(),
+        }
+    }
+
+    /// Reads the metadata of the project stored in `managed_dir`, if there is any
+    pub fn read(managed_dir: &Path) -> Result<Option<Self>> {
+        let path = managed_dir.join(PROJECT_METADATA_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let metadata = serde_json::from_str(&content)
+            .with_context(|| format!("Project metadata '{}' is not valid.", path.display()))?;
+        Ok(Some(metadata))
+    }
+
+    /// Reads the metadata, creating it (with a new id) when it's missing
+    pub fn load(managed_dir: &Path) -> Result<Self> {
+        match Self::read(managed_dir)? {
+            Some(metadata) => Ok(metadata),
+            None => {
+                let metadata = ProjectMetadata {
+                    created_at: None,
+                    ..ProjectMetadata::new(&Uuid::new_v4().to_string())
+                };
+                metadata.write(managed_dir)?;
+                Ok(metadata)
+            }
+        }
+    }
+
+    /// Reads the metadata of all projects stored in `projects_path`, along with the names
+    /// of their managed dirs. Directories without metadata, or with metadata that can't be
+    /// read, are skipped; the startup migration warns about the latter.
+    pub fn read_all(projects_path: &Path) -> Result<Vec<(String, Self)>> {
+        let mut result = vec![];
+        let Ok(entries) = fs::read_dir(projects_path) else {
+            return Ok(result);
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if path.is_dir()
+                && let Ok(Some(metadata)) = Self::read(&path)
+            {
+                result.push((name.to_owned(), metadata));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Finds the name of the project with the given id among the projects stored in
+    /// `projects_path`
+    pub fn find_name(projects_path: &Path, id: &str) -> Result<Option<String>> {
+        Ok(Self::read_all(projects_path)?
+            .into_iter()
+            .find(|(_, metadata)| metadata.id == id)
+            .map(|(name, _)| name))
+    }
+
+    pub fn write(&self, managed_dir: &Path) -> Result<()> {
+        fs::write(
+            managed_dir.join(PROJECT_METADATA_FILE),
+            serde_json::to_string_pretty(self)? + "\n",
+        )?;
+        Ok(())
+    }
+
+    /// Loads the metadata, applies `change` and saves the result
+    pub fn update(managed_dir: &Path, change: impl FnOnce(&mut Self)) -> Result<()> {
+        let mut metadata = Self::load(managed_dir)?;
+        change(&mut metadata);
+        metadata.write(managed_dir)
+    }
+
+    /// Records that the item at `relative_path` was just added to puff. Items inside it
+    /// (files absorbed by a newly managed directory) are dropped.
+    pub fn record_item(managed_dir: &Path, relative_path: &Path) -> Result<()> {
+        let key = item_key(relative_path);
+        let prefix = format!("{key}/");
+        Self::update(managed_dir, |m| {
+            m.items.retain(|k, _| !k.starts_with(&prefix));
+            m.items.insert(key, ItemMetadata { added_at: now() });
+        })
+    }
+
+    pub fn forget_item(managed_dir: &Path, relative_path: &Path) -> Result<()> {
+        Self::update(managed_dir, |m| {
+            m.items.remove(&item_key(relative_path));
+        })
+    }
+
+    /// Moves the metadata of the item at `from` to `to`, keeping the time it was added
+    pub fn move_item(managed_dir: &Path, from: &Path, to: &Path) -> Result<()> {
+        Self::update(managed_dir, |m| {
+            let item = m
+                .items
+                .remove(&item_key(from))
+                .unwrap_or(ItemMetadata { added_at: now() });
+            m.items.insert(item_key(to), item);
+        })
+    }
+
+    /// Replaces the item of a managed directory with items of the files in it, which keep
+    /// the time the directory was added
+    pub fn split_item(managed_dir: &Path, relative_dir: &Path, files: &[PathBuf]) -> Result<()> {
+        Self::update(managed_dir, |m| {
+            let item = m
+                .items
+                .remove(&item_key(relative_dir))
+                .unwrap_or(ItemMetadata { added_at: now() });
+            for file in files {
+                m.items.insert(item_key(file), item.clone());
+            }
+        })
+    }
+
+    pub fn item(&self, relative_path: &Path) -> Option<&ItemMetadata> {
+        self.items.get(&item_key(relative_path))
+    }
+
+    pub fn created(&self) -> Option<SystemTime> {
+        self.created_at.map(to_system_time)
+    }
+}
+
+impl ItemMetadata {
+    pub fn added(&self) -> SystemTime {
+        to_system_time(self.added_at)
+    }
+}
+
+fn item_key(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn to_system_time(seconds: u64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProjectMetadata;
+    use std::path::Path;
+
+    #[test]
+    fn load_creates_missing_metadata_once() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = ProjectMetadata::load(dir.path()).unwrap();
+        let second = ProjectMetadata::load(dir.path()).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(None, first.created_at);
+    }
+
+    #[test]
+    fn record_item_and_forget_item_update_items() {
+        let dir = tempfile::tempdir().unwrap();
+        ProjectMetadata::new("1").write(dir.path()).unwrap();
+
+        ProjectMetadata::record_item(dir.path(), Path::new("config/.env")).unwrap();
+        let metadata = ProjectMetadata::read(dir.path()).unwrap().unwrap();
+        assert!(metadata.item(Path::new("config/.env")).is_some());
+        assert_eq!("1", metadata.id);
+
+        ProjectMetadata::forget_item(dir.path(), Path::new("config/.env")).unwrap();
+        let metadata = ProjectMetadata::read(dir.path()).unwrap().unwrap();
+        assert!(metadata.items.is_empty());
+    }
+
+    #[test]
+    fn move_item_keeps_added_time() {
+        let dir = tempfile::tempdir().unwrap();
+        ProjectMetadata::new("1").write(dir.path()).unwrap();
+        ProjectMetadata::record_item(dir.path(), Path::new("config/dev.env")).unwrap();
+        let added =
+            ProjectMetadata::read(dir.path()).unwrap().unwrap().items["config/dev.env"].added_at;
+
+        ProjectMetadata::move_item(
+            dir.path(),
+            Path::new("config/dev.env"),
+            Path::new("config/local.env"),
+        )
+        .unwrap();
+
+        let metadata = ProjectMetadata::read(dir.path()).unwrap().unwrap();
+        assert!(metadata.item(Path::new("config/dev.env")).is_none());
+        assert_eq!(
+            added,
+            metadata
+                .item(Path::new("config/local.env"))
+                .unwrap()
+                .added_at
+        );
+    }
+}
@@ -1,9 +1,16 @@
-use super::{app_config::AppConfig, locations::LocationsProvider};
-use crate::managed_dirs;
+use super::{
+    app_config::{AppConfig, Project},
+    locations::LocationsProvider,
+    project_metadata::ProjectMetadata,
+};
+use crate::{managed_dirs, puff_ignore::PuffIgnore};
 use anyhow::{Result, bail};
+use serde::Serialize;
 use std::{
+    fmt::Display,
     fs,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 pub struct ProjectsRetriever<'a> {
@@ -24,22 +31,28 @@ impl<'a> ProjectsRetriever<'a> {
     }
 
     pub fn get_details(&self, project_name: &str) -> Result<Option<ProjectDetails>> {
-        let project_config = self
-            .app_config
-            .projects
-            .iter()
-            .find(|p| p.name == project_name);
-
         let managed_dir = self.locations_provider.get_managed_dir(project_name);
         if !managed_dir.exists() {
             return Ok(None);
         }
 
         let items = collect_items_recursively(&managed_dir, &managed_dir)?;
+        let stored_metadata = ProjectMetadata::read(&managed_dir)?;
+        let project_config = self.find_association(project_name, stored_metadata.as_ref());
+        // Metadata is created for every project at startup; the fallback only covers
+        // data directories that appeared in the meantime
+        let metadata = match stored_metadata {
+            Some(metadata) => metadata,
+            None => ProjectMetadata {
+                created_at: None,
+                ..ProjectMetadata::new(project_config.map(|p| p.id.as_str()).unwrap_or_default())
+            },
+        };
         let info = ProjectInfo {
             name: project_name.to_owned(),
             managed_dir,
             items,
+            metadata,
         };
 
         Ok(Some(match project_config {
@@ -63,31 +76,57 @@ impl<'a> ProjectsRetriever<'a> {
     /// with any user's directory
     pub fn get_unassociated_projects(&self) -> Result<Vec<String>> {
         let all = self.get_all_projects()?;
-        let associated: Vec<_> = self.app_config.projects.iter().map(|p| &p.name).collect();
 
-        if all.len() < associated.len() {
-            // TODO: What should user do in such scenario?
-            bail!(
-                "puff configuration is corrupted: the registry references projects that no longer exist on disk."
-            );
+        let mut unassociated = vec![];
+        for name in all {
+            let metadata = ProjectMetadata::read(&self.locations_provider.get_managed_dir(&name))?;
+            if self.find_association(&name, metadata.as_ref()).is_none() {
+                unassociated.push(name);
+            }
         }
+        Ok(unassociated)
+    }
 
-        Ok(all
+    /// Returns the registry entries whose data directory doesn't exist (e.g. it was
+    /// deleted, or not synced to this machine yet). `puff reconcile` fixes them.
+    pub fn get_entries_without_data(&self) -> Vec<Project> {
+        self.app_config
+            .projects
             .iter()
-            .filter(|n| !associated.contains(n))
+            .filter(|p| !self.locations_provider.get_managed_dir(&p.name).is_dir())
             .cloned()
-            .collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// Finds the registry entry of a stored project. Entries are matched by the id from
+    /// the project's metadata, so a project renamed on another machine is still found.
+    /// Only projects without metadata are matched by name.
+    fn find_association(
+        &self,
+        project_name: &str,
+        metadata: Option<&ProjectMetadata>,
+    ) -> Option<&Project> {
+        let projects = &self.app_config.projects;
+        match metadata {
+            Some(metadata) => projects.iter().find(|p| p.id == metadata.id),
+            None => projects.iter().find(|p| p.name == project_name),
+        }
     }
 
     /// Returns names of all the projects that puff stores (some of them might
-    /// not be associated yet)
+    /// not be associated yet). Every managed dir is a project; its identity comes from
+    /// the metadata file inside it.
     fn get_all_projects(&self) -> Result<Vec<String>> {
         let location = self.locations_provider.get_projects_data_path();
         let paths = fs::read_dir(location)?;
 
         let mut projects = vec![];
         for path in paths {
-            let name = path?.file_name().into_string();
+            let path = path?;
+            if !path.file_type()?.is_dir() {
+                continue;
+            }
+            let name = path.file_name().into_string();
             match name {
                 Ok(name) => projects.push(name),
                 Err(osstr) => {
@@ -106,6 +145,8 @@ pub struct ProjectInfo {
     pub name: String,
     pub managed_dir: PathBuf,
     pub items: Vec<ManagedItem>,
+    /// Machine-independent details stored in the managed dir
+    pub metadata: ProjectMetadata,
 }
 
 #[non_exhaustive]
@@ -132,6 +173,97 @@ impl ProjectDetails {
             ProjectDetails::Unassociated(info) => info,
         }
     }
+
+    /// Checks whether the associated path exists and all managed items are linked in it.
+    /// Unassociated projects have no health to report.
+    pub fn health(&self) -> Option<ProjectHealth> {
+        let ProjectDetails::Associated(associated) = self else {
+            return None;
+        };
+
+        if !associated.user_dir.is_dir() {
+            return Some(ProjectHealth::MissingPath);
+        }
+
+        let all_linked = associated.info.items.iter().all(|item| {
+            item.link_state(&associated.user_dir, &associated.info.managed_dir) == LinkState::Linked
+        });
+
+        Some(if all_linked {
+            ProjectHealth::Ok
+        } else {
+            ProjectHealth::BrokenLinks
+        })
+    }
+}
+
+impl ProjectInfo {
+    /// Counts managed items and measures the project's footprint in the data store.
+    pub fn stats(&self) -> Result<ProjectStats> {
+        let mut stats = ProjectStats {
+            files: self.items.iter().filter(|i| !i.is_directory()).count(),
+            directories: self.items.iter().filter(|i| i.is_directory()).count(),
+            size: 0,
+            modified: None,
+        };
+
+        let mut stack = vec![self.managed_dir.clone()];
+        while let Some(current) = stack.pop() {
+            for entry in fs::read_dir(&current)? {
+                let path = entry?.path();
+                if managed_dirs::is_metadata_file(&self.managed_dir, &path) {
+                    continue;
+                }
+
+                let metadata = fs::symlink_metadata(&path)?;
+                if let Ok(modified) = metadata.modified() {
+                    stats.modified = stats.modified.max(Some(modified));
+                }
+                if metadata.is_dir() {
+                    stack.push(path);
+                } else {
+                    stats.size += metadata.len();
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Summary of a project's managed items in the data store.
+#[derive(Default)]
+pub struct ProjectStats {
+    pub files: usize,
+    pub directories: usize,
+    /// Total size of managed files, in bytes
+    pub size: u64,
+    /// The most recent modification time of any managed file or directory
+    pub modified: Option<SystemTime>,
+}
+
+/// Whether an associated project's path and symlinks are in place.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectHealth {
+    Ok,
+    /// Some managed items are missing or replaced in the associated path
+    BrokenLinks,
+    /// The associated path does not exist on this machine
+    MissingPath,
+    /// Puff's copy of the project in the data store can't be read
+    UnreadableData,
+}
+
+impl Display for ProjectHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectHealth::Ok => write!(f, "ok"),
+            ProjectHealth::BrokenLinks => write!(f, "broken links"),
+            ProjectHealth::MissingPath => write!(f, "missing path"),
+            ProjectHealth::UnreadableData => write!(f, "unreadable data"),
+        }
+    }
 }
 
 /// A managed item: either a single file or an entire directory.
@@ -158,13 +290,50 @@ impl ManagedItem {
             ManagedItem::Directory(p) => format!("{}/", p.display()),
         }
     }
+
+    /// Checks what occupies the item's location in `user_dir`, comparing it against the
+    /// managed copy in `managed_dir`.
+    pub fn link_state(&self, user_dir: &Path, managed_dir: &Path) -> LinkState {
+        let user_path = user_dir.join(self.path());
+        if user_path.symlink_metadata().is_err() {
+            return LinkState::Missing;
+        }
+
+        match fs::read_link(&user_path) {
+            Ok(target) if target == managed_dir.join(self.path()) => LinkState::Linked,
+            _ => LinkState::Conflict,
+        }
+    }
+}
+
+/// State of a managed item's location in a project directory.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkState {
+    /// The path is a symlink pointing to the managed copy
+    Linked,
+    /// Nothing exists at the path
+    Missing,
+    /// The path is occupied by a regular file, a directory or a foreign symlink
+    Conflict,
+}
+
+impl Display for LinkState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkState::Linked => write!(f, "linked"),
+            LinkState::Missing => write!(f, "missing"),
+            LinkState::Conflict => write!(f, "conflict"),
+        }
+    }
 }
 
 /// Collects all managed items under `dir`, returning their paths relative to `base`.
 /// Managed directories are yielded as single entries instead of being recursed into.
+/// Entries excluded by the `.puffignore` of `base` are skipped.
 fn collect_items_recursively(base: &Path, dir: &Path) -> Result<Vec<ManagedItem>> {
     let managed_dir_set = managed_dirs::read_managed_dirs_set(base)?;
-    let managed_dirs_filename = managed_dirs::managed_dirs_filename();
+    let excludes = PuffIgnore::read(base)?;
 
     let mut items = vec![];
     let mut stack = vec![dir.to_owned()];
@@ -173,12 +342,8 @@ fn collect_items_recursively(base: &Path, dir: &Path) -> Result<Vec<ManagedItem>
             let path = entry?.path();
             let relative = path.strip_prefix(base)?.to_owned();
 
-            if path.is_file()
-                && path
-                    .file_name()
-                    .map(|n| n == managed_dirs_filename)
-                    .unwrap_or(false)
-                && path.parent() == Some(base)
+            if managed_dirs::is_metadata_file(base, &path)
+                || excludes.is_excluded(&relative, path.is_dir())
             {
                 continue;
             }
@@ -205,7 +370,7 @@ mod tests {
 
     use std::path::Path;
 
-    use super::ProjectsRetriever;
+    use super::{LinkState, ManagedItem, ProjectDetails, ProjectHealth, ProjectsRetriever};
 
     #[test]
     fn is_associated_when_associated_project_is_provided_true_is_returned() {
@@ -222,6 +387,7 @@ mod tests {
                 id: String::from("1"),
                 path: Path::new(checked_dir.path().to_str().unwrap()).to_path_buf(),
             }],
+            ..Default::default()
         };
 
         let sut = ProjectsRetriever::new(app_config, &locations_provider);
@@ -239,7 +405,7 @@ mod tests {
             config_dir.path().to_path_buf(),
             data_dir.path().to_path_buf(),
         );
-        let app_config = AppConfig { projects: vec![] };
+        let app_config = AppConfig::default();
 
         let sut = ProjectsRetriever::new(app_config, &locations_provider);
 
@@ -283,6 +449,7 @@ mod tests {
                     path: Path::new(proj_3_dir.path().to_str().unwrap()).to_path_buf(),
                 },
             ],
+            ..Default::default()
         };
 
         let sut = ProjectsRetriever::new(app_config, &locations_provider);
@@ -320,6 +487,7 @@ mod tests {
                     path: Path::new(proj_2_dir.path().to_str().unwrap()).to_path_buf(),
                 },
             ],
+            ..Default::default()
         };
 
         let sut = ProjectsRetriever::new(app_config, &locations_provider);
@@ -330,6 +498,35 @@ mod tests {
         assert!(result.first().unwrap() == "proj3");
     }
 
+    #[test]
+    fn get_unassociated_projects_when_some_entries_have_no_data_remaining_projects_are_returned() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let config_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(data_dir.path().join("projects/proj3")).unwrap();
+
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let app_config = AppConfig {
+            projects: vec![
+                Project::with_id("proj1", "1", Path::new("/proj1")),
+                Project::with_id("proj2", "2", Path::new("/proj2")),
+            ],
+            ..Default::default()
+        };
+
+        let sut = ProjectsRetriever::new(app_config, &locations_provider);
+
+        assert_eq!(vec!["proj3"], sut.get_unassociated_projects().unwrap());
+        let without_data: Vec<_> = sut
+            .get_entries_without_data()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(vec!["proj1", "proj2"], without_data);
+    }
+
     #[test]
     fn get_all_projects_when_some_projects_exist_then_proper_vector_is_returned() {
         let proj_1_dir = tempfile::tempdir().unwrap();
@@ -358,6 +555,7 @@ mod tests {
                     path: Path::new(proj_2_dir.path().to_str().unwrap()).to_path_buf(),
                 },
             ],
+            ..Default::default()
         };
 
         let sut = ProjectsRetriever::new(app_config, &locations_provider);
@@ -381,7 +579,7 @@ mod tests {
             config_dir.path().to_path_buf(),
             data_dir.path().to_path_buf(),
         );
-        let app_config = AppConfig { projects: vec![] };
+        let app_config = AppConfig::default();
 
         let sut = ProjectsRetriever::new(app_config, &locations_provider);
 
@@ -391,5 +589,93 @@ mod tests {
         assert_eq!(0, result.len());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn link_state_reports_linked_missing_and_conflict() {
+        let user_dir = tempfile::tempdir().unwrap();
+        let managed_dir = tempfile::tempdir().unwrap();
+        fs::write(managed_dir.path().join("linked"), "a").unwrap();
+        fs::write(managed_dir.path().join("conflict"), "b").unwrap();
+        std::os::unix::fs::symlink(
+            managed_dir.path().join("linked"),
+            user_dir.path().join("linked"),
+        )
+        .unwrap();
+        fs::write(user_dir.path().join("conflict"), "local").unwrap();
+
+        let state = |name: &str| {
+            ManagedItem::File(name.into()).link_state(user_dir.path(), managed_dir.path())
+        };
+
+        assert_eq!(LinkState::Linked, state("linked"));
+        assert_eq!(LinkState::Conflict, state("conflict"));
+        assert_eq!(LinkState::Missing, state("missing"));
+    }
+
+    #[test]
+    fn stats_counts_items_and_sums_file_sizes() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let config_dir = tempfile::tempdir().unwrap();
+        let managed_dir = data_dir.path().join("projects/proj");
+        fs::create_dir_all(managed_dir.join("config")).unwrap();
+        fs::write(managed_dir.join(".env"), "1234").unwrap();
+        fs::write(managed_dir.join("config/db.env"), "123456").unwrap();
+        fs::write(managed_dir.join(".puff_managed_dirs"), "config\n").unwrap();
+
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let sut = ProjectsRetriever::new(AppConfig::default(), &locations_provider);
+
+        let details = sut.get_details("proj").unwrap().unwrap();
+        let stats = details.info().stats().unwrap();
+
+        assert_eq!(1, stats.files);
+        assert_eq!(1, stats.directories);
+        assert_eq!(10, stats.size);
+        assert!(stats.modified.is_some());
+    }
+
+    #[test]
+    fn health_reports_missing_path_and_broken_links() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let config_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(data_dir.path().join("projects/proj")).unwrap();
+        fs::write(data_dir.path().join("projects/proj/.env"), "A=1").unwrap();
+        fs::create_dir_all(data_dir.path().join("projects/gone")).unwrap();
+
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let app_config = AppConfig {
+            projects: vec![
+                Project {
+                    name: String::from("proj"),
+                    id: String::from("1"),
+                    path: user_dir.path().to_path_buf(),
+                },
+                Project {
+                    name: String::from("gone"),
+                    id: String::from("2"),
+                    path: user_dir.path().join("does-not-exist"),
+                },
+            ],
+            ..Default::default()
+        };
+        let sut = ProjectsRetriever::new(app_config, &locations_provider);
+
+        let health = |name: &str| sut.get_details(name).unwrap().unwrap().health();
+
+        assert_eq!(Some(ProjectHealth::BrokenLinks), health("proj"));
+        assert_eq!(Some(ProjectHealth::MissingPath), health("gone"));
+        assert!(matches!(
+            sut.get_details("proj").unwrap(),
+            Some(ProjectDetails::Associated(_))
+        ));
+    }
+
     // TODO: Test get_associated_projects fn
 }
@@ -0,0 +1,239 @@
+use anyhow::{Result, bail};
+use directories::BaseDirs;
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+};
+
+/// Translates project paths between their absolute form and the portable form stored in
+/// config.json: `~/...` for paths in the home directory, or `{name}/...` for paths inside
+/// one of the user-defined roots (e.g. `code` = `~/src`). Portable paths make config.json
+/// usable on machines with a different home directory or code location.
+pub struct PathMapper {
+    home: Option<PathBuf>,
+    /// Expanded roots, the most specific (longest) ones first
+    roots: Vec<(String, PathBuf)>,
+}
+
+impl PathMapper {
+    /// Creates a mapper for the current user's home directory
+    pub fn new(roots: &BTreeMap<String, String>) -> Self {
+        let home = BaseDirs::new().map(|d| d.home_dir().to_owned());
+        PathMapper::with_home(home, roots)
+    }
+
+    pub fn with_home(home: Option<PathBuf>, roots: &BTreeMap<String, String>) -> Self {
+        let mut mapper = PathMapper {
+            home,
+            roots: vec![],
+        };
+        let mut expanded: Vec<(String, PathBuf)> = roots
+            .iter()
+            .map(|(name, path)| (name.clone(), mapper.expand_home(Path::new(path))))
+            .collect();
+        expanded.sort_by_key(|(_, path)| std::cmp::Reverse(path.components().count()));
+        mapper.roots = expanded;
+        mapper
+    }
+
+    /// Turns a stored path into an absolute one. Paths referring to unknown roots are
+    /// returned unchanged, so they show up as missing.
+    pub fn expand(&self, stored: &Path) -> PathBuf {
+        let mut components = stored.components();
+        if let Some(Component::Normal(first)) = components.next()
+            && let Some(name) = first
+                .to_str()
+                .and_then(|f| f.strip_prefix('{'))
+                .and_then(|f| f.strip_suffix('}'))
+            && let Some((_, root)) = self.roots.iter().find(|(n, _)| n == name)
+        {
+            return join(root, components.as_path());
+        }
+        self.expand_home(stored)
+    }
+
+    /// Turns an absolute path into its portable form, preferring the most specific root
+    pub fn contract(&self, path: &Path) -> PathBuf {
+        for (name, root) in &self.roots {
+            if let Ok(rest) = path.strip_prefix(root) {
+                return portable(&format!("{{{name}}}"), rest);
+            }
+        }
+        if let Some(home) = &self.home
+            && let Ok(rest) = path.strip_prefix(home)
+        {
+            return portable("~", rest);
+        }
+        path.to_owned()
+    }
+
+    /// Converts a path given by the user into the form a root is stored in
+    pub fn root_value(&self, path: &Path) -> Result<String> {
+        let expanded = self.expand_home(path);
+        if !expanded.is_absolute() {
+            bail!("The root path '{}' must be absolute.", path.display());
+        }
+        let value = match &self.home {
+            Some(home) => match expanded.strip_prefix(home) {
+                Ok(rest) => portable("~", rest),
+                Err(_) => expanded,
+            },
+            None => expanded,
+        };
+        Ok(value.to_string_lossy().into_owned())
+    }
+
+    fn expand_home(&self, path: &Path) -> PathBuf {
+        match (&self.home, path.strip_prefix("~")) {
+            (Some(home), Ok(rest)) => join(home, rest),
+            _ => path.to_owned(),
+        }
+    }
+}
+
+/// Like `Path::join`, without leaving a trailing separator when `rest` is empty
+fn join(base: &Path, rest: &Path) -> PathBuf {
+    if rest.as_os_str().is_empty() {
+        base.to_owned()
+    } else {
+        base.join(rest)
+    }
+}
+
+/// Joins `prefix` and `rest` with forward slashes, which every OS accepts
+fn portable(prefix: &str, rest: &Path) -> PathBuf {
+    let mut result = prefix.to_owned();
+    for component in rest.components() {
+        result.push('/');
+        result.push_str(&component.as_os_str().to_string_lossy());
+    }
+    PathBuf::from(result)
+}
+
+/// Checks that a root name can be used in `{name}` placeholders
+pub fn validate_root_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "Invalid root name '{}'. Use letters, digits, '-' and '_' only.",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// A `FROM=TO` rule that replaces the `FROM` prefix of paths imported from another machine
+#[derive(Clone, Debug)]
+pub struct PathRewrite {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl PathRewrite {
+    pub fn parse(rule: &str) -> Result<Self> {
+        let Some((from, to)) = rule.split_once('=') else {
+            bail!("Invalid rewrite rule '{}'. Expected FROM=TO.", rule);
+        };
+        if from.is_empty() || to.is_empty() {
+            bail!("Invalid rewrite rule '{}'. Expected FROM=TO.", rule);
+        }
+        Ok(PathRewrite {
+            from: PathBuf::from(from),
+            to: PathBuf::from(to),
+        })
+    }
+
+    /// Applies the first matching rule to `path`
+    pub fn apply_all(rules: &[PathRewrite], path: &Path) -> PathBuf {
+        for rule in rules {
+            if let Ok(rest) = path.strip_prefix(&rule.from) {
+                return join(&rule.to, rest);
+            }
+        }
+        path.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathMapper, PathRewrite};
+    use std::{
+        collections::BTreeMap,
+        path::{Path, PathBuf},
+    };
+
+    fn mapper() -> PathMapper {
+        let roots = BTreeMap::from([
+            ("code".to_owned(), "~/src".to_owned()),
+            ("work".to_owned(), "~/src/work".to_owned()),
+            ("mnt".to_owned(), "/mnt/projects".to_owned()),
+        ]);
+        PathMapper::with_home(Some(PathBuf::from("/home/me")), &roots)
+    }
+
+    #[test]
+    fn contract_prefers_most_specific_root() {
+        let mapper = mapper();
+
+        assert_eq!(
+            Path::new("{work}/api"),
+            mapper.contract(Path::new("/home/me/src/work/api"))
+        );
+        assert_eq!(
+            Path::new("{code}/web"),
+            mapper.contract(Path::new("/home/me/src/web"))
+        );
+        assert_eq!(
+            Path::new("~/notes"),
+            mapper.contract(Path::new("/home/me/notes"))
+        );
+        assert_eq!(
+            Path::new("/opt/app"),
+            mapper.contract(Path::new("/opt/app"))
+        );
+    }
+
+    #[test]
+    fn expand_reverses_contract() {
+        let mapper = mapper();
+
+        for path in [
+            "/home/me/src/work/api",
+            "/home/me/notes",
+            "/mnt/projects/x",
+            "/opt/app",
+        ] {
+            assert_eq!(
+                Path::new(path),
+                mapper.expand(&mapper.contract(Path::new(path)))
+            );
+        }
+    }
+
+    #[test]
+    fn expand_with_unknown_root_keeps_path() {
+        let mapper = mapper();
+
+        assert_eq!(
+            Path::new("{other}/app"),
+            mapper.expand(Path::new("{other}/app"))
+        );
+    }
+
+    #[test]
+    fn rewrite_replaces_prefix() {
+        let rules = vec![PathRewrite::parse("/Users/me/code=/home/me/code").unwrap()];
+
+        assert_eq!(
+            Path::new("/home/me/code/app"),
+            PathRewrite::apply_all(&rules, Path::new("/Users/me/code/app"))
+        );
+        assert_eq!(
+            Path::new("/Users/me/other"),
+            PathRewrite::apply_all(&rules, Path::new("/Users/me/other"))
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{Result, anyhow, bail};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::git_ignore::IgnoreTarget;
+
+/// Global preferences stored in the `settings` section of config.json
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Settings {
+    /// Where `puff add -g` writes ignore entries of managed items
+    #[serde(default)]
+    pub git_ignore_target: IgnoreTarget,
+    /// How many days backups of replaced files are kept; 0 keeps them forever
+    #[serde(default = "default_backup_retention_days")]
+    pub backup_retention_days: u32,
+    /// Permissions that newly managed files are stored with (Unix only)
+    #[serde(default)]
+    pub managed_file_mode: FileMode,
+}
+
+/// Settings that override the global ones for a single project. They are stored in the
+/// project's metadata, so they apply on every machine.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProjectSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_ignore_target: Option<IgnoreTarget>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_retention_days: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub managed_file_mode: Option<FileMode>,
+}
+
+/// Names of the settings, as used by `puff config`
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum SettingKey {
+    GitIgnoreTarget,
+    BackupRetentionDays,
+    ManagedFileMode,
+}
+
+/// Unix permission bits, written in octal (`600`) in config.json and by `puff config`.
+/// The owner can always read and write.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct FileMode(u32);
+
+impl FileMode {
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Default for FileMode {
+    fn default() -> Self {
+        FileMode(0o600)
+    }
+}
+
+impl fmt::Display for FileMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:03o}", self.0)
+    }
+}
+
+impl TryFrom<String> for FileMode {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        let digits = value.trim_start_matches("0o");
+        let Ok(bits) = u32::from_str_radix(digits, 8) else {
+            bail!("Invalid value '{value}'. Expected octal permissions such as 600.");
+        };
+        if bits > 0o777 || bits & 0o600 != 0o600 {
+            bail!("Invalid value '{value}'. The owner must be able to read and write, e.g. 600.");
+        }
+        Ok(FileMode(bits))
+    }
+}
+
+impl From<FileMode> for String {
+    fn from(mode: FileMode) -> Self {
+        mode.to_string()
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            git_ignore_target: IgnoreTarget::default(),
+            backup_retention_days: default_backup_retention_days(),
+            managed_file_mode: FileMode::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn is_default(&self) -> bool {
+        *self == Settings::default()
+    }
+
+    /// Returns the value of the setting in the same form that `set` accepts
+    pub fn get(&self, key: SettingKey) -> String {
+        match key {
+            SettingKey::GitIgnoreTarget => value_name(self.git_ignore_target),
+            SettingKey::BackupRetentionDays => self.backup_retention_days.to_string(),
+            SettingKey::ManagedFileMode => self.managed_file_mode.to_string(),
+        }
+    }
+
+    /// Parses `value` and stores it in the setting
+    pub fn set(&mut self, key: SettingKey, value: &str) -> Result<()> {
+        match key {
+            SettingKey::GitIgnoreTarget => self.git_ignore_target = parse_value(value)?,
+            SettingKey::BackupRetentionDays => self.backup_retention_days = parse_days(value)?,
+            SettingKey::ManagedFileMode => {
+                self.managed_file_mode = FileMode::try_from(value.to_owned())?
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ProjectSettings {
+    pub fn is_default(&self) -> bool {
+        *self == ProjectSettings::default()
+    }
+
+    /// Returns the overridden value of the setting, if there is any
+    pub fn get(&self, key: SettingKey) -> Option<String> {
+        match key {
+            SettingKey::GitIgnoreTarget => self.git_ignore_target.map(value_name),
+            SettingKey::BackupRetentionDays => self.backup_retention_days.map(|d| d.to_string()),
+            SettingKey::ManagedFileMode => self.managed_file_mode.map(|m| m.to_string()),
+        }
+    }
+
+    pub fn set(&mut self, key: SettingKey, value: &str) -> Result<()> {
+        match key {
+            SettingKey::GitIgnoreTarget => self.git_ignore_target = Some(parse_value(value)?),
+            SettingKey::BackupRetentionDays => {
+                self.backup_retention_days = Some(parse_days(value)?)
+            }
+            SettingKey::ManagedFileMode => {
+                self.managed_file_mode = Some(FileMode::try_from(value.to_owned())?)
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the override, so that the global setting applies again
+    pub fn unset(&mut self, key: SettingKey) {
+        match key {
+            SettingKey::GitIgnoreTarget => self.git_ignore_target = None,
+            SettingKey::BackupRetentionDays => self.backup_retention_days = None,
+            SettingKey::ManagedFileMode => self.managed_file_mode = None,
+        }
+    }
+
+    /// Returns `global` with this project's overrides applied
+    pub fn apply(&self, global: &Settings) -> Settings {
+        Settings {
+            git_ignore_target: self.git_ignore_target.unwrap_or(global.git_ignore_target),
+            backup_retention_days: self
+                .backup_retention_days
+                .unwrap_or(global.backup_retention_days),
+            managed_file_mode: self.managed_file_mode.unwrap_or(global.managed_file_mode),
+        }
+    }
+}
+
+impl SettingKey {
+    pub fn name(&self) -> String {
+        value_name(*self)
+    }
+}
+
+fn default_backup_retention_days() -> u32 {
+    30
+}
+
+fn parse_days(value: &str) -> Result<u32> {
+    value.parse().map_err(|_| {
+        anyhow!(
+            "Invalid value '{}'. Expected a number of days (0 keeps backups forever).",
+            value
+        )
+    })
+}
+
+fn value_name<T: ValueEnum>(value: T) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_owned())
+        .unwrap_or_default()
+}
+
+fn parse_value<T: ValueEnum>(value: &str) -> Result<T> {
+    T::from_str(value, true).map_err(|_| {
+        let allowed: Vec<String> = T::value_variants()
+            .iter()
+            .map(|v| value_name(v.clone()))
+            .collect();
+        anyhow!(
+            "Invalid value '{}'. Allowed values: {}.",
+            value,
+            allowed.join(", ")
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProjectSettings, SettingKey, Settings};
+    use crate::git_ignore::IgnoreTarget;
+
+    #[test]
+    fn set_parses_value_of_the_setting() {
+        let mut settings = Settings::default();
+
+        settings
+            .set(SettingKey::GitIgnoreTarget, "exclude")
+            .unwrap();
+
+        assert_eq!(IgnoreTarget::InfoExclude, settings.git_ignore_target);
+        assert_eq!("exclude", settings.get(SettingKey::GitIgnoreTarget));
+    }
+
+    #[test]
+    fn set_with_invalid_value_fails() {
+        let mut settings = Settings::default();
+
+        let result = settings.set(SettingKey::GitIgnoreTarget, "nowhere");
+
+        assert!(result.is_err());
+        assert!(settings.is_default());
+    }
+
+    #[test]
+    fn project_settings_override_only_the_values_they_set() {
+        let global = Settings {
+            git_ignore_target: IgnoreTarget::InfoExclude,
+            ..Settings::default()
+        };
+        let mut project = ProjectSettings::default();
+
+        assert_eq!(
+            IgnoreTarget::InfoExclude,
+            project.apply(&global).git_ignore_target
+        );
+
+        project
+            .set(SettingKey::GitIgnoreTarget, "gitignore")
+            .unwrap();
+        assert_eq!(
+            IgnoreTarget::GitIgnore,
+            project.apply(&global).git_ignore_target
+        );
+
+        project.unset(SettingKey::GitIgnoreTarget);
+        assert!(project.is_default());
+    }
+
+    #[test]
+    fn managed_file_mode_is_octal_and_keeps_owner_access() {
+        let mut settings = Settings::default();
+
+        settings.set(SettingKey::ManagedFileMode, "640").unwrap();
+
+        assert_eq!(0o640, settings.managed_file_mode.bits());
+        assert_eq!("640", settings.get(SettingKey::ManagedFileMode));
+        assert!(settings.set(SettingKey::ManagedFileMode, "400").is_err());
+        assert!(settings.set(SettingKey::ManagedFileMode, "rw").is_err());
+    }
+
+    #[test]
+    fn project_can_override_managed_file_mode() {
+        let mut project = ProjectSettings::default();
+
+        project.set(SettingKey::ManagedFileMode, "640").unwrap();
+
+        assert_eq!("640", project.get(SettingKey::ManagedFileMode).unwrap());
+        assert_eq!(
+            0o640,
+            project.apply(&Settings::default()).managed_file_mode.bits()
+        );
+    }
+}
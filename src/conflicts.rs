@@ -0,0 +1,587 @@
+use anyhow::{Context, Result, anyhow, bail};
+use clap::ValueEnum;
+use std::{
+    env, fs,
+    io::{IsTerminal, Write},
+    path::Path,
+    process::Command,
+};
+
+use crate::{
+    backups::BackupStore, config::settings::FileMode, fs_utils::copy_dir_recursive,
+    io_utils::prompt_select, permissions::restrict_managed,
+};
+
+/// How a file that exists both in the project and in puff's data directory gets resolved
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum)]
+pub enum OnConflict {
+    /// Keep a backup of the project's copy (see `puff backups`) and link puff's copy
+    Backup,
+    /// Leave the project's copy alone and don't link the file
+    Skip,
+    /// Replace the project's copy with a link to puff's copy
+    Overwrite,
+    /// Replace puff's copy with the project's copy and link it
+    Adopt,
+    /// Fail with an error
+    Fail,
+}
+
+/// What the caller does with the conflicting path once the conflict is resolved
+#[derive(Debug, PartialEq)]
+pub enum Resolution {
+    /// The project's copy is gone; the path gets linked to puff's copy
+    Link,
+    /// The project's copy stays where it is
+    Skip,
+}
+
+/// Resolves conflicts between a file in the project and its managed copy. Without a
+/// mode, the user is asked (showing a diff of the two copies) when puff runs in a
+/// terminal, and `fallback` is used otherwise.
+#[derive(Clone, Copy)]
+pub struct ConflictResolver {
+    mode: Option<OnConflict>,
+    fallback: OnConflict,
+    file_mode: FileMode,
+}
+
+/// An option of the interactive conflict resolution
+enum Choice {
+    Mode(OnConflict),
+    Merge,
+}
+
+impl ConflictResolver {
+    pub fn new(mode: Option<OnConflict>, fallback: OnConflict) -> Self {
+        ConflictResolver {
+            mode,
+            fallback,
+            file_mode: FileMode::default(),
+        }
+    }
+
+    /// Gives adopted project copies the permissions `file_mode` instead of the default 600
+    pub fn with_file_mode(mut self, file_mode: FileMode) -> Self {
+        self.file_mode = file_mode;
+        self
+    }
+
+    /// Resolves the conflict between the project's file or directory at `local` and puff's
+    /// copy at `relative_path` in `managed_dir`. Identical files aren't a conflict: the
+    /// project's copy just gets linked. A file on one side and a directory on the other
+    /// fails without touching either.
+    pub fn resolve(
+        &self,
+        local: &Path,
+        managed_dir: &Path,
+        relative_path: &Path,
+    ) -> Result<Resolution> {
+        let managed = &managed_dir.join(relative_path);
+        let local_is_dir = local.is_dir();
+        if local_is_dir != managed.is_dir() {
+            bail!(
+                "Conflict: {:?} is a {} in the project directory but a {} in puff's registry. \
+                Move the project's copy out of the way and re-run.",
+                relative_path,
+                if local_is_dir { "directory" } else { "file" },
+                if local_is_dir { "file" } else { "directory" }
+            );
+        }
+        if local_is_dir {
+            return self.resolve_dir(local, managed_dir, relative_path);
+        }
+
+        let local_content = fs::read(local).ok();
+        let managed_content = fs::read(managed)?;
+        if local_content.as_ref() == Some(&managed_content) {
+            fs::remove_file(local)?;
+            return Ok(Resolution::Link);
+        }
+
+        let choice = match self.mode {
+            Some(mode) => Choice::Mode(mode),
+            None if std::io::stdin().is_terminal() => {
+                ask(relative_path, local_content.as_deref(), &managed_content)?
+            }
+            None => Choice::Mode(self.fallback),
+        };
+
+        match choice {
+            Choice::Mode(OnConflict::Backup) => {
+                let backup =
+                    BackupStore::for_managed_dir(managed_dir)?.backup(local, relative_path)?;
+                fs::remove_file(local)?;
+                println!(
+                    "Conflict: {:?} exists in both the project directory and puff's registry. \
+                    The project's copy was backed up as {}. \
+                    It now points to the puff-managed version. Run 'puff backups restore' to get it back.",
+                    relative_path, backup.id
+                );
+                Ok(Resolution::Link)
+            }
+            Choice::Mode(OnConflict::Skip) => {
+                println!(
+                    "Skipped {:?}: the project's copy differs from puff's and was left alone.",
+                    relative_path
+                );
+                Ok(Resolution::Skip)
+            }
+            Choice::Mode(OnConflict::Overwrite) => {
+                fs::remove_file(local)?;
+                println!(
+                    "Replaced the project's copy of {:?} with puff's.",
+                    relative_path
+                );
+                Ok(Resolution::Link)
+            }
+            Choice::Mode(OnConflict::Adopt) => {
+                fs::copy(local, managed)?;
+                restrict_managed(managed, self.file_mode)?;
+                fs::remove_file(local)?;
+                println!(
+                    "Replaced puff's copy of {:?} with the project's.",
+                    relative_path
+                );
+                Ok(Resolution::Link)
+            }
+            Choice::Mode(OnConflict::Fail) => bail!(
+                "Conflict: {:?} exists in both the project directory and puff's registry. \
+                Use --on-conflict to choose how to resolve it.",
+                relative_path
+            ),
+            Choice::Merge => {
+                let local_text = to_text(local_content.as_deref(), local)?;
+                let managed_text = to_text(Some(&managed_content), managed)?;
+                let merged = merge_in_editor(&conflict_text(&local_text, &managed_text))?;
+                fs::write(managed, merged)?;
+                fs::remove_file(local)?;
+                println!("Saved the merged version of {:?} in puff.", relative_path);
+                Ok(Resolution::Link)
+            }
+        }
+    }
+
+    /// Resolves the conflict between the project's directory at `local` and puff's
+    /// directory at `relative_path` in `managed_dir`. Directories can't be merged, the
+    /// other modes work as they do for files.
+    fn resolve_dir(
+        &self,
+        local: &Path,
+        managed_dir: &Path,
+        relative_path: &Path,
+    ) -> Result<Resolution> {
+        let managed = &managed_dir.join(relative_path);
+        let mode = match self.mode {
+            Some(mode) => mode,
+            None if std::io::stdin().is_terminal() => ask_dir(relative_path)?,
+            None => self.fallback,
+        };
+
+        match mode {
+            OnConflict::Backup => {
+                let backup =
+                    BackupStore::for_managed_dir(managed_dir)?.backup(local, relative_path)?;
+                remove_dir_or_link(local)?;
+                println!(
+                    "Conflict: {:?} exists as a real directory. \
+                    It was backed up as {} and now points to the puff-managed version. \
+                    Run 'puff backups restore' to get it back.",
+                    relative_path, backup.id
+                );
+                Ok(Resolution::Link)
+            }
+            OnConflict::Skip => {
+                println!(
+                    "Skipped {:?}: the project's directory was left alone.",
+                    relative_path
+                );
+                Ok(Resolution::Skip)
+            }
+            OnConflict::Overwrite => {
+                remove_dir_or_link(local)?;
+                println!(
+                    "Replaced the project's directory {:?} with puff's.",
+                    relative_path
+                );
+                Ok(Resolution::Link)
+            }
+            OnConflict::Adopt => {
+                fs::remove_dir_all(managed)?;
+                copy_dir_recursive(local, managed)?;
+                restrict_managed(managed, self.file_mode)?;
+                remove_dir_or_link(local)?;
+                println!(
+                    "Replaced puff's directory {:?} with the project's.",
+                    relative_path
+                );
+                Ok(Resolution::Link)
+            }
+            OnConflict::Fail => bail!(
+                "Conflict: directory {:?} exists in both the project directory and puff's registry. \
+                Use --on-conflict to choose how to resolve it.",
+                relative_path
+            ),
+        }
+    }
+}
+
+/// Removes the directory at `path`, or only the link if it's a symlink to one
+fn remove_dir_or_link(path: &Path) -> Result<()> {
+    if path.is_symlink() {
+        fs::remove_file(path)?;
+    } else {
+        fs::remove_dir_all(path)?;
+    }
+    Ok(())
+}
+
+fn ask_dir(relative_path: &Path) -> Result<OnConflict> {
+    println!(
+        "Conflict: directory {:?} exists in both the project directory and puff's registry.",
+        relative_path
+    );
+    let mut choices = vec![
+        (
+            "Keep the project's directory (replaces puff's)",
+            OnConflict::Adopt,
+        ),
+        (
+            "Keep puff's directory (discards the project's)",
+            OnConflict::Overwrite,
+        ),
+        (
+            "Keep both (backs up the project's directory)",
+            OnConflict::Backup,
+        ),
+        ("Skip", OnConflict::Skip),
+    ];
+    let items: Vec<String> = choices.iter().map(|(label, _)| label.to_string()).collect();
+    let selection = prompt_select("How should the conflict be resolved?", &items)?;
+    Ok(choices.swap_remove(selection).1)
+}
+
+fn ask(relative_path: &Path, local: Option<&[u8]>, managed: &[u8]) -> Result<Choice> {
+    println!(
+        "Conflict: {:?} exists in both the project directory and puff's registry.",
+        relative_path
+    );
+    let texts = local
+        .and_then(|l| String::from_utf8(l.to_vec()).ok())
+        .zip(String::from_utf8(managed.to_vec()).ok());
+    match &texts {
+        Some((local, managed)) => print_diff(local, managed),
+        None => println!("The copies differ (binary content)."),
+    }
+
+    let mut choices = vec![
+        (
+            "Keep the project's copy (replaces puff's)",
+            Choice::Mode(OnConflict::Adopt),
+        ),
+        (
+            "Keep puff's copy (discards the project's)",
+            Choice::Mode(OnConflict::Overwrite),
+        ),
+    ];
+    if texts.is_some() {
+        choices.push(("Merge them in $EDITOR", Choice::Merge));
+    }
+    choices.push((
+        "Keep both (backs up the project's copy)",
+        Choice::Mode(OnConflict::Backup),
+    ));
+    choices.push(("Skip", Choice::Mode(OnConflict::Skip)));
+
+    let items: Vec<String> = choices.iter().map(|(label, _)| label.to_string()).collect();
+    let selection = prompt_select("How should the conflict be resolved?", &items)?;
+    Ok(choices.swap_remove(selection).1)
+}
+
+fn print_diff(local: &str, managed: &str) {
+    println!("--- project");
+    println!("+++ puff");
+    match line_diff(local, managed) {
+        Some(lines) => {
+            for line in lines {
+                match line {
+                    DiffLine::Same(l) => println!("  {l}"),
+                    DiffLine::Local(l) => println!("- {l}"),
+                    DiffLine::Managed(l) => println!("+ {l}"),
+                }
+            }
+        }
+        None => println!("(the files are too large to show a diff)"),
+    }
+}
+
+fn to_text(content: Option<&[u8]>, path: &Path) -> Result<String> {
+    content
+        .and_then(|c| String::from_utf8(c.to_vec()).ok())
+        .ok_or_else(|| anyhow!("{} is not a text file.", path.display()))
+}
+
+/// A line of the diff between the project's copy and puff's copy of a file
+#[derive(Debug, PartialEq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Local(&'a str),
+    Managed(&'a str),
+}
+
+/// Number of line pairs above which no diff gets computed
+const MAX_DIFF_SIZE: usize = 1_000_000;
+
+/// Diffs the lines of two texts (longest common subsequence). Returns `None` for texts
+/// too large to diff.
+fn line_diff<'a>(local: &'a str, managed: &'a str) -> Option<Vec<DiffLine<'a>>> {
+    let a: Vec<&str> = local.lines().collect();
+    let b: Vec<&str> = managed.lines().collect();
+    if a.len() * b.len() > MAX_DIFF_SIZE {
+        return None;
+    }
+
+    // lcs[i][j] is the length of the common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            lines.push(DiffLine::Same(a[i]));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(DiffLine::Local(a[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Managed(b[j]));
+            j += 1;
+        }
+    }
+    Some(lines)
+}
+
+/// Combines both copies into a text with git-style conflict markers around the lines
+/// that differ
+fn conflict_text(local: &str, managed: &str) -> String {
+    let Some(lines) = line_diff(local, managed) else {
+        return format!("<<<<<<< project\n{local}\n=======\n{managed}\n>>>>>>> puff\n");
+    };
+
+    let mut text = String::new();
+    let mut local_hunk = vec![];
+    let mut managed_hunk = vec![];
+    let flush = |text: &mut String, local: &mut Vec<&str>, managed: &mut Vec<&str>| {
+        if local.is_empty() && managed.is_empty() {
+            return;
+        }
+        text.push_str("<<<<<<< project\n");
+        for line in local.drain(..) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text.push_str("=======\n");
+        for line in managed.drain(..) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text.push_str(">>>>>>> puff\n");
+    };
+    for line in lines {
+        match line {
+            DiffLine::Same(l) => {
+                flush(&mut text, &mut local_hunk, &mut managed_hunk);
+                text.push_str(l);
+                text.push('\n');
+            }
+            DiffLine::Local(l) => local_hunk.push(l),
+            DiffLine::Managed(l) => managed_hunk.push(l),
+        }
+    }
+    flush(&mut text, &mut local_hunk, &mut managed_hunk);
+    text
+}
+
+/// Lets the user edit `text` in $VISUAL or $EDITOR and returns the result, which must not
+/// contain conflict markers anymore
+fn merge_in_editor(text: &str) -> Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_owned());
+    let mut file = tempfile::Builder::new().suffix(".merge").tempfile()?;
+    file.write_all(text.as_bytes())?;
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().ok_or_else(|| anyhow!("$EDITOR is empty."))?;
+    let status = Command::new(program)
+        .args(parts)
+        .arg(file.path())
+        .status()
+        .with_context(|| format!("Could not run '{editor}'."))?;
+    if !status.success() {
+        bail!("'{editor}' failed, nothing was changed.");
+    }
+
+    let merged = fs::read_to_string(file.path())?;
+    if merged
+        .lines()
+        .any(|l| l.starts_with("<<<<<<<") || l.starts_with(">>>>>>>"))
+    {
+        bail!("The merged file still contains conflict markers, nothing was changed.");
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConflictResolver, DiffLine, OnConflict, Resolution, conflict_text, line_diff};
+    use crate::backups::BackupStore;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn line_diff_keeps_common_lines() {
+        let lines = line_diff("A=1\nB=local\nC=3\n", "A=1\nB=puff\nC=3\n").unwrap();
+
+        assert_eq!(
+            vec![
+                DiffLine::Same("A=1"),
+                DiffLine::Local("B=local"),
+                DiffLine::Managed("B=puff"),
+                DiffLine::Same("C=3"),
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn conflict_text_marks_only_differing_lines() {
+        let text = conflict_text("A=1\nB=local\n", "A=1\nB=puff\n");
+
+        assert_eq!(
+            "A=1\n<<<<<<< project\nB=local\n=======\nB=puff\n>>>>>>> puff\n",
+            text
+        );
+    }
+
+    #[test]
+    fn resolve_adopt_replaces_managed_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("local.env");
+        let managed_dir = dir.path().join("projects/app");
+        let managed = managed_dir.join(".env");
+        fs::create_dir_all(&managed_dir).unwrap();
+        fs::write(&local, "A=local").unwrap();
+        fs::write(&managed, "A=puff").unwrap();
+
+        let resolution = ConflictResolver::new(Some(OnConflict::Adopt), OnConflict::Fail)
+            .resolve(&local, &managed_dir, Path::new(".env"))
+            .unwrap();
+
+        assert_eq!(Resolution::Link, resolution);
+        assert!(!local.exists());
+        assert_eq!("A=local", fs::read_to_string(&managed).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_adopt_restricts_permissions_of_adopted_copy() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("local.env");
+        let managed_dir = dir.path().join("projects/app");
+        let managed = managed_dir.join(".env");
+        fs::create_dir_all(&managed_dir).unwrap();
+        fs::write(&local, "A=local").unwrap();
+        fs::set_permissions(&local, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::write(&managed, "A=puff").unwrap();
+
+        ConflictResolver::new(Some(OnConflict::Adopt), OnConflict::Fail)
+            .resolve(&local, &managed_dir, Path::new(".env"))
+            .unwrap();
+
+        let mode = fs::metadata(&managed).unwrap().permissions().mode() & 0o777;
+        assert_eq!(0o600, mode);
+    }
+
+    #[test]
+    fn resolve_backup_keeps_project_copy_in_backup_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("local.env");
+        let managed_dir = dir.path().join("projects/app");
+        fs::create_dir_all(&managed_dir).unwrap();
+        fs::write(&local, "A=local").unwrap();
+        fs::write(managed_dir.join(".env"), "A=puff").unwrap();
+
+        let resolution = ConflictResolver::new(Some(OnConflict::Backup), OnConflict::Fail)
+            .resolve(&local, &managed_dir, Path::new(".env"))
+            .unwrap();
+
+        assert_eq!(Resolution::Link, resolution);
+        assert!(!local.exists());
+        let store = BackupStore::for_managed_dir(&managed_dir).unwrap();
+        let backups = store.list().unwrap();
+        assert_eq!(1, backups.len());
+        assert_eq!(
+            "A=local",
+            fs::read_to_string(store.stored_path(&backups[0])).unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_adopt_of_directory_replaces_managed_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("project/config");
+        let managed_dir = dir.path().join("projects/app");
+        fs::create_dir_all(&local).unwrap();
+        fs::create_dir_all(managed_dir.join("config")).unwrap();
+        fs::write(local.join("dev.env"), "A=local").unwrap();
+        fs::write(managed_dir.join("config/old.env"), "A=puff").unwrap();
+
+        let resolution = ConflictResolver::new(Some(OnConflict::Adopt), OnConflict::Fail)
+            .resolve(&local, &managed_dir, Path::new("config"))
+            .unwrap();
+
+        assert_eq!(Resolution::Link, resolution);
+        assert!(!local.exists());
+        assert!(!managed_dir.join("config/old.env").exists());
+        assert_eq!(
+            "A=local",
+            fs::read_to_string(managed_dir.join("config/dev.env")).unwrap()
+        );
+    }
+
+    #[test]
+    fn resolve_fails_for_directory_in_place_of_file_without_touching_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("project/.env");
+        let managed_dir = dir.path().join("projects/app");
+        fs::create_dir_all(&local).unwrap();
+        fs::create_dir_all(&managed_dir).unwrap();
+        fs::write(local.join("nested"), "x").unwrap();
+        fs::write(managed_dir.join(".env"), "A=puff").unwrap();
+
+        let result = ConflictResolver::new(Some(OnConflict::Overwrite), OnConflict::Fail).resolve(
+            &local,
+            &managed_dir,
+            Path::new(".env"),
+        );
+
+        assert!(result.is_err());
+        assert!(local.join("nested").exists());
+        assert_eq!(
+            "A=puff",
+            fs::read_to_string(managed_dir.join(".env")).unwrap()
+        );
+    }
+}
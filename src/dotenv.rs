@@ -0,0 +1,75 @@
+use std::path::Path;
+
+/// Values shorter than this are too common ("true", "3000", "dev") to be treated as secrets
+pub const MIN_SECRET_LENGTH: usize = 8;
+
+/// Checks whether the file name looks like a dotenv file (`.env`, `.env.local`, `prod.env`)
+pub fn is_dotenv_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy())
+        .is_some_and(|n| n.starts_with(".env") || n.ends_with(".env"))
+}
+
+/// Parses `KEY=value` lines of a dotenv file. Comments, blank lines and lines without
+/// `=` are skipped, an `export ` prefix is allowed, and quotes around values are removed.
+pub fn parse(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_owned(), unquote(value.trim()).to_owned()))
+        })
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    // Unquoted values may be followed by a comment
+    match value.find(" #") {
+        Some(index) => value[..index].trim_end(),
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_dotenv_file, parse};
+    use std::path::Path;
+
+    #[test]
+    fn parse_handles_comments_quotes_and_export() {
+        let content = "# comment\n\nexport API_KEY=abc123\nDB_URL=\"postgres://x\"\nNAME='puff' \nPORT=3000 # dev\ninvalid line\n";
+
+        let result = parse(content);
+
+        assert_eq!(
+            vec![
+                ("API_KEY".to_owned(), "abc123".to_owned()),
+                ("DB_URL".to_owned(), "postgres://x".to_owned()),
+                ("NAME".to_owned(), "puff".to_owned()),
+                ("PORT".to_owned(), "3000".to_owned()),
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn is_dotenv_file_matches_common_names() {
+        assert!(is_dotenv_file(Path::new(".env")));
+        assert!(is_dotenv_file(Path::new("apps/.env.local")));
+        assert!(is_dotenv_file(Path::new("config/prod.env")));
+        assert!(!is_dotenv_file(Path::new("environment.json")));
+    }
+}
@@ -0,0 +1,347 @@
+use anyhow::{Result, anyhow, bail};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread,
+};
+
+/// A git working tree along with the git directory of its repository
+pub struct GitRepository {
+    /// Root of the working tree (the directory containing `.git`)
+    pub work_tree: PathBuf,
+    /// Git directory shared by all worktrees of the repository
+    pub common_dir: PathBuf,
+}
+
+impl GitRepository {
+    /// Finds the repository containing `path` by looking for `.git` in its ancestors.
+    /// Linked worktrees, where `.git` is a file pointing to the actual git directory,
+    /// are resolved to the common git directory of the main repository.
+    pub fn discover(path: &Path) -> Result<Option<GitRepository>> {
+        for ancestor in path.ancestors() {
+            let dot_git = ancestor.join(".git");
+            if dot_git.is_dir() {
+                return Ok(Some(GitRepository::new(ancestor, dot_git)?));
+            }
+            if dot_git.is_file() {
+                let git_dir = read_git_dir_file(&dot_git)?;
+                return Ok(Some(GitRepository::new(ancestor, git_dir)?));
+            }
+        }
+        Ok(None)
+    }
+
+    fn new(work_tree: &Path, git_dir: PathBuf) -> Result<Self> {
+        let commondir_file = git_dir.join("commondir");
+        let common_dir = if commondir_file.is_file() {
+            let common_dir = fs::read_to_string(&commondir_file)?;
+            normalize(&git_dir.join(common_dir.trim()))
+        } else {
+            normalize(&git_dir)
+        };
+
+        Ok(GitRepository {
+            work_tree: work_tree.to_owned(),
+            common_dir,
+        })
+    }
+
+    /// Path of the repository's exclude file, shared by all of its worktrees
+    pub fn info_exclude_path(&self) -> PathBuf {
+        self.common_dir.join("info").join("exclude")
+    }
+
+    /// Returns the roots of the main working tree and of all linked worktrees that still
+    /// exist. Linked worktrees are listed in `<common dir>/worktrees/<name>/gitdir`.
+    pub fn worktrees(&self) -> Result<Vec<PathBuf>> {
+        let mut worktrees = vec![];
+        if self.common_dir.file_name().is_some_and(|n| n == ".git")
+            && let Some(main) = self.common_dir.parent()
+        {
+            worktrees.push(main.to_owned());
+        }
+
+        let Ok(entries) = fs::read_dir(self.common_dir.join("worktrees")) else {
+            return Ok(worktrees);
+        };
+        for entry in entries {
+            let gitdir_file = entry?.path().join("gitdir");
+            let Ok(dot_git) = fs::read_to_string(&gitdir_file) else {
+                continue;
+            };
+            if let Some(worktree) = Path::new(dot_git.trim()).parent()
+                && worktree.is_dir()
+            {
+                worktrees.push(normalize(worktree));
+            }
+        }
+        Ok(worktrees)
+    }
+
+    /// Returns the paths in git's index that are equal to or inside any of `paths`
+    /// (relative to the working tree)
+    pub fn tracked_paths(&self, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        if paths.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut args = vec!["ls-files", "-z", "--"];
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+        args.extend(paths.iter().map(String::as_str));
+        let output = self.git(&args)?;
+
+        Ok(split_paths(&output))
+    }
+
+    /// Returns the ones of `paths` (relative to the working tree) that git ignores, whether
+    /// they are tracked or not. All of git's ignore sources count, `core.excludesFile`
+    /// included.
+    pub fn ignored_paths(&self, paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        if paths.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // Paths go through stdin, which is the only way to get NUL-separated output
+        let mut input = vec![];
+        for path in paths {
+            input.extend_from_slice(path.to_string_lossy().as_bytes());
+            input.push(0);
+        }
+        let mut child = Command::new("git")
+            .arg("-C")
+            .arg(&self.work_tree)
+            .args(["check-ignore", "--no-index", "-z", "--stdin"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow!("Could not run git: {e}"))?;
+        // Written from another thread, so that a full stdout pipe can't block git
+        let mut stdin = child.stdin.take().expect("stdin of git is piped");
+        let writer = thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        writer
+            .join()
+            .map_err(|_| anyhow!("Could not pass paths to git"))??;
+
+        // git check-ignore exits with 1 when none of the paths is ignored
+        match output.status.code() {
+            Some(0) => Ok(split_paths(&output.stdout)),
+            Some(1) => Ok(vec![]),
+            _ => bail!(
+                "git check-ignore failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+    }
+
+    /// Returns paths (relative to the working tree) of files added, copied, modified or
+    /// renamed in the index
+    pub fn staged_paths(&self) -> Result<Vec<PathBuf>> {
+        let output = self.git(&[
+            "diff",
+            "--cached",
+            "--name-only",
+            "-z",
+            "--diff-filter=ACMR",
+        ])?;
+        Ok(split_paths(&output))
+    }
+
+    /// Returns the paths (relative to the working tree) of the submodules in git's index,
+    /// which have no content of their own
+    pub fn staged_gitlinks(&self) -> Result<Vec<PathBuf>> {
+        let output = self.git(&["ls-files", "--stage", "-z"])?;
+        Ok(String::from_utf8_lossy(&output)
+            .split('\0')
+            .filter_map(|entry| entry.strip_prefix("160000 "))
+            .filter_map(|entry| entry.split_once('\t'))
+            .map(|(_, path)| PathBuf::from(path))
+            .collect())
+    }
+
+    /// Returns the staged content of a file (relative to the working tree)
+    pub fn staged_content(&self, path: &Path) -> Result<Vec<u8>> {
+        let spec = format!(":{}", path.to_string_lossy().replace('\\', "/"));
+        self.git(&["show", &spec])
+    }
+
+    /// Returns the directory git reads hooks from (honouring `core.hooksPath`)
+    pub fn hooks_dir(&self) -> PathBuf {
+        match self.git(&["rev-parse", "--git-path", "hooks"]) {
+            Ok(output) => self
+                .work_tree
+                .join(String::from_utf8_lossy(&output).trim_end()),
+            Err(_) => self.common_dir.join("hooks"),
+        }
+    }
+
+    /// Returns the URLs of all remotes configured in the repository
+    pub fn remote_urls(&self) -> Vec<String> {
+        // git config exits with 1 when nothing matches
+        let Ok(output) = self.git(&["config", "--get-regexp", r"^remote\..*\.url$"]) else {
+            return vec![];
+        };
+        String::from_utf8_lossy(&output)
+            .lines()
+            .filter_map(|l| l.split_once(' ').map(|(_, url)| url.trim().to_owned()))
+            .filter(|url| !url.is_empty())
+            .collect()
+    }
+
+    /// Finds the worktree of this repository that contains `path` and returns `path`
+    /// relative to it. A project's location within one worktree is the same in all
+    /// the others, so this is how its counterparts in other worktrees are found.
+    pub fn relative_to_worktrees(&self, path: &Path) -> Result<Option<PathBuf>> {
+        let path = normalize(path);
+        let mut worktrees = self.worktrees()?;
+        worktrees.push(self.work_tree.clone());
+
+        Ok(worktrees
+            .iter()
+            .filter_map(|w| path.strip_prefix(normalize(w)).ok().map(Path::to_owned))
+            .min_by_key(|relative| relative.components().count()))
+    }
+
+    /// Runs git in the working tree and returns its standard output
+    fn git(&self, args: &[&str]) -> Result<Vec<u8>> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.work_tree)
+            .args(args)
+            .output()
+            .map_err(|e| anyhow!("Could not run git: {e}"))?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    }
+}
+
+/// Splits NUL-separated paths printed by git (`-z`)
+fn split_paths(output: &[u8]) -> Vec<PathBuf> {
+    String::from_utf8_lossy(output)
+        .split('\0')
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Reads a `.git` file of a linked worktree ("gitdir: <path>")
+fn read_git_dir_file(dot_git: &Path) -> Result<PathBuf> {
+    let content = fs::read_to_string(dot_git)?;
+    let git_dir = content
+        .lines()
+        .find_map(|l| l.strip_prefix("gitdir:"))
+        .map(str::trim)
+        .ok_or_else(|| anyhow!("{} is not a valid git file.", dot_git.display()))?;
+
+    let git_dir = Path::new(git_dir);
+    Ok(if git_dir.is_absolute() {
+        git_dir.to_owned()
+    } else {
+        dot_git.parent().unwrap_or(Path::new("")).join(git_dir)
+    })
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GitRepository;
+    use std::fs;
+
+    #[test]
+    fn discover_finds_repository_of_nested_path() {
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir_all(repo.path().join(".git")).unwrap();
+        fs::create_dir_all(repo.path().join("apps/web")).unwrap();
+
+        let result = GitRepository::discover(&repo.path().join("apps/web"))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(repo.path(), result.work_tree);
+        assert_eq!(
+            fs::canonicalize(repo.path().join(".git")).unwrap(),
+            result.common_dir
+        );
+    }
+
+    #[test]
+    fn discover_resolves_common_dir_of_linked_worktree() {
+        let main = tempfile::tempdir().unwrap();
+        let worktree = tempfile::tempdir().unwrap();
+        let worktree_git_dir = main.path().join(".git/worktrees/feature");
+        fs::create_dir_all(&worktree_git_dir).unwrap();
+        fs::write(worktree_git_dir.join("commondir"), "../..\n").unwrap();
+        fs::write(
+            worktree.path().join(".git"),
+            format!("gitdir: {}\n", worktree_git_dir.display()),
+        )
+        .unwrap();
+
+        let result = GitRepository::discover(worktree.path()).unwrap().unwrap();
+
+        assert_eq!(worktree.path(), result.work_tree);
+        assert_eq!(
+            fs::canonicalize(main.path().join(".git")).unwrap(),
+            result.common_dir
+        );
+        assert_eq!(
+            fs::canonicalize(main.path().join(".git"))
+                .unwrap()
+                .join("info/exclude"),
+            result.info_exclude_path()
+        );
+    }
+
+    #[test]
+    fn worktrees_lists_main_and_existing_linked_worktrees() {
+        let main = tempfile::tempdir().unwrap();
+        let worktree = tempfile::tempdir().unwrap();
+        fs::create_dir_all(main.path().join(".git/worktrees/feature")).unwrap();
+        fs::create_dir_all(main.path().join(".git/worktrees/removed")).unwrap();
+        fs::write(
+            main.path().join(".git/worktrees/feature/gitdir"),
+            format!("{}\n", worktree.path().join(".git").display()),
+        )
+        .unwrap();
+        fs::write(
+            main.path().join(".git/worktrees/removed/gitdir"),
+            "/nonexistent/removed/.git\n",
+        )
+        .unwrap();
+
+        let repository = GitRepository::discover(main.path()).unwrap().unwrap();
+        let result = repository.worktrees().unwrap();
+
+        assert_eq!(
+            vec![
+                fs::canonicalize(main.path()).unwrap(),
+                fs::canonicalize(worktree.path()).unwrap()
+            ],
+            result
+        );
+    }
+
+    #[test]
+    fn discover_returns_none_outside_of_repository() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let result = GitRepository::discover(dir.path()).unwrap();
+
+        assert!(result.is_none());
+    }
+}
@@ -1,8 +1,50 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::Write;
-use std::{fs::File, path::Path};
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use crate::git::GitRepository;
+use crate::patterns::GlobPattern;
+
+/// Name of the file (in the root of a project's managed dir) that records the
+/// ignore entries added by puff, so that they can be removed later on
+pub(crate) const GIT_IGNORE_ENTRIES_FILE: &str = ".puff_git_ignore_entries";
+
+/// The file that puff writes ignore entries of managed items to
+#[derive(Clone, Copy, Default, PartialEq, Debug, Serialize, Deserialize, ValueEnum)]
+pub enum IgnoreTarget {
+    /// The .gitignore file of the project root, committed along with the project
+    #[default]
+    #[serde(rename = "gitignore")]
+    #[value(name = "gitignore")]
+    GitIgnore,
+    /// The repository's .git/info/exclude file, which is never committed
+    #[serde(rename = "exclude")]
+    #[value(name = "exclude")]
+    InfoExclude,
+}
+
+impl Display for IgnoreTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IgnoreTarget::GitIgnore => write!(f, ".gitignore"),
+            IgnoreTarget::InfoExclude => write!(f, ".git/info/exclude"),
+        }
+    }
+}
+
+/// An ignore entry added by puff, along with the file it was added to
+#[derive(PartialEq)]
+struct RecordedEntry {
+    target: IgnoreTarget,
+    entry: String,
+}
 
 /// Adds files to existing/new .gitignore
 pub struct GitIgnoreHandler {}
@@ -12,44 +54,429 @@ impl GitIgnoreHandler {
         GitIgnoreHandler {}
     }
 
-    /// An existing .gitignore file in the user_dir will be updated
-    /// with the provided file_name. If .gitignore does not exist, it
-    /// will be created
-    pub fn add_to_git_ignore(&self, user_dir: &Path, file_name: &str) -> Result<GitIgnoreResult> {
-        if !user_dir.join(".gitignore").exists() {
-            self.create_git_ignore_file(user_dir, file_name)?;
+    /// Makes sure that git ignores a managed item located at `relative_path` in the project.
+    /// Unless the existing rules (including the ones of nested .gitignore files and of
+    /// .git/info/exclude) already ignore the path, an entry gets added to the `target`
+    /// file and recorded in the project's managed dir. Entries that the file already
+    /// contains are not recorded, so that forgetting the item leaves them alone.
+    pub fn ignore_managed_path(
+        &self,
+        project_root: &Path,
+        managed_dir: &Path,
+        relative_path: &Path,
+        target: IgnoreTarget,
+    ) -> Result<GitIgnoreResult> {
+        let repository = GitRepository::discover(project_root)?;
+
+        let paths = [relative_path.to_path_buf()];
+        if !GitIgnoreRules::ignored_paths(repository.as_ref(), project_root, &paths)?.is_empty() {
+            return Ok(GitIgnoreResult::AlreadyIgnored);
+        }
+
+        let (path, entry) = match target {
+            IgnoreTarget::GitIgnore => (
+                project_root.join(".gitignore"),
+                git_ignore_entry(relative_path),
+            ),
+            IgnoreTarget::InfoExclude => {
+                let Some(repository) = &repository else {
+                    bail!(
+                        "'{}' is not inside a git repository, so .git/info/exclude can't be used.",
+                        project_root.display()
+                    );
+                };
+                (
+                    repository.info_exclude_path(),
+                    exclude_entry(repository, project_root, relative_path)?,
+                )
+            }
+        };
+        let result = self.add_entry(&path, &entry)?;
+        if result == GitIgnoreResult::AlreadyIgnored {
+            return Ok(result);
+        }
+
+        let recorded = RecordedEntry { target, entry };
+        let mut entries = read_recorded_entries(managed_dir)?;
+        if !entries.contains(&recorded) {
+            entries.push(recorded);
+            write_recorded_entries(managed_dir, &entries)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Removes the ignore entry of a managed item, if it was added by puff.
+    /// Returns the file the entry was removed from.
+    pub fn unignore_managed_path(
+        &self,
+        project_root: &Path,
+        managed_dir: &Path,
+        relative_path: &Path,
+    ) -> Result<Option<IgnoreTarget>> {
+        let repository = GitRepository::discover(project_root)?;
+        let mut candidates = vec![RecordedEntry {
+            target: IgnoreTarget::GitIgnore,
+            entry: git_ignore_entry(relative_path),
+        }];
+        if let Some(repository) = &repository {
+            candidates.push(RecordedEntry {
+                target: IgnoreTarget::InfoExclude,
+                entry: exclude_entry(repository, project_root, relative_path)?,
+            });
+        }
+        // Older versions of puff recorded entries without the leading slash
+        let unanchored: Vec<RecordedEntry> = candidates
+            .iter()
+            .map(|c| RecordedEntry {
+                target: c.target,
+                entry: c.entry.trim_start_matches('/').to_owned(),
+            })
+            .collect();
+        candidates.extend(unanchored);
+
+        let mut entries = read_recorded_entries(managed_dir)?;
+        let Some(recorded) = candidates.into_iter().find(|c| entries.contains(c)) else {
+            return Ok(None);
+        };
+
+        entries.retain(|e| *e != recorded);
+        write_recorded_entries(managed_dir, &entries)?;
+
+        let path = match (recorded.target, &repository) {
+            (IgnoreTarget::InfoExclude, Some(repository)) => repository.info_exclude_path(),
+            _ => project_root.join(".gitignore"),
+        };
+        let removed = self.remove_entry(&path, &recorded.entry)?;
+
+        Ok(removed.then_some(recorded.target))
+    }
+
+    /// Appends `entry` to the ignore file at `path`, unless it already contains it.
+    /// The file (and its parent directory) gets created if it doesn't exist.
+    fn add_entry(&self, path: &Path, entry: &str) -> Result<GitIgnoreResult> {
+        if !path.exists() {
+            self.create_ignore_file(path, entry)?;
 
             Ok(GitIgnoreResult::FileCreated)
         } else {
-            self.append_to_existing_git_ignore(user_dir, file_name)?;
+            let content = fs::read_to_string(path)?;
+            if content.lines().any(|l| l.trim_end() == entry) {
+                return Ok(GitIgnoreResult::AlreadyIgnored);
+            }
+
+            self.append_to_existing_ignore_file(path, entry, &content)?;
 
             Ok(GitIgnoreResult::FileUpdated)
         }
     }
 
-    fn create_git_ignore_file(&self, dir: &Path, file_to_ignore: &str) -> Result<()> {
-        let mut file = File::create(dir.join(".gitignore"))?;
+    fn create_ignore_file(&self, path: &Path, file_to_ignore: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = File::create(path)?;
         writeln!(file, "{file_to_ignore}")?;
 
         Ok(())
     }
 
-    fn append_to_existing_git_ignore(&self, dir: &Path, file_to_ignore: &str) -> Result<()> {
-        let mut file = OpenOptions::new()
-            .append(true)
-            .open(dir.join(".gitignore"))?;
+    fn append_to_existing_ignore_file(
+        &self,
+        path: &Path,
+        file_to_ignore: &str,
+        current_content: &str,
+    ) -> Result<()> {
+        let mut file = OpenOptions::new().append(true).open(path)?;
 
-        writeln!(file)?; // to make sure we're not appending to some non-empty line
+        // to make sure we're not appending to some non-empty line
+        if !current_content.is_empty() && !current_content.ends_with('\n') {
+            writeln!(file)?;
+        }
         writeln!(file, "{file_to_ignore}")?;
 
         Ok(())
     }
+
+    /// Removes the first line equal to `entry` from the ignore file at `path`
+    fn remove_entry(&self, path: &Path, entry: &str) -> Result<bool> {
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        let content = fs::read_to_string(path)?;
+        let mut lines: Vec<&str> = content.lines().collect();
+        let Some(index) = lines.iter().position(|l| l.trim_end() == entry) else {
+            return Ok(false);
+        };
+        lines.remove(index);
+
+        let mut new_content = lines.join("\n");
+        if !new_content.is_empty() {
+            new_content.push('\n');
+        }
+        fs::write(path, new_content)?;
+
+        Ok(true)
+    }
+}
+
+/// Returns the .gitignore entry (relative to the project root) of a managed item. The
+/// entry is anchored with a leading slash, so that it doesn't ignore files with the same
+/// name in subdirectories.
+fn git_ignore_entry(relative_path: &Path) -> String {
+    relative_path
+        .components()
+        .map(|c| format!("/{}", c.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// Returns the .git/info/exclude entry of a managed item. Exclude patterns are relative
+/// to the root of the working tree, which may be a parent of the project root.
+fn exclude_entry(
+    repository: &GitRepository,
+    project_root: &Path,
+    relative_path: &Path,
+) -> Result<String> {
+    let project_in_repository = project_root.strip_prefix(&repository.work_tree)?;
+    Ok(git_ignore_entry(&project_in_repository.join(relative_path)))
+}
+
+/// Reads the entries recorded in the managed dir. Entries of .git/info/exclude are
+/// prefixed with "exclude" and a tab, .gitignore entries are stored as they are.
+fn read_recorded_entries(managed_dir: &Path) -> Result<Vec<RecordedEntry>> {
+    let path = managed_dir.join(GIT_IGNORE_ENTRIES_FILE);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| match l.split_once('\t') {
+            Some(("exclude", entry)) => RecordedEntry {
+                target: IgnoreTarget::InfoExclude,
+                entry: entry.to_owned(),
+            },
+            _ => RecordedEntry {
+                target: IgnoreTarget::GitIgnore,
+                entry: l.to_owned(),
+            },
+        })
+        .collect())
+}
+
+fn write_recorded_entries(managed_dir: &Path, entries: &[RecordedEntry]) -> Result<()> {
+    let path = managed_dir.join(GIT_IGNORE_ENTRIES_FILE);
+    if entries.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|e| match e.target {
+            IgnoreTarget::GitIgnore => e.entry.clone(),
+            IgnoreTarget::InfoExclude => format!("exclude\t{}", e.entry),
+        })
+        .collect();
+    fs::write(path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+/// The ignore rules of a directory tree, read from its .gitignore files.
+/// Rules of nested .gitignore files apply to their own subtree and take precedence
+/// over the rules of their parents, just like in git.
+pub struct GitIgnoreRules {
+    rules: Vec<GitIgnoreRule>,
+}
+
+struct GitIgnoreRule {
+    /// Directory of the .gitignore file, relative to the root of the tree
+    base: PathBuf,
+    pattern: GlobPattern,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl GitIgnoreRules {
+    /// Reads only the .gitignore files that can affect `relative_path`: the one of `root`
+    /// and the ones of the directories between `root` and the path
+    pub fn load_for_path(root: &Path, relative_path: &Path) -> Result<Self> {
+        let mut rules = GitIgnoreRules { rules: vec![] };
+        let mut dirs: Vec<&Path> = relative_path.ancestors().skip(1).collect();
+        dirs.reverse();
+        for relative_dir in dirs {
+            let git_ignore = root.join(relative_dir).join(".gitignore");
+            if git_ignore.is_file() {
+                rules.add_file_rules(relative_dir, &fs::read_to_string(git_ignore)?);
+            }
+        }
+        Ok(rules)
+    }
+
+    /// Checks whether git would ignore the path (relative to `project_root`), taking into
+    /// account the repository's .git/info/exclude and the .gitignore files above the
+    /// project root as well. Without a repository, only the project's files are read.
+    pub fn is_ignored_in_repository(
+        repository: Option<&GitRepository>,
+        project_root: &Path,
+        relative_path: &Path,
+        is_dir: bool,
+    ) -> Result<bool> {
+        let Some(repository) = repository else {
+            return Ok(
+                Self::load_for_path(project_root, relative_path)?.is_ignored(relative_path, is_dir)
+            );
+        };
+
+        let path = project_root
+            .strip_prefix(&repository.work_tree)?
+            .join(relative_path);
+
+        // Rules of .git/info/exclude have the lowest precedence, so they go first
+        let mut rules = GitIgnoreRules { rules: vec![] };
+        let exclude = repository.info_exclude_path();
+        if exclude.is_file() {
+            rules.add_file_rules(Path::new(""), &fs::read_to_string(exclude)?);
+        }
+        rules
+            .rules
+            .extend(Self::load_for_path(&repository.work_tree, &path)?.rules);
+
+        Ok(rules.is_ignored(&path, is_dir))
+    }
+
+    /// Returns the ones of `relative_paths` (relative to `project_root`) that git ignores.
+    /// In a repository git itself is asked, so that every source of ignore rules counts.
+    /// Only when git can't be run, or outside of a repository, are the ignore files read.
+    pub fn ignored_paths(
+        repository: Option<&GitRepository>,
+        project_root: &Path,
+        relative_paths: &[PathBuf],
+    ) -> Result<Vec<PathBuf>> {
+        if let Some(repository) = repository {
+            let offset = project_root.strip_prefix(&repository.work_tree)?;
+            let paths: Vec<PathBuf> = relative_paths.iter().map(|p| offset.join(p)).collect();
+            if let Ok(ignored) = repository.ignored_paths(&paths) {
+                return Ok(relative_paths
+                    .iter()
+                    .zip(&paths)
+                    .filter(|(_, path)| ignored.contains(path))
+                    .map(|(relative_path, _)| relative_path.clone())
+                    .collect());
+            }
+        }
+
+        let mut ignored = vec![];
+        for relative_path in relative_paths {
+            // Managed items are symlinks, which git treats as files
+            if Self::is_ignored_in_repository(repository, project_root, relative_path, false)? {
+                ignored.push(relative_path.clone());
+            }
+        }
+        Ok(ignored)
+    }
+
+    /// Parses the content of a .gitignore file located in `base` (relative to the root)
+    pub fn add_file_rules(&mut self, base: &Path, content: &str) {
+        for line in content.lines() {
+            if let Some(rule) = GitIgnoreRule::parse(base, line) {
+                self.rules.push(rule);
+            }
+        }
+    }
+
+    /// Checks whether git would ignore the path (relative to the root). A path is
+    /// ignored if it matches a rule, or if any of its parent directories is ignored.
+    pub fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let mut ancestors: Vec<&Path> = relative_path
+            .ancestors()
+            .skip(1)
+            .take_while(|a| !a.as_os_str().is_empty())
+            .collect();
+        ancestors.reverse();
+
+        if ancestors
+            .iter()
+            .any(|a| self.last_match(a, true) == Some(true))
+        {
+            return true;
+        }
+        self.last_match(relative_path, is_dir) == Some(true)
+    }
+
+    /// Returns whether the last rule matching the path ignores it (`Some(true)`),
+    /// re-includes it (`Some(false)`) or whether no rule matches (`None`)
+    fn last_match(&self, relative_path: &Path, is_dir: bool) -> Option<bool> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(relative_path, is_dir))
+            .map(|rule| !rule.negated)
+    }
+}
+
+impl GitIgnoreRule {
+    fn parse(base: &Path, line: &str) -> Option<GitIgnoreRule> {
+        let mut line = trim_unescaped_trailing_spaces(line);
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        // A leading `!` negates the rule, while `\!` and `\#` escape those characters
+        let negated = line.starts_with('!');
+        if negated || line.starts_with("\\!") || line.starts_with("\\#") {
+            line = &line[1..];
+        }
+
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+
+        // Patterns without a slash (other than a trailing one) match at any depth
+        let pattern = if line.contains('/') {
+            line.trim_start_matches('/').to_owned()
+        } else {
+            format!("**/{line}")
+        };
+
+        Some(GitIgnoreRule {
+            base: base.to_owned(),
+            pattern: GlobPattern::new(&pattern),
+            negated,
+            dir_only,
+        })
+    }
+
+    fn matches(&self, relative_path: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        match relative_path.strip_prefix(&self.base) {
+            Ok(path) => self.pattern.matches(path),
+            Err(_) => false,
+        }
+    }
+}
+
+fn trim_unescaped_trailing_spaces(line: &str) -> &str {
+    let trimmed = line.trim_end();
+    if trimmed.ends_with('\\') && trimmed.len() < line.len() {
+        &line[..trimmed.len() + 1]
+    } else {
+        trimmed
+    }
 }
 
 #[derive(PartialEq, Debug)]
 pub enum GitIgnoreResult {
     FileCreated,
     FileUpdated,
+    /// The path was already ignored, nothing was changed
+    AlreadyIgnored,
 }
 
 impl Display for GitIgnoreResult {
@@ -57,17 +484,297 @@ impl Display for GitIgnoreResult {
         match self {
             GitIgnoreResult::FileCreated => write!(f, "created"),
             GitIgnoreResult::FileUpdated => write!(f, "updated"),
+            GitIgnoreResult::AlreadyIgnored => write!(f, "already ignored"),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::git_ignore::GitIgnoreResult;
+    use crate::git_ignore::{GitIgnoreResult, IgnoreTarget};
     use std::fs::{self, File};
     use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    use super::{GIT_IGNORE_ENTRIES_FILE, GitIgnoreHandler, GitIgnoreRules};
+
+    fn rules(content: &str) -> GitIgnoreRules {
+        let mut rules = GitIgnoreRules { rules: vec![] };
+        rules.add_file_rules(Path::new(""), content);
+        rules
+    }
+
+    #[test]
+    fn add_to_git_ignore_when_entry_exists_file_is_not_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), "testfile\n").unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        let result = sut
+            .add_entry(&dir.path().join(".gitignore"), "testfile")
+            .unwrap();
+
+        assert_eq!(GitIgnoreResult::AlreadyIgnored, result);
+        let contents = fs::read_to_string(dir.path().join(".gitignore")).unwrap();
+        assert_eq!("testfile\n", contents);
+    }
+
+    #[test]
+    fn add_to_git_ignore_does_not_add_blank_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".gitignore"), "a\n").unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        sut.add_entry(&dir.path().join(".gitignore"), "b").unwrap();
+        sut.add_entry(&dir.path().join(".gitignore"), "c").unwrap();
+
+        let contents = fs::read_to_string(dir.path().join(".gitignore")).unwrap();
+        assert_eq!("a\nb\nc\n", contents);
+    }
+
+    #[test]
+    fn ignore_managed_path_writes_relative_path_to_project_root() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        let result = sut
+            .ignore_managed_path(
+                project.path(),
+                managed.path(),
+                Path::new("config/db.env"),
+                IgnoreTarget::GitIgnore,
+            )
+            .unwrap();
+
+        assert_eq!(GitIgnoreResult::FileCreated, result);
+        let contents = fs::read_to_string(project.path().join(".gitignore")).unwrap();
+        assert_eq!("/config/db.env\n", contents);
+        assert!(!project.path().join("config/.gitignore").exists());
+    }
+
+    #[test]
+    fn ignore_managed_path_when_pattern_already_ignores_path_nothing_is_added() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+        fs::create_dir_all(project.path().join("apps")).unwrap();
+        fs::write(project.path().join(".gitignore"), "*.env\n").unwrap();
+        fs::write(project.path().join("apps/.gitignore"), "local/\n").unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        let by_root = sut
+            .ignore_managed_path(
+                project.path(),
+                managed.path(),
+                Path::new("apps/db.env"),
+                IgnoreTarget::GitIgnore,
+            )
+            .unwrap();
+        let by_nested = sut
+            .ignore_managed_path(
+                project.path(),
+                managed.path(),
+                Path::new("apps/local/secret.json"),
+                IgnoreTarget::GitIgnore,
+            )
+            .unwrap();
+
+        assert_eq!(GitIgnoreResult::AlreadyIgnored, by_root);
+        assert_eq!(GitIgnoreResult::AlreadyIgnored, by_nested);
+        let contents = fs::read_to_string(project.path().join(".gitignore")).unwrap();
+        assert_eq!("*.env\n", contents);
+    }
+
+    #[test]
+    fn unignore_managed_path_removes_only_entries_added_by_puff() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+        fs::write(
+            project.path().join(".gitignore"),
+            "node_modules\n.env.user\n",
+        )
+        .unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        sut.ignore_managed_path(
+            project.path(),
+            managed.path(),
+            Path::new(".env"),
+            IgnoreTarget::GitIgnore,
+        )
+        .unwrap();
+
+        let removed_own = sut
+            .unignore_managed_path(project.path(), managed.path(), Path::new(".env"))
+            .unwrap();
+        let removed_user = sut
+            .unignore_managed_path(project.path(), managed.path(), Path::new(".env.user"))
+            .unwrap();
 
-    use super::GitIgnoreHandler;
+        assert_eq!(Some(IgnoreTarget::GitIgnore), removed_own);
+        assert_eq!(None, removed_user);
+        let contents = fs::read_to_string(project.path().join(".gitignore")).unwrap();
+        assert_eq!("node_modules\n.env.user\n", contents);
+    }
+
+    #[test]
+    fn ignore_managed_path_does_not_record_entries_it_did_not_write() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".gitignore"), "/.env\n!.env\n").unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        let result = sut
+            .ignore_managed_path(
+                project.path(),
+                managed.path(),
+                Path::new(".env"),
+                IgnoreTarget::GitIgnore,
+            )
+            .unwrap();
+        let removed = sut
+            .unignore_managed_path(project.path(), managed.path(), Path::new(".env"))
+            .unwrap();
+
+        assert_eq!(GitIgnoreResult::AlreadyIgnored, result);
+        assert_eq!(None, removed);
+        let contents = fs::read_to_string(project.path().join(".gitignore")).unwrap();
+        assert_eq!("/.env\n!.env\n", contents);
+    }
+
+    #[test]
+    fn unignore_managed_path_removes_unanchored_entries_of_older_versions() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+        fs::write(project.path().join(".gitignore"), "config/db.env\n").unwrap();
+        fs::write(
+            managed.path().join(GIT_IGNORE_ENTRIES_FILE),
+            "config/db.env\n",
+        )
+        .unwrap();
+
+        let removed = GitIgnoreHandler::new()
+            .unignore_managed_path(project.path(), managed.path(), Path::new("config/db.env"))
+            .unwrap();
+
+        assert_eq!(Some(IgnoreTarget::GitIgnore), removed);
+        assert_eq!(
+            "",
+            fs::read_to_string(project.path().join(".gitignore")).unwrap()
+        );
+    }
+
+    #[test]
+    fn ignore_managed_path_in_worktree_writes_to_common_info_exclude() {
+        let main = tempfile::tempdir().unwrap();
+        let worktree = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+        let worktree_git_dir = main.path().join(".git/worktrees/feature");
+        fs::create_dir_all(&worktree_git_dir).unwrap();
+        fs::write(worktree_git_dir.join("commondir"), "../..\n").unwrap();
+        fs::write(
+            worktree.path().join(".git"),
+            format!("gitdir: {}\n", worktree_git_dir.display()),
+        )
+        .unwrap();
+        let project_root = worktree.path().join("apps/web");
+        fs::create_dir_all(&project_root).unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        let result = sut
+            .ignore_managed_path(
+                &project_root,
+                managed.path(),
+                Path::new(".env"),
+                IgnoreTarget::InfoExclude,
+            )
+            .unwrap();
+        let exclude = main.path().join(".git/info/exclude");
+
+        assert_eq!(GitIgnoreResult::FileCreated, result);
+        assert_eq!("/apps/web/.env\n", fs::read_to_string(&exclude).unwrap());
+        assert!(!project_root.join(".gitignore").exists());
+
+        let again = sut
+            .ignore_managed_path(
+                &project_root,
+                managed.path(),
+                Path::new(".env"),
+                IgnoreTarget::GitIgnore,
+            )
+            .unwrap();
+        let removed = sut
+            .unignore_managed_path(&project_root, managed.path(), Path::new(".env"))
+            .unwrap();
+
+        assert_eq!(GitIgnoreResult::AlreadyIgnored, again);
+        assert_eq!(Some(IgnoreTarget::InfoExclude), removed);
+        assert_eq!("", fs::read_to_string(&exclude).unwrap());
+    }
+
+    #[test]
+    fn ignore_managed_path_with_info_exclude_outside_of_repository_fails() {
+        let project = tempfile::tempdir().unwrap();
+        let managed = tempfile::tempdir().unwrap();
+
+        let sut = GitIgnoreHandler::new();
+        let result = sut.ignore_managed_path(
+            project.path(),
+            managed.path(),
+            Path::new(".env"),
+            IgnoreTarget::InfoExclude,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn is_ignored_matches_names_at_any_depth() {
+        let rules = rules("# comment\n.env\n*.pem\n");
+        assert!(rules.is_ignored(Path::new(".env"), false));
+        assert!(rules.is_ignored(Path::new("apps/web/.env"), false));
+        assert!(rules.is_ignored(Path::new("certs/server.pem"), false));
+        assert!(!rules.is_ignored(Path::new(".env.local"), false));
+    }
+
+    #[test]
+    fn is_ignored_anchors_patterns_containing_slash() {
+        let rules = rules("/config/local.json\nbuild/\n");
+        assert!(rules.is_ignored(Path::new("config/local.json"), false));
+        assert!(!rules.is_ignored(Path::new("apps/config/local.json"), false));
+        assert!(rules.is_ignored(Path::new("build/out.txt"), false));
+        assert!(!rules.is_ignored(Path::new("build"), false));
+    }
+
+    #[test]
+    fn is_ignored_respects_negations() {
+        let rules = rules(".env*\n!.env.example\n");
+        assert!(rules.is_ignored(Path::new(".env.local"), false));
+        assert!(!rules.is_ignored(Path::new(".env.example"), false));
+    }
+
+    #[test]
+    fn ignored_paths_without_repository_reads_nested_git_ignore_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("apps/web")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.key\n").unwrap();
+        fs::write(
+            dir.path().join("apps/web/.gitignore"),
+            "local.json\n!public.key\n",
+        )
+        .unwrap();
+
+        let paths = [
+            PathBuf::from("apps/web/local.json"),
+            PathBuf::from("local.json"),
+            PathBuf::from("apps/private.key"),
+            PathBuf::from("apps/web/public.key"),
+        ];
+
+        let ignored = GitIgnoreRules::ignored_paths(None, dir.path(), &paths).unwrap();
+
+        assert_eq!(vec![paths[0].clone(), paths[2].clone()], ignored);
+    }
 
     #[test]
     fn add_to_git_ignore_when_git_ignore_does_not_exist() {
@@ -75,7 +782,9 @@ mod tests {
         let file_name = "testfile";
 
         let sut = GitIgnoreHandler::new();
-        let result = sut.add_to_git_ignore(dir.path(), file_name).unwrap();
+        let result = sut
+            .add_entry(&dir.path().join(".gitignore"), file_name)
+            .unwrap();
 
         assert_eq!(GitIgnoreResult::FileCreated, result);
 
@@ -95,7 +804,9 @@ mod tests {
         let file_name = "testfile";
 
         let sut = GitIgnoreHandler::new();
-        let result = sut.add_to_git_ignore(dir.path(), file_name).unwrap();
+        let result = sut
+            .add_entry(&dir.path().join(".gitignore"), file_name)
+            .unwrap();
 
         assert_eq!(GitIgnoreResult::FileUpdated, result);
 
@@ -0,0 +1,214 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{config::projects::ManagedItem, git::GitRepository, git_ignore::GitIgnoreRules};
+
+/// Reason why git could pick up a managed item
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageProblem {
+    /// No .gitignore or .git/info/exclude rule ignores the path
+    NotIgnored,
+    /// The path (or something inside it) is in git's index, so ignore rules don't apply
+    Tracked,
+}
+
+impl Display for CoverageProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoverageProblem::NotIgnored => write!(f, "not ignored by git"),
+            CoverageProblem::Tracked => write!(f, "tracked by git"),
+        }
+    }
+}
+
+/// A managed item that git could commit in one of the project's checkouts
+#[derive(Debug, Serialize)]
+pub struct UncoveredItem {
+    /// The project directory in the checkout: the associated path or its counterpart
+    /// in a linked worktree
+    pub checkout: PathBuf,
+    /// Path of the managed item, relative to the project directory
+    pub path: PathBuf,
+    pub problem: CoverageProblem,
+}
+
+/// Checks whether git ignores every managed item, both in `project_root` and in the
+/// corresponding directories of the repository's other worktrees. Items are reported
+/// when they are tracked, or when they exist in the checkout and are not ignored.
+/// Projects outside of git have nothing to report.
+pub fn find_uncovered_items(
+    project_root: &Path,
+    items: &[ManagedItem],
+) -> Result<Vec<UncoveredItem>> {
+    let Some(repository) = GitRepository::discover(project_root)? else {
+        return Ok(vec![]);
+    };
+    let offset = project_root.strip_prefix(&repository.work_tree)?.to_owned();
+
+    // The project's own checkout goes first, exactly as it was given
+    let mut work_trees = vec![repository.work_tree.clone()];
+    let own = fs::canonicalize(&repository.work_tree)?;
+    for work_tree in repository.worktrees()? {
+        if fs::canonicalize(&work_tree).is_ok_and(|w| w != own) {
+            work_trees.push(work_tree);
+        }
+    }
+
+    let mut uncovered = vec![];
+    for work_tree in work_trees {
+        let checkout = if offset.as_os_str().is_empty() {
+            work_tree.clone()
+        } else {
+            work_tree.join(&offset)
+        };
+        if !checkout.is_dir() {
+            continue;
+        }
+
+        let checkout_repository = GitRepository {
+            work_tree,
+            common_dir: repository.common_dir.clone(),
+        };
+        let paths: Vec<PathBuf> = items.iter().map(|i| offset.join(i.path())).collect();
+        // Without a working git binary, only the ignore rules can be checked
+        let tracked = checkout_repository
+            .tracked_paths(&paths)
+            .unwrap_or_default();
+        let existing: Vec<PathBuf> = items
+            .iter()
+            .map(|i| i.path().to_owned())
+            .filter(|p| checkout.join(p).symlink_metadata().is_ok())
+            .collect();
+        let ignored =
+            GitIgnoreRules::ignored_paths(Some(&checkout_repository), &checkout, &existing)?;
+
+        for (item, path) in items.iter().zip(&paths) {
+            let problem = if tracked.iter().any(|t| t.starts_with(path)) {
+                CoverageProblem::Tracked
+            } else if existing.iter().any(|p| p == item.path())
+                && !ignored.iter().any(|p| p == item.path())
+            {
+                CoverageProblem::NotIgnored
+            } else {
+                continue;
+            };
+
+            uncovered.push(UncoveredItem {
+                checkout: checkout.clone(),
+                path: item.path().to_owned(),
+                problem,
+            });
+        }
+    }
+
+    Ok(uncovered)
+}
+
+/// Prints a warning listing the uncovered items, along with hints on how to fix them
+pub fn print_warning(uncovered: &[UncoveredItem], project_root: &Path) {
+    if uncovered.is_empty() {
+        return;
+    }
+
+    println!("WARNING: git could commit these managed items:");
+    for item in uncovered {
+        if item.checkout == project_root {
+            println!("  {} ({})", item.path.display(), item.problem);
+        } else {
+            println!(
+                "  {} in {} ({})",
+                item.path.display(),
+                item.checkout.display(),
+                item.problem
+            );
+        }
+    }
+
+    if uncovered
+        .iter()
+        .any(|i| i.problem == CoverageProblem::NotIgnored)
+    {
+        println!(
+            "Run `puff gitignore fix` to add the missing entries (with -x, they cover all worktrees)."
+        );
+    }
+    if uncovered
+        .iter()
+        .any(|i| i.problem == CoverageProblem::Tracked)
+    {
+        println!("Use `git rm --cached <path>` to stop tracking the tracked ones.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoverageProblem, find_uncovered_items};
+    use crate::config::projects::ManagedItem;
+    use std::{fs, path::PathBuf};
+
+    #[test]
+    fn find_uncovered_items_reports_items_not_ignored_in_any_worktree() {
+        let main = tempfile::tempdir().unwrap();
+        let worktree = tempfile::tempdir().unwrap();
+        let worktree_git_dir = main.path().join(".git/worktrees/feature");
+        fs::create_dir_all(&worktree_git_dir).unwrap();
+        fs::write(worktree_git_dir.join("commondir"), "../..\n").unwrap();
+        fs::write(
+            worktree_git_dir.join("gitdir"),
+            format!("{}\n", worktree.path().join(".git").display()),
+        )
+        .unwrap();
+        fs::write(
+            worktree.path().join(".git"),
+            format!("gitdir: {}\n", worktree_git_dir.display()),
+        )
+        .unwrap();
+        fs::write(main.path().join(".gitignore"), ".env\nconfig\n").unwrap();
+        fs::write(worktree.path().join(".gitignore"), ".env\n").unwrap();
+        for checkout in [main.path(), worktree.path()] {
+            fs::write(checkout.join(".env"), "A=1").unwrap();
+            fs::create_dir_all(checkout.join("config")).unwrap();
+        }
+        let items = vec![
+            ManagedItem::File(PathBuf::from(".env")),
+            ManagedItem::Directory(PathBuf::from("config")),
+        ];
+
+        let result = find_uncovered_items(main.path(), &items).unwrap();
+
+        assert_eq!(1, result.len());
+        assert_eq!(
+            fs::canonicalize(worktree.path()).unwrap(),
+            result[0].checkout
+        );
+        assert_eq!(PathBuf::from("config"), result[0].path);
+        assert_eq!(CoverageProblem::NotIgnored, result[0].problem);
+    }
+
+    #[test]
+    fn find_uncovered_items_skips_items_missing_in_checkout() {
+        let repo = tempfile::tempdir().unwrap();
+        fs::create_dir_all(repo.path().join(".git")).unwrap();
+        let items = vec![ManagedItem::File(PathBuf::from(".env"))];
+
+        let result = find_uncovered_items(repo.path(), &items).unwrap();
+
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn find_uncovered_items_outside_of_repository_reports_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let items = vec![ManagedItem::File(PathBuf::from(".env"))];
+
+        let result = find_uncovered_items(dir.path(), &items).unwrap();
+
+        assert!(result.is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use inquire::{
-    Confirm, Select, Text,
+    Confirm, MultiSelect, Select, Text,
     validator::{StringValidator, Validation},
 };
 
@@ -37,3 +37,12 @@ pub fn prompt_select(prompt: &str, items: &[String]) -> Result<usize> {
     let choice = Select::new(prompt, items.clone()).prompt()?;
     Ok(items.iter().position(|&s| s == choice).unwrap_or(0))
 }
+
+/// Lets the user pick any number of items. Returns indices of the chosen items.
+pub fn prompt_multi_select(prompt: &str, items: &[String]) -> Result<Vec<usize>> {
+    let all: Vec<usize> = (0..items.len()).collect();
+    let choices = MultiSelect::new(prompt, items.to_vec())
+        .with_default(&all)
+        .raw_prompt()?;
+    Ok(choices.into_iter().map(|c| c.index).collect())
+}
@@ -1,29 +1,78 @@
-use anyhow::Result;
+// Lints that only some of the older unit tests trip
+#![cfg_attr(
+    test,
+    allow(clippy::len_zero, clippy::needless_borrows_for_generic_args)
+)]
+
+use anyhow::{Result, anyhow};
 use app_init::AppInitializer;
 use clap::{CommandFactory, Parser};
 use clap_complete::CompleteEnv;
-use cli_args::{AppArgs, Command};
+use cli_args::{
+    AppArgs, BackupsSubcommand, Command, ConfigSubcommand, GitignoreSubcommand, HooksSubcommand,
+    RootsSubcommand, TrashSubcommand,
+};
 use commands::{
-    add_command::AddCommand, cd_command::CdCommand, file_forget_command::ForgetCommand,
-    init_command::InitCommand, link_command::LinkCommand, list_command::ListCommand,
-    project_forget_command::ProjectForgetCommand, status_command::StatusCommand,
+    add_command::AddCommand,
+    backups_command::BackupsCommand,
+    cd_command::CdCommand,
+    check_staged_command::CheckStagedCommand,
+    config_command::ConfigCommand,
+    doctor_command::DoctorCommand,
+    file_forget_command::ForgetCommand,
+    gitignore_command::GitIgnoreCommand,
+    hooks_command::HooksCommand,
+    init_command::InitCommand,
+    link_command::LinkCommand,
+    list_command::{ListCommand, ListOptions},
+    merge_command::MergeCommand,
+    mv_command::MvCommand,
+    project_describe_command::ProjectDescribeCommand,
+    project_forget_command::ProjectForgetCommand,
+    project_mark_command::ProjectMarkCommand,
+    project_show_command::ProjectShowCommand,
+    prune_links_command::{PruneAction, PruneLinksCommand},
+    reconcile_command::ReconcileCommand,
+    recover_command::RecoverCommand,
+    scan_command::ScanCommand,
+    split_command::SplitCommand,
+    status_command::StatusCommand,
+    trash_command::TrashCommand,
 };
 use config::{
     app_config::AppConfigManager, locations::LocationsProvider, projects::ProjectsRetriever,
+    roots::PathRewrite,
 };
+use conflicts::{ConflictResolver, OnConflict};
+use git_ignore::IgnoreTarget;
+use patterns::is_glob;
 use std::{env, path::Path};
 
 mod app_init;
+mod backups;
 mod cli_args;
 mod commands;
 mod completions;
 mod config;
+mod conflicts;
+mod dotenv;
 mod fs_utils;
+mod git;
 mod git_ignore;
+mod ignore_coverage;
 mod io_utils;
 mod managed_dirs;
+mod marker;
 mod migration;
+mod output;
+mod patterns;
+mod permissions;
 mod project_init;
+mod puff_ignore;
+mod puff_links;
+mod remotes;
+mod shred;
+mod trash;
 
 fn main() {
     if let Err(e) = run() {
@@ -82,82 +131,350 @@ fn run() -> Result<()> {
         config_file_path: locations_provider.get_config_file_path(),
     };
     let app_config = app_config_manager.get_config()?;
+    // Expired backups are only removed by the commands that may make new ones, so that
+    // other commands never delete anything as a side effect
+    let makes_backups = matches!(
+        args.command,
+        Command::Init(_)
+            | Command::Add { .. }
+            | Command::Link { .. }
+            | Command::Project {
+                subcommand: cli_args::ProjectSubcommand::Forget(_)
+            }
+    );
+    let settings = app_config.settings.clone();
+    // Set by the commands that handle several paths and go on after a failing one
+    let mut some_failed = false;
 
     match args.command {
         Command::Init(options) => {
-            let retriever = ProjectsRetriever::new(app_config, &locations_provider);
             let cwd = env::current_dir()?;
+            let settings = locations_provider.settings_for_path(&cwd, &app_config.settings)?;
+            let retriever = ProjectsRetriever::new(app_config, &locations_provider);
 
             let command = InitCommand {
                 projects_retriever: &retriever,
                 app_config_manager: &app_config_manager,
                 locations_provider: &locations_provider,
+                conflict_resolver: ConflictResolver::new(options.on_conflict, OnConflict::Backup)
+                    .with_file_mode(settings.managed_file_mode),
             };
-            command.init(&cwd, options.name, options.associate)?;
+            match options.scan {
+                Some(root) => command.init_scan(&root, options.skip_confirmation)?,
+                None => command.init(&cwd, options.name, options.associate, options.marker)?,
+            }
         }
         Command::Add {
             files,
             git_ignore,
+            git_exclude,
             dir,
+            no_default_excludes,
+            on_conflict,
         } => {
             let cwd = env::current_dir()?;
-            let command = AddCommand::new(&locations_provider);
+            let settings = locations_provider.settings_for_path(&cwd, &app_config.settings)?;
+            let git_ignore = if git_exclude {
+                Some(IgnoreTarget::InfoExclude)
+            } else if git_ignore {
+                Some(settings.git_ignore_target)
+            } else {
+                None
+            };
+            let command = AddCommand::with_conflict_resolver(
+                &locations_provider,
+                ConflictResolver::new(on_conflict, OnConflict::Fail),
+            )
+            .with_file_mode(settings.managed_file_mode);
             let mut had_error = false;
             for file in files {
-                if let Err(e) = command.add_file(file, &cwd, git_ignore, dir) {
-                    eprintln!("Error: {e}");
-                    had_error = true;
+                let pattern = file.to_string_lossy().into_owned();
+                // Existing paths are added as they are, even if their names contain wildcards
+                let result = if is_glob(&pattern) && !cwd.join(&file).exists() {
+                    if dir {
+                        Err(anyhow!(
+                            "--dir can't be used with the glob pattern '{pattern}', which only matches existing paths."
+                        ))
+                    } else {
+                        command
+                            .add_glob(&pattern, &cwd, git_ignore, no_default_excludes)
+                            .map(|summary| summary.failed == 0)
+                    }
+                } else {
+                    command.add_file(file, &cwd, git_ignore, dir).map(|_| true)
+                };
+                match result {
+                    Ok(succeeded) => had_error |= !succeeded,
+                    Err(e) => {
+                        eprintln!("Error: {e}");
+                        had_error = true;
+                    }
                 }
             }
-            if had_error {
-                std::process::exit(1);
-            }
+            some_failed = had_error;
+        }
+        Command::Scan { add_all, format } => {
+            let cwd = env::current_dir()?;
+            let settings = locations_provider.settings_for_path(&cwd, &app_config.settings)?;
+            let command = ScanCommand::new(&locations_provider, settings.managed_file_mode);
+            command.scan(&cwd, add_all, format)?;
         }
         Command::List(options) => {
             let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
             let command = ListCommand::new(&projects_retriever);
-            command.list(options.only_associated, options.only_unassociated)?;
+            let list_options = ListOptions {
+                only_associated: options.only_associated,
+                only_unassociated: options.only_unassociated,
+                name_filter: options.name_filter,
+                only_unhealthy: options.only_unhealthy,
+                sort: options.sort,
+                reverse: options.reverse,
+            };
+            command.list(&list_options, options.format)?;
         }
-        Command::Forget { files, delete_file } => {
+        Command::Forget {
+            files,
+            delete_file,
+            shred,
+        } => {
             let cwd = env::current_dir()?;
             let command = ForgetCommand::new(&locations_provider);
             let mut had_error = false;
             for file in files {
-                if let Err(e) = command.forget_file(file, &cwd, delete_file) {
+                if let Err(e) = command.forget_file(file, &cwd, delete_file, shred) {
                     eprintln!("Error: {e}");
                     had_error = true;
                 }
             }
-            if had_error {
-                std::process::exit(1);
-            }
+            some_failed = had_error;
         }
-        Command::Status => {
+        Command::Mv { old, new } => {
+            let cwd = env::current_dir()?;
+            let command = MvCommand::new(&locations_provider);
+            command.mv(&old, &new, &cwd)?;
+        }
+        Command::Split { dir } => {
+            let cwd = env::current_dir()?;
+            let command = SplitCommand::new(&locations_provider);
+            command.split(&dir, &cwd)?;
+        }
+        Command::Merge {
+            dir,
+            skip_confirmation,
+            dry_run,
+        } => {
+            let cwd = env::current_dir()?;
+            let settings = locations_provider.settings_for_path(&cwd, &app_config.settings)?;
+            let command = MergeCommand::new(&locations_provider, settings.managed_file_mode);
+            command.merge(&dir, &cwd, skip_confirmation, dry_run)?;
+        }
+        Command::Status { format } => {
             let cwd = env::current_dir()?;
             let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
             let command = StatusCommand::new(&locations_provider, &projects_retriever);
-            command.status(&cwd)?;
+            command.status(&cwd, format)?;
         }
         Command::Project { subcommand } => match subcommand {
             cli_args::ProjectSubcommand::Forget(details) => {
                 let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
-                let command = ProjectForgetCommand::new(&projects_retriever, &app_config_manager);
+                let command = ProjectForgetCommand::new(
+                    &projects_retriever,
+                    &app_config_manager,
+                    &locations_provider,
+                );
                 command.forget_project(
                     details.project_name,
                     details.delete_files,
+                    details.shred,
                     details.skip_confirmation,
                 )?;
             }
+            cli_args::ProjectSubcommand::Show(details) => {
+                let cwd = env::current_dir()?;
+                let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
+                let command = ProjectShowCommand::new(&locations_provider, &projects_retriever);
+                command.show(details.project_name, &cwd, details.format)?;
+            }
+            cli_args::ProjectSubcommand::Mark(details) => {
+                let cwd = env::current_dir()?;
+                let command = ProjectMarkCommand::new(&locations_provider, &app_config_manager);
+                command.mark(details.project_name, &cwd)?;
+            }
+            cli_args::ProjectSubcommand::Describe(details) => {
+                let cwd = env::current_dir()?;
+                let command = ProjectDescribeCommand::new(&locations_provider);
+                command.describe(details.description, details.project_name, &cwd)?;
+            }
         },
-        Command::Link { project_name } => {
+        Command::Doctor { fix } => {
+            let file_mode = app_config.settings.managed_file_mode;
+            let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
+            let command = DoctorCommand::new(&projects_retriever, &locations_provider, file_mode);
+            command.check(fix)?;
+        }
+        Command::Reconcile {
+            missing_action,
+            list_only,
+        } => {
+            let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
+            let command = ReconcileCommand::new(
+                &projects_retriever,
+                &app_config_manager,
+                &locations_provider,
+            );
+            command.reconcile(missing_action, list_only)?;
+        }
+        Command::Recover {
+            dirs,
+            skip_confirmation,
+        } => {
+            let command = RecoverCommand::new(&app_config_manager, &locations_provider);
+            command.recover(&dirs, skip_confirmation)?;
+        }
+        Command::PruneLinks {
+            dirs,
+            delete,
+            restore,
+            list_only,
+        } => {
+            let cwd = env::current_dir()?;
+            let action = if delete {
+                Some(PruneAction::Delete)
+            } else if restore {
+                Some(PruneAction::Restore)
+            } else {
+                None
+            };
+            let command = PruneLinksCommand::new(&app_config_manager, &locations_provider);
+            command.prune(&dirs, &cwd, action, list_only)?;
+        }
+        Command::Gitignore { subcommand } => match subcommand {
+            GitignoreSubcommand::Fix { git_exclude } => {
+                let cwd = env::current_dir()?;
+                let target = if git_exclude {
+                    IgnoreTarget::InfoExclude
+                } else {
+                    locations_provider
+                        .settings_for_path(&cwd, &app_config.settings)?
+                        .git_ignore_target
+                };
+                let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
+                let command = GitIgnoreCommand::new(&locations_provider, &projects_retriever);
+                command.fix(&cwd, target)?;
+            }
+        },
+        Command::Hooks { subcommand } => match subcommand {
+            HooksSubcommand::Install { worktrees } => {
+                let cwd = env::current_dir()?;
+                let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
+                let command = HooksCommand::new(&locations_provider, &projects_retriever);
+                command.install(&cwd, worktrees)?;
+            }
+            HooksSubcommand::PostCheckout { previous_head, .. } => {
+                let cwd = env::current_dir()?;
+                let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
+                let command = HooksCommand::new(&locations_provider, &projects_retriever);
+                command.post_checkout(&cwd, &previous_head)?;
+            }
+        },
+        Command::CheckStaged => {
+            let cwd = env::current_dir()?;
+            let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
+            let command = CheckStagedCommand::new(&projects_retriever);
+            command.check(&cwd)?;
+        }
+        Command::Backups { subcommand } => {
+            let cwd = env::current_dir()?;
+            let command = BackupsCommand::new(&locations_provider, &settings);
+            match subcommand {
+                BackupsSubcommand::List { project } => command.list(project.as_deref(), &cwd)?,
+                BackupsSubcommand::Restore {
+                    path,
+                    id,
+                    to,
+                    project,
+                } => command.restore(
+                    &path,
+                    id.as_deref(),
+                    to.as_deref(),
+                    project.as_deref(),
+                    &cwd,
+                )?,
+                BackupsSubcommand::Clean {
+                    older_than,
+                    project,
+                    skip_confirmation,
+                } => command.clean(older_than, project.as_deref(), &cwd, skip_confirmation)?,
+            }
+        }
+        Command::Trash { subcommand } => {
+            let command = TrashCommand::new(&locations_provider, &settings);
+            match subcommand {
+                TrashSubcommand::List => command.list()?,
+                TrashSubcommand::Restore { id, to } => {
+                    command.restore(&id, to.as_deref(), &env::current_dir()?)?
+                }
+                TrashSubcommand::Empty {
+                    older_than,
+                    shred,
+                    skip_confirmation,
+                } => command.empty(older_than, shred, skip_confirmation)?,
+            }
+        }
+        Command::Config { subcommand } => {
+            let command = ConfigCommand::new(&app_config_manager, &locations_provider);
+            match subcommand {
+                ConfigSubcommand::List { project } => command.list(project.as_deref())?,
+                ConfigSubcommand::Get { key, project } => command.get(key, project.as_deref())?,
+                ConfigSubcommand::Set {
+                    key,
+                    value,
+                    project,
+                } => command.set(key, &value, project.as_deref())?,
+                ConfigSubcommand::Unset { key, project } => command.unset(key, &project)?,
+                ConfigSubcommand::Roots { subcommand } => match subcommand {
+                    RootsSubcommand::List => command.list_roots()?,
+                    RootsSubcommand::Add { name, path } => command.add_root(&name, &path)?,
+                    RootsSubcommand::Remove { name } => command.remove_root(&name)?,
+                },
+                ConfigSubcommand::Import { file, rewrites } => {
+                    let rewrites = rewrites
+                        .iter()
+                        .map(|r| PathRewrite::parse(r))
+                        .collect::<Result<Vec<_>>>()?;
+                    command.import(&file, &rewrites)?
+                }
+            }
+        }
+        Command::Link {
+            project_name,
+            on_conflict,
+        } => {
             let cwd = env::current_dir()?;
+            let settings = locations_provider.settings_for_path(&cwd, &app_config.settings)?;
             let projects_retriever = ProjectsRetriever::new(app_config, &locations_provider);
-            let command = LinkCommand::new(&projects_retriever, &locations_provider);
-            command.link(&project_name, &cwd)?;
+            let command = LinkCommand::new(
+                &projects_retriever,
+                &locations_provider,
+                ConflictResolver::new(on_conflict, OnConflict::Backup)
+                    .with_file_mode(settings.managed_file_mode),
+            );
+            match project_name {
+                Some(project_name) => command.link(&project_name, &cwd)?,
+                None => command.link_current(&cwd)?,
+            }
         }
         // handled up above
         Command::Completions { .. } | Command::Cd { .. } => unreachable!(),
     }
 
+    if makes_backups {
+        backups::remove_expired_after_command(&locations_provider, &settings);
+    }
+    // The errors were already printed, along with what succeeded
+    if some_failed {
+        std::process::exit(1);
+    }
+
     Ok(())
 }
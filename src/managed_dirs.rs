@@ -3,10 +3,27 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::{config::project_metadata, git_ignore, puff_ignore, remotes};
+
 const MANAGED_DIRS_FILE: &str = ".puff_managed_dirs";
 
-pub fn managed_dirs_filename() -> &'static str {
-    MANAGED_DIRS_FILE
+/// Files that puff keeps in the root of a project's managed dir for its own bookkeeping.
+/// They are not managed items and never get symlinked into the project.
+const METADATA_FILES: [&str; 5] = [
+    MANAGED_DIRS_FILE,
+    project_metadata::PROJECT_METADATA_FILE,
+    git_ignore::GIT_IGNORE_ENTRIES_FILE,
+    remotes::GIT_REMOTES_FILE,
+    puff_ignore::PUFF_IGNORE_FILE,
+];
+
+/// Checks whether `path` is one of puff's metadata files in the root of `managed_dir`
+pub fn is_metadata_file(managed_dir: &Path, path: &Path) -> bool {
+    path.parent() == Some(managed_dir)
+        && path.is_file()
+        && path
+            .file_name()
+            .is_some_and(|n| METADATA_FILES.iter().any(|m| n == *m))
 }
 
 fn managed_dirs_path(managed_dir: &Path) -> PathBuf {
@@ -160,6 +177,23 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn is_metadata_file_matches_only_root_bookkeeping_files() {
+        let dir = tempfile::tempdir().unwrap();
+        add_managed_dir(dir.path(), Path::new("config")).unwrap();
+        fs::create_dir_all(dir.path().join("config")).unwrap();
+        fs::write(dir.path().join("config/.puff_managed_dirs"), "").unwrap();
+
+        assert!(is_metadata_file(
+            dir.path(),
+            &dir.path().join(".puff_managed_dirs")
+        ));
+        assert!(!is_metadata_file(
+            dir.path(),
+            &dir.path().join("config/.puff_managed_dirs")
+        ));
+    }
+
     #[test]
     fn classify_path_works() {
         let dir = tempfile::tempdir().unwrap();
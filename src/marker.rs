@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::config::app_config::{AppConfig, Project};
+
+/// Name of the marker file that identifies a puff project. It's meant to be committed,
+/// so that clones and worktrees of the repository are recognized as the same project.
+pub const MARKER_FILE: &str = ".puff";
+
+/// Content of the `.puff` marker file
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProjectMarker {
+    pub name: String,
+    pub id: String,
+}
+
+impl ProjectMarker {
+    pub fn new(name: &str, id: &str) -> Self {
+        ProjectMarker {
+            name: name.to_owned(),
+            id: id.to_owned(),
+        }
+    }
+
+    /// Reads the marker file of `dir`, if there is one
+    pub fn read(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(MARKER_FILE);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let marker = serde_json::from_str(&content)
+            .with_context(|| format!("Marker file '{}' is not valid.", path.display()))?;
+        Ok(Some(marker))
+    }
+
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        fs::write(
+            dir.join(MARKER_FILE),
+            serde_json::to_string_pretty(self)? + "\n",
+        )?;
+        Ok(())
+    }
+
+    /// Finds the registered project the marker refers to: by id, or by name for projects
+    /// registered before the marker was created
+    pub fn find_project<'a>(&self, config: &'a AppConfig) -> Option<&'a Project> {
+        config
+            .projects
+            .iter()
+            .find(|p| p.id == self.id)
+            .or_else(|| config.projects.iter().find(|p| p.name == self.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProjectMarker;
+    use crate::config::app_config::{AppConfig, Project};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn read_returns_written_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        ProjectMarker::new("repo", "1").write(dir.path()).unwrap();
+
+        let result = ProjectMarker::read(dir.path()).unwrap();
+
+        assert_eq!(Some(ProjectMarker::new("repo", "1")), result);
+    }
+
+    #[test]
+    fn find_project_falls_back_to_name() {
+        let config = AppConfig {
+            projects: vec![Project::with_id("repo", "2", Path::new("/repo"))],
+            ..Default::default()
+        };
+
+        let by_id = ProjectMarker::new("other", "2").find_project(&config);
+        let by_name = ProjectMarker::new("repo", "1").find_project(&config);
+
+        assert_eq!(Some("repo"), by_id.map(|p| p.name.as_str()));
+        assert_eq!(Some("repo"), by_name.map(|p| p.name.as_str()));
+    }
+
+    #[test]
+    fn read_with_invalid_content_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".puff"), "not json").unwrap();
+
+        let result = ProjectMarker::read(dir.path());
+
+        assert!(result.is_err());
+    }
+}
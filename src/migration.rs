@@ -1,8 +1,14 @@
-use crate::config::{app_config::AppConfigManager, locations::LocationsProvider};
+use crate::config::{
+    app_config::{AppConfigManager, is_valid_id},
+    locations::LocationsProvider,
+    project_metadata::ProjectMetadata,
+};
 use crate::fs_utils::copy_dir_recursive;
+use crate::marker::ProjectMarker;
 use anyhow::{Result, bail};
 use std::fs;
 use std::path::Path;
+use uuid::Uuid;
 
 /// Migrates the legacy `{config_path}/configs/` directory to `{data_path}/projects/`.
 /// Returns `Ok(true)` if migration was performed, `Ok(false)` if not needed.
@@ -43,6 +49,125 @@ pub fn migrate_projects_path_if_needed(locations_provider: &LocationsProvider) -
     Ok(true)
 }
 
+/// Creates the metadata file in managed dirs that don't have one yet (created by older
+/// versions of puff, or copied from such a machine). Associated projects keep the id
+/// from config.json; the others get a new one. Metadata that can't be read is left
+/// alone with a warning, so that one broken project doesn't stop puff.
+pub fn create_missing_project_metadata(locations_provider: &LocationsProvider) -> Result<()> {
+    let projects_path = locations_provider.get_projects_data_path();
+    let Ok(entries) = fs::read_dir(&projects_path) else {
+        return Ok(());
+    };
+    let config = AppConfigManager::new(locations_provider.get_config_file_path())?.get_config()?;
+
+    for entry in entries {
+        let managed_dir = entry?.path();
+        if !managed_dir.is_dir() {
+            continue;
+        }
+        match ProjectMetadata::read(&managed_dir) {
+            Ok(None) => {}
+            Ok(Some(_)) => continue,
+            Err(e) => {
+                eprintln!("Warning: {e} The project is skipped until it's fixed.");
+                continue;
+            }
+        }
+        let name = managed_dir.file_name().unwrap_or_default();
+        match config.projects.iter().find(|p| name == p.name.as_str()) {
+            Some(project) => ProjectMetadata {
+                created_at: None,
+                ..ProjectMetadata::new(&project.id)
+            }
+            .write(&managed_dir)?,
+            None => {
+                ProjectMetadata::load(&managed_dir)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Makes the project id the link between config.json and the data directory:
+/// - placeholder ids of old versions (e.g. "1") are replaced by new UUIDs
+/// - registry entries take the id from their project's metadata, and the name of its
+///   managed dir (which differs for projects renamed on another machine)
+///
+/// The .puff markers of associated directories are updated along with the registry.
+pub fn normalize_project_ids(locations_provider: &LocationsProvider) -> Result<()> {
+    let projects_path = locations_provider.get_projects_data_path();
+    let config_manager = AppConfigManager::new(locations_provider.get_config_file_path())?;
+    let mut config = config_manager.get_config()?;
+    let original = config.projects.clone();
+
+    let mut stored = ProjectMetadata::read_all(&projects_path)?;
+    for (name, metadata) in &mut stored {
+        if is_valid_id(&metadata.id) {
+            continue;
+        }
+        let new_id = Uuid::new_v4().to_string();
+        for project in config.projects.iter_mut() {
+            if project.id == metadata.id && project.name == *name {
+                project.id = new_id.clone();
+            }
+        }
+        metadata.id = new_id;
+        metadata.write(&projects_path.join(&*name))?;
+    }
+
+    // Entries are matched by id. Entries with placeholder ids are matched by name, unless
+    // another entry claims that project by id already.
+    let matches: Vec<_> = config
+        .projects
+        .iter()
+        .map(|project| {
+            stored
+                .iter()
+                .find(|(_, m)| m.id == project.id)
+                .or_else(|| {
+                    if is_valid_id(&project.id) {
+                        None
+                    } else {
+                        stored.iter().find(|(name, _)| *name == project.name)
+                    }
+                })
+                .filter(|(_, m)| {
+                    m.id == project.id || !config.projects.iter().any(|p| p.id == m.id)
+                })
+                .map(|(name, m)| (name.clone(), m.id.clone()))
+        })
+        .collect();
+    for (project, stored_project) in config.projects.iter_mut().zip(matches) {
+        match stored_project {
+            Some((name, id)) => {
+                project.name = name;
+                project.id = id;
+            }
+            None if !is_valid_id(&project.id) => project.id = Uuid::new_v4().to_string(),
+            None => {}
+        }
+    }
+
+    let mut changed = false;
+    for (before, after) in original.iter().zip(&config.projects) {
+        if before.id == after.id && before.name == after.name {
+            continue;
+        }
+        changed = true;
+        if let Ok(Some(marker)) = ProjectMarker::read(&after.path)
+            && marker.id == before.id
+        {
+            ProjectMarker::new(&after.name, &after.id).write(&after.path)?;
+        }
+    }
+    if changed {
+        config_manager.save_config(&config)?;
+    }
+
+    Ok(())
+}
+
 /// Best-effort: walk each associated project's user directory and repoint
 /// symlinks that targeted the old configs/ path to the new projects/ path.
 fn repoint_symlinks(locations_provider: &LocationsProvider, old_base: &Path, new_base: &Path) {
@@ -204,4 +329,100 @@ mod tests {
         assert!(target.starts_with(data_dir.path().join("projects")));
         assert_eq!(fs::read_to_string(&symlink_path).unwrap(), "SECRET=1");
     }
+
+    #[test]
+    fn missing_metadata_is_created_with_id_from_config() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        write_config(
+            config_dir.path(),
+            r#"{"projects":[{"name":"associated","id":"abc","path":"/somewhere"}]}"#,
+        );
+        let projects = data_dir.path().join("projects");
+        fs::create_dir_all(projects.join("associated")).unwrap();
+        fs::create_dir_all(projects.join("unassociated")).unwrap();
+
+        let lp = setup_locations(config_dir.path(), data_dir.path());
+        create_missing_project_metadata(&lp).unwrap();
+
+        let associated = ProjectMetadata::read(&projects.join("associated"))
+            .unwrap()
+            .unwrap();
+        let unassociated = ProjectMetadata::read(&projects.join("unassociated"))
+            .unwrap()
+            .unwrap();
+        assert_eq!("abc", associated.id);
+        assert_ne!("abc", unassociated.id);
+    }
+
+    #[test]
+    fn placeholder_ids_are_replaced_in_registry_and_metadata() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        write_config(
+            config_dir.path(),
+            r#"{"projects":[{"name":"proj","id":"1","path":"/somewhere"},{"name":"nodata","id":"2","path":"/other"}]}"#,
+        );
+        let managed_dir = data_dir.path().join("projects/proj");
+        fs::create_dir_all(&managed_dir).unwrap();
+
+        let lp = setup_locations(config_dir.path(), data_dir.path());
+        create_missing_project_metadata(&lp).unwrap();
+        normalize_project_ids(&lp).unwrap();
+
+        let config = AppConfigManager::new(lp.get_config_file_path())
+            .unwrap()
+            .get_config()
+            .unwrap();
+        let metadata = ProjectMetadata::read(&managed_dir).unwrap().unwrap();
+        assert!(is_valid_id(&metadata.id));
+        assert_eq!(metadata.id, config.projects[0].id);
+        assert!(is_valid_id(&config.projects[1].id));
+    }
+
+    #[test]
+    fn invalid_metadata_of_one_project_does_not_stop_the_others() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        write_config(config_dir.path(), r#"{"projects":[]}"#);
+        let broken_dir = data_dir.path().join("projects/broken");
+        let new_dir = data_dir.path().join("projects/new");
+        fs::create_dir_all(&broken_dir).unwrap();
+        fs::create_dir_all(&new_dir).unwrap();
+        fs::write(broken_dir.join(".puff_project.json"), "{ not json").unwrap();
+
+        let lp = setup_locations(config_dir.path(), data_dir.path());
+        create_missing_project_metadata(&lp).unwrap();
+        normalize_project_ids(&lp).unwrap();
+
+        assert!(ProjectMetadata::read(&new_dir).unwrap().is_some());
+        assert_eq!(
+            "{ not json",
+            fs::read_to_string(broken_dir.join(".puff_project.json")).unwrap()
+        );
+    }
+
+    #[test]
+    fn registry_entry_takes_the_name_of_a_renamed_project() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let id = Uuid::new_v4().to_string();
+        write_config(
+            config_dir.path(),
+            &format!(r#"{{"projects":[{{"name":"old","id":"{id}","path":"/somewhere"}}]}}"#),
+        );
+        let managed_dir = data_dir.path().join("projects/new");
+        fs::create_dir_all(&managed_dir).unwrap();
+        ProjectMetadata::new(&id).write(&managed_dir).unwrap();
+
+        let lp = setup_locations(config_dir.path(), data_dir.path());
+        normalize_project_ids(&lp).unwrap();
+
+        let config = AppConfigManager::new(lp.get_config_file_path())
+            .unwrap()
+            .get_config()
+            .unwrap();
+        assert_eq!("new", config.projects[0].name);
+        assert_eq!(id, config.projects[0].id);
+    }
 }
@@ -0,0 +1,239 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::{path::PathBuf, time::SystemTime};
+
+use crate::config::projects::{LinkState, ManagedItem, ProjectDetails};
+
+/// The format in which commands print their results
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output
+    #[default]
+    Text,
+    /// Machine-readable JSON output
+    Json,
+}
+
+/// Serializable representation of a puff-managed project.
+#[derive(Serialize)]
+pub struct ProjectView {
+    pub name: String,
+    pub id: String,
+    pub description: Option<String>,
+    /// Unix time (in seconds) of the project's creation, if known
+    pub created_at: Option<u64>,
+    pub associated: bool,
+    pub path: Option<PathBuf>,
+    pub managed_dir: PathBuf,
+    pub items: Vec<ManagedItemView>,
+}
+
+/// Serializable representation of a single managed item.
+#[derive(Serialize)]
+pub struct ManagedItemView {
+    pub kind: ManagedItemKind,
+    pub path: PathBuf,
+    pub managed_path: PathBuf,
+    /// State of the item in the project's associated path. Absent for unassociated projects.
+    pub link_state: Option<LinkState>,
+    /// Unix time (in seconds) when the item was added, if known
+    pub added_at: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManagedItemKind {
+    File,
+    Directory,
+}
+
+impl From<&ProjectDetails> for ProjectView {
+    fn from(details: &ProjectDetails) -> Self {
+        let info = details.info();
+        let user_dir = match details {
+            ProjectDetails::Associated(associated) => Some(associated.user_dir.clone()),
+            ProjectDetails::Unassociated(_) => None,
+        };
+
+        let items = info
+            .items
+            .iter()
+            .map(|item| ManagedItemView {
+                kind: match item {
+                    ManagedItem::File(_) => ManagedItemKind::File,
+                    ManagedItem::Directory(_) => ManagedItemKind::Directory,
+                },
+                path: item.path().to_owned(),
+                managed_path: info.managed_dir.join(item.path()),
+                link_state: user_dir
+                    .as_deref()
+                    .map(|dir| item.link_state(dir, &info.managed_dir)),
+                added_at: info.metadata.item(item.path()).map(|i| i.added_at),
+            })
+            .collect();
+
+        ProjectView {
+            name: info.name.clone(),
+            id: info.metadata.id.clone(),
+            description: info.metadata.description.clone(),
+            created_at: info.metadata.created_at,
+            associated: user_dir.is_some(),
+            path: user_dir,
+            managed_dir: info.managed_dir.clone(),
+            items,
+        }
+    }
+}
+
+/// Formats a number of bytes with a binary unit, e.g. "1.5 KiB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Prints `rows` below `header`, with every column padded to its widest cell
+pub fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let header = header.map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(rows) {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        println!("{}", line.trim_end());
+    }
+}
+
+/// Formats a point in time as "YYYY-MM-DD HH:MM" (UTC)
+pub fn format_time(time: SystemTime) -> String {
+    let (year, month, day, secs_of_day) = civil_time(time);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60
+    )
+}
+
+/// Formats a point in time as "YYYYMMDD-HHMMSS" (UTC), which sorts chronologically and
+/// can be used in file names
+pub fn format_timestamp(time: SystemTime) -> String {
+    let (year, month, day, secs_of_day) = civil_time(time);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Splits a point in time into year, month, day and seconds of the day (UTC)
+fn civil_time(time: SystemTime) -> (i64, i64, i64, u64) {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil-from-days conversion (proleptic Gregorian calendar)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, secs_of_day)
+}
+
+/// Prints `value` to stdout as pretty-printed JSON
+pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProjectView, format_size, format_time, format_timestamp};
+    use crate::config::{
+        app_config::{AppConfig, Project},
+        locations::LocationsProvider,
+        projects::ProjectsRetriever,
+    };
+    use std::fs;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn format_size_uses_binary_units() {
+        assert_eq!("0 B", format_size(0));
+        assert_eq!("1023 B", format_size(1023));
+        assert_eq!("1.5 KiB", format_size(1536));
+        assert_eq!("2.0 MiB", format_size(2 * 1024 * 1024));
+    }
+
+    #[test]
+    fn format_time_prints_utc_date_and_time() {
+        assert_eq!("1970-01-01 00:00", format_time(SystemTime::UNIX_EPOCH));
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!("2024-02-29 12:34", format_time(time));
+        assert_eq!("20240229-123456", format_timestamp(time));
+    }
+
+    #[test]
+    fn project_view_serializes_items_with_kind_paths_and_link_state() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let user_dir = tempfile::tempdir().unwrap();
+        let managed_dir = data_dir.path().join("projects/proj");
+        fs::create_dir_all(&managed_dir).unwrap();
+        fs::write(managed_dir.join(".env"), "A=1").unwrap();
+
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let app_config = AppConfig {
+            projects: vec![Project {
+                name: String::from("proj"),
+                id: String::from("1"),
+                path: user_dir.path().to_path_buf(),
+            }],
+            ..Default::default()
+        };
+        let retriever = ProjectsRetriever::new(app_config, &locations_provider);
+        let details = retriever.get_details("proj").unwrap().unwrap();
+
+        let json = serde_json::to_value(ProjectView::from(&details)).unwrap();
+
+        assert_eq!("proj", json["name"]);
+        assert_eq!(true, json["associated"]);
+        assert_eq!("file", json["items"][0]["kind"]);
+        assert_eq!(".env", json["items"][0]["path"]);
+        assert_eq!(
+            managed_dir.join(".env").to_str().unwrap(),
+            json["items"][0]["managed_path"]
+        );
+        assert_eq!("missing", json["items"][0]["link_state"]);
+    }
+}
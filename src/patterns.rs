@@ -0,0 +1,241 @@
+use anyhow::Result;
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+/// Directories that glob expansion does not descend into unless asked to
+pub const DEFAULT_EXCLUDED_DIRS: [&str; 3] = [".git", "node_modules", "target"];
+
+/// Returns true if the text contains any glob metacharacters
+pub fn is_glob(text: &str) -> bool {
+    text.contains(['*', '?', '['])
+}
+
+/// A shell-style glob pattern matched against relative paths segment by segment.
+/// Supports `*`, `?`, character classes (`[abc]`, `[a-z]`, `[!abc]`) and `**`, which
+/// matches any number of directories. Wildcards match leading dots too.
+#[derive(Debug, Clone)]
+pub struct GlobPattern {
+    segments: Vec<String>,
+}
+
+impl GlobPattern {
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split(['/', std::path::MAIN_SEPARATOR])
+            .filter(|s| !s.is_empty() && *s != ".")
+            .map(String::from)
+            .collect();
+        GlobPattern { segments }
+    }
+
+    /// Checks whether the pattern matches the whole `path` (relative to the pattern's base)
+    pub fn matches(&self, path: &Path) -> bool {
+        let components = path_segments(path);
+        let components: Vec<&str> = components.iter().map(String::as_str).collect();
+        match_segments(&self.segments, &components)
+    }
+}
+
+/// Expands an absolute glob pattern into the existing paths it matches, in sorted order.
+/// Only files are matched unless the pattern ends with a separator, in which case only
+/// directories are. Symlinked directories and `excluded_dirs` are never descended into.
+pub fn expand(pattern: &str, excluded_dirs: &[&str]) -> Result<Vec<PathBuf>> {
+    let dirs_only = pattern.ends_with('/') || pattern.ends_with(std::path::MAIN_SEPARATOR);
+
+    // Split the pattern into a literal base directory and the part containing wildcards
+    let mut base = PathBuf::new();
+    let mut rest = vec![];
+    for component in Path::new(pattern).components() {
+        let text = component.as_os_str().to_string_lossy();
+        if rest.is_empty() && !is_glob(&text) {
+            base.push(component);
+        } else {
+            rest.push(text.into_owned());
+        }
+    }
+
+    if rest.is_empty() {
+        return Ok(if base.exists() { vec![base] } else { vec![] });
+    }
+
+    let glob = GlobPattern::new(&rest.join("/"));
+    let mut matches = vec![];
+    let mut stack = vec![base.clone()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries {
+            let path = entry?.path();
+            let relative = path.strip_prefix(&base)?;
+            let is_real_dir = path.is_dir() && !path.is_symlink();
+
+            if (path.is_dir() == dirs_only) && glob.matches(relative) {
+                matches.push(path.clone());
+                if dirs_only {
+                    continue;
+                }
+            }
+
+            let excluded = path
+                .file_name()
+                .map(|n| excluded_dirs.iter().any(|e| n == *e))
+                .unwrap_or(false);
+            if is_real_dir && !excluded {
+                stack.push(path);
+            }
+        }
+    }
+
+    matches.sort();
+    Ok(matches)
+}
+
+fn path_segments(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                let pattern: Vec<char> = first.chars().collect();
+                let text: Vec<char> = segment.chars().collect();
+                match_segment(&pattern, &text) && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|skip| match_segment(&pattern[1..], &text[skip..])),
+        Some('?') => !text.is_empty() && match_segment(&pattern[1..], &text[1..]),
+        Some('[') => match (text.first(), parse_class(pattern)) {
+            (Some(c), Some((matcher, len))) => {
+                matcher.matches(*c) && match_segment(&pattern[len..], &text[1..])
+            }
+            // An unterminated class is matched literally
+            (Some('['), None) => match_segment(&pattern[1..], &text[1..]),
+            _ => false,
+        },
+        Some('\\') if pattern.len() > 1 => {
+            text.first() == Some(&pattern[1]) && match_segment(&pattern[2..], &text[1..])
+        }
+        Some(c) => text.first() == Some(c) && match_segment(&pattern[1..], &text[1..]),
+    }
+}
+
+struct CharClass {
+    negated: bool,
+    ranges: Vec<(char, char)>,
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        self.ranges.iter().any(|(lo, hi)| *lo <= c && c <= *hi) != self.negated
+    }
+}
+
+/// Parses a `[...]` class at the start of `pattern`, returning it along with its length
+fn parse_class(pattern: &[char]) -> Option<(CharClass, usize)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = vec![];
+    let mut first = true;
+    while let Some(&c) = pattern.get(i) {
+        if c == ']' && !first {
+            return Some((CharClass { negated, ranges }, i + 1));
+        }
+        first = false;
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&e| e != ']') {
+            ranges.push((c, pattern[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        GlobPattern::new(pattern).matches(Path::new(path))
+    }
+
+    #[test]
+    fn star_matches_within_a_segment() {
+        assert!(matches("*.env", ".env"));
+        assert!(matches("*.env", "local.env"));
+        assert!(!matches("*.env", "config/local.env"));
+    }
+
+    #[test]
+    fn double_star_matches_any_number_of_directories() {
+        assert!(matches("**/.env", ".env"));
+        assert!(matches("**/.env", "apps/web/.env"));
+        assert!(matches("apps/**/*.json", "apps/a/b/c.json"));
+        assert!(!matches("apps/**/*.json", "libs/c.json"));
+    }
+
+    #[test]
+    fn question_mark_and_classes_match_single_characters() {
+        assert!(matches("file?.txt", "file1.txt"));
+        assert!(!matches("file?.txt", "file10.txt"));
+        assert!(matches("[abc].key", "b.key"));
+        assert!(matches("[a-c].key", "c.key"));
+        assert!(!matches("[!a-c].key", "a.key"));
+    }
+
+    #[test]
+    fn expand_finds_nested_files_and_skips_excluded_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("apps/web")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/pkg")).unwrap();
+        fs::write(dir.path().join(".env"), "").unwrap();
+        fs::write(dir.path().join("apps/web/.env"), "").unwrap();
+        fs::write(dir.path().join("node_modules/pkg/.env"), "").unwrap();
+
+        let pattern = format!("{}/**/.env", dir.path().display());
+        let result = expand(&pattern, &DEFAULT_EXCLUDED_DIRS).unwrap();
+
+        assert_eq!(
+            vec![dir.path().join(".env"), dir.path().join("apps/web/.env")],
+            result
+        );
+    }
+
+    #[test]
+    fn expand_with_trailing_separator_matches_only_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/secrets")).unwrap();
+        fs::write(dir.path().join("secrets"), "").unwrap();
+
+        let pattern = format!("{}/**/secrets/", dir.path().display());
+        let result = expand(&pattern, &[]).unwrap();
+
+        assert_eq!(vec![dir.path().join("a/secrets")], result);
+    }
+}
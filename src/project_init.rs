@@ -1 +1,3 @@
 pub mod existing;
+pub mod recover;
+pub mod scan;
@@ -1,52 +1,90 @@
 use crate::{
-    config::app_config::AppConfigManager,
-    fs_utils::{backup_dir, backup_file, symlink_dir, symlink_file},
+    config::{
+        app_config::{AppConfigManager, Project},
+        project_metadata::ProjectMetadata,
+    },
+    conflicts::{ConflictResolver, Resolution},
+    fs_utils::{symlink_dir, symlink_file},
     managed_dirs,
+    puff_ignore::PuffIgnore,
+    remotes,
 };
-use anyhow::{Result, anyhow, bail};
+use anyhow::{Result, bail};
 use std::{fs, path::Path};
 
 /// Initializes a project that already exists in puff's configs
 /// directory.
 pub struct ExistingProjectInitializer<'a> {
     app_config_manager: &'a AppConfigManager,
+    conflict_resolver: ConflictResolver,
 }
 
 impl<'a> ExistingProjectInitializer<'a> {
-    pub fn new(app_config_manager: &'a AppConfigManager) -> Self {
-        ExistingProjectInitializer { app_config_manager }
+    pub fn new(
+        app_config_manager: &'a AppConfigManager,
+        conflict_resolver: ConflictResolver,
+    ) -> Self {
+        ExistingProjectInitializer {
+            app_config_manager,
+            conflict_resolver,
+        }
     }
 
     /// It updates puff's config file by adding that new project there.
+    /// The project keeps the id stored in its metadata, so it's the same on all machines.
     pub fn init_project(&self, name: &str, user_dir: &Path, managed_dir: &Path) -> Result<()> {
         if !managed_dir.exists() {
             bail!("The project folder does not exist in puff's configs");
         }
 
-        self.app_config_manager.add_project(name, user_dir)?;
+        let id = ProjectMetadata::load(managed_dir)?.id;
+        self.init_registered_project(Project::with_id(name, &id, user_dir), managed_dir)
+    }
+
+    /// Same as `init_project`, but keeps the id of the given project (e.g. one read from
+    /// a .puff marker file)
+    pub fn init_registered_project(&self, project: Project, managed_dir: &Path) -> Result<()> {
+        if !managed_dir.exists() {
+            bail!("The project folder does not exist in puff's configs");
+        }
+
+        let user_dir = project.path.clone();
+        self.app_config_manager.register_project(project)?;
 
-        create_symlinks_for_managed_files(user_dir, managed_dir)?;
+        create_symlinks_for_managed_files(&user_dir, managed_dir, self.conflict_resolver)?;
+        remotes::record(managed_dir, &user_dir)?;
 
         Ok(())
     }
 }
 
 /// Creates symlinks in `target_dir` for all files in `managed_dir`,
-/// preserving directory structure.
-pub fn create_symlinks_for_managed_files(target_dir: &Path, managed_dir: &Path) -> Result<()> {
-    walk_managed_dir(target_dir, managed_dir, managed_dir)
+/// preserving directory structure. Files that already exist in `target_dir` are
+/// resolved with `conflict_resolver`.
+pub fn create_symlinks_for_managed_files(
+    target_dir: &Path,
+    managed_dir: &Path,
+    conflict_resolver: ConflictResolver,
+) -> Result<()> {
+    walk_managed_dir(target_dir, managed_dir, managed_dir, conflict_resolver)
 }
 
-fn walk_managed_dir(target_dir: &Path, managed_dir: &Path, current_dir: &Path) -> Result<()> {
+fn walk_managed_dir(
+    target_dir: &Path,
+    managed_dir: &Path,
+    current_dir: &Path,
+    conflict_resolver: ConflictResolver,
+) -> Result<()> {
     let managed_dir_set = managed_dirs::read_managed_dirs_set(managed_dir)?;
-    let managed_dirs_filename = managed_dirs::managed_dirs_filename();
+    let excludes = PuffIgnore::read(managed_dir)?;
 
     walk_managed_dir_inner(
         target_dir,
         managed_dir,
         current_dir,
         &managed_dir_set,
-        managed_dirs_filename,
+        &excludes,
+        conflict_resolver,
     )
 }
 
@@ -55,7 +93,8 @@ fn walk_managed_dir_inner(
     managed_dir: &Path,
     current_dir: &Path,
     managed_dir_set: &std::collections::HashSet<std::path::PathBuf>,
-    managed_dirs_filename: &str,
+    excludes: &PuffIgnore,
+    conflict_resolver: ConflictResolver,
 ) -> Result<()> {
     for entry in current_dir.read_dir()? {
         match entry {
@@ -63,30 +102,27 @@ fn walk_managed_dir_inner(
                 let path = entry.path();
                 let relative_path = path.strip_prefix(managed_dir)?;
 
-                if path.is_file()
-                    && path
-                        .file_name()
-                        .map(|n| n == managed_dirs_filename)
-                        .unwrap_or(false)
-                    && path.parent() == Some(managed_dir)
+                if managed_dirs::is_metadata_file(managed_dir, &path)
+                    || excludes.is_excluded(relative_path, path.is_dir())
                 {
                     continue;
                 }
 
                 if path.is_dir() {
                     if managed_dir_set.contains(&relative_path.to_path_buf()) {
-                        symlink_one_dir(&path, target_dir, relative_path)?;
+                        symlink_one_dir(managed_dir, target_dir, relative_path, conflict_resolver)?;
                     } else {
                         walk_managed_dir_inner(
                             target_dir,
                             managed_dir,
                             &path,
                             managed_dir_set,
-                            managed_dirs_filename,
+                            excludes,
+                            conflict_resolver,
                         )?;
                     }
                 } else {
-                    symlink_one_file(&path, target_dir, relative_path)?;
+                    symlink_one_file(managed_dir, target_dir, relative_path, conflict_resolver)?;
                 }
             }
             Err(_err) => {
@@ -99,7 +135,13 @@ fn walk_managed_dir_inner(
     Ok(())
 }
 
-fn symlink_one_dir(managed_path: &Path, target_dir: &Path, relative_path: &Path) -> Result<()> {
+fn symlink_one_dir(
+    managed_dir: &Path,
+    target_dir: &Path,
+    relative_path: &Path,
+    conflict_resolver: ConflictResolver,
+) -> Result<()> {
+    let managed_path = managed_dir.join(relative_path);
     let dir_in_target = target_dir.join(relative_path);
     fs::create_dir_all(dir_in_target.parent().unwrap())?;
 
@@ -109,42 +151,24 @@ fn symlink_one_dir(managed_path: &Path, target_dir: &Path, relative_path: &Path)
         return Ok(());
     }
 
-    if dir_in_target.exists() || dir_in_target.symlink_metadata().is_ok() {
-        if dir_in_target.is_dir() && !dir_in_target.is_symlink() {
-            let backup = backup_dir(&dir_in_target)?;
-            fs::remove_dir_all(&dir_in_target)?;
-            println!(
-                "Conflict: {:?} exists as a real directory. \
-                A backup was created at {}. \
-                It now points to the puff-managed version.",
-                relative_path
-                    .file_name()
-                    .unwrap_or(relative_path.as_os_str()),
-                backup.unwrap(),
-            );
-        } else {
-            let backup = backup_file(&dir_in_target)?;
-            fs::remove_file(&dir_in_target)?;
-            println!(
-                "Conflict: {:?} exists in both the project directory and puff's registry. \
-                A backup of the original was created at {}.",
-                relative_path
-                    .file_name()
-                    .unwrap_or(relative_path.as_os_str()),
-                backup.unwrap(),
-            );
-        }
+    if dir_in_target.symlink_metadata().is_ok()
+        && conflict_resolver.resolve(&dir_in_target, managed_dir, relative_path)?
+            == Resolution::Skip
+    {
+        return Ok(());
     }
 
-    symlink_dir(managed_path, &dir_in_target)?;
+    symlink_dir(&managed_path, &dir_in_target)?;
     Ok(())
 }
 
-fn symlink_one_file(managed_file: &Path, target_dir: &Path, relative_path: &Path) -> Result<()> {
-    let managed_file_name = managed_file
-        .file_name()
-        .ok_or_else(|| anyhow!("Existing file {:?} could not be read", managed_file))?;
-
+fn symlink_one_file(
+    managed_dir: &Path,
+    target_dir: &Path,
+    relative_path: &Path,
+    conflict_resolver: ConflictResolver,
+) -> Result<()> {
+    let managed_file = managed_dir.join(relative_path);
     let file_in_target_dir = target_dir.join(relative_path);
     fs::create_dir_all(file_in_target_dir.parent().unwrap())?;
 
@@ -154,21 +178,14 @@ fn symlink_one_file(managed_file: &Path, target_dir: &Path, relative_path: &Path
         return Ok(());
     }
 
-    if file_in_target_dir.exists() || file_in_target_dir.symlink_metadata().is_ok() {
-        let backup = backup_file(&file_in_target_dir)?;
-        fs::remove_file(&file_in_target_dir)?;
-        println!(
-            "Conflict: {:?} exists in both the project directory and puff's registry. \
-            A backup of the original file was created at {}. \
-            {:?} now points to the puff-managed version. \
-            Review the backup before committing.",
-            managed_file_name,
-            backup.unwrap(),
-            file_in_target_dir.file_name().unwrap()
-        );
+    if file_in_target_dir.symlink_metadata().is_ok()
+        && conflict_resolver.resolve(&file_in_target_dir, managed_dir, relative_path)?
+            == Resolution::Skip
+    {
+        return Ok(());
     }
 
-    symlink_file(managed_file, &file_in_target_dir)?;
+    symlink_file(&managed_file, &file_in_target_dir)?;
 
     Ok(())
 }
@@ -177,6 +194,7 @@ fn symlink_one_file(managed_file: &Path, target_dir: &Path, relative_path: &Path
 mod tests {
     use super::ExistingProjectInitializer;
     use crate::config::app_config::{AppConfig, AppConfigManager};
+    use crate::conflicts::{ConflictResolver, OnConflict};
     use std::fs::{self, File};
     use std::io::{BufReader, Write};
     use std::path::Path;
@@ -189,7 +207,10 @@ mod tests {
         write!(file, "{{\"projects\":[]}}").unwrap();
         // let (config_file, _) = prepare_sut_and_stuff();
         let config_manager = AppConfigManager::new(config_file.clone()).unwrap();
-        let sut = ExistingProjectInitializer::new(&config_manager);
+        let sut = ExistingProjectInitializer::new(
+            &config_manager,
+            ConflictResolver::new(None, OnConflict::Backup),
+        );
 
         let project_name = "some-project";
         let user_dir = tempfile::tempdir().unwrap();
@@ -214,7 +235,10 @@ mod tests {
         write!(file, "{{\"projects\":[]}}").unwrap();
         // let (config_file, _) = prepare_sut_and_stuff();
         let config_manager = AppConfigManager::new(config_file.clone()).unwrap();
-        let sut = ExistingProjectInitializer::new(&config_manager);
+        let sut = ExistingProjectInitializer::new(
+            &config_manager,
+            ConflictResolver::new(None, OnConflict::Backup),
+        );
 
         let project_name = "some-project";
         let user_dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,103 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::puff_links::PuffLink;
+
+/// A project root inferred from the links in it
+#[derive(Debug, PartialEq)]
+pub struct RecoveredProject {
+    pub name: String,
+    pub path: PathBuf,
+    /// Number of links that point to the project's data
+    pub links: usize,
+}
+
+/// Projects whose links imply several roots that can't be told apart
+#[derive(Debug, PartialEq)]
+pub struct AmbiguousRecovery {
+    pub name: String,
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct RecoveryResult {
+    pub projects: Vec<RecoveredProject>,
+    pub ambiguous: Vec<AmbiguousRecovery>,
+}
+
+/// Infers the root of every project from its links. When the links of a project imply
+/// several roots (e.g. the main checkout and its worktrees), the one with the most links
+/// wins; a tie is reported as ambiguous.
+pub fn infer_projects(links: &[PuffLink]) -> RecoveryResult {
+    let mut roots: BTreeMap<&str, BTreeMap<PathBuf, usize>> = BTreeMap::new();
+    for link in links {
+        if let Some(root) = link.project_root() {
+            *roots
+                .entry(link.project.as_str())
+                .or_default()
+                .entry(root)
+                .or_default() += 1;
+        }
+    }
+
+    let mut result = RecoveryResult::default();
+    for (name, counts) in roots {
+        let most = counts.values().copied().max().unwrap_or_default();
+        let best: Vec<PathBuf> = counts
+            .into_iter()
+            .filter(|(_, count)| *count == most)
+            .map(|(path, _)| path)
+            .collect();
+        if let [path] = best.as_slice() {
+            result.projects.push(RecoveredProject {
+                name: name.to_owned(),
+                path: path.clone(),
+                links: most,
+            });
+        } else {
+            result.ambiguous.push(AmbiguousRecovery {
+                name: name.to_owned(),
+                paths: best,
+            });
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecoveredProject, infer_projects};
+    use crate::puff_links::PuffLink;
+    use std::path::PathBuf;
+
+    fn link(path: &str, project: &str, item: &str) -> PuffLink {
+        PuffLink {
+            path: PathBuf::from(path),
+            project: project.to_owned(),
+            item: PathBuf::from(item),
+        }
+    }
+
+    #[test]
+    fn infer_projects_prefers_root_with_most_links() {
+        let links = vec![
+            link("/code/api/.env", "api", ".env"),
+            link("/code/api/config/db.env", "api", "config/db.env"),
+            link("/code/api-wt/.env", "api", ".env"),
+            link("/code/web/.env", "web", ".env"),
+            link("/code/web-wt/.env", "web", ".env"),
+        ];
+
+        let result = infer_projects(&links);
+
+        assert_eq!(
+            vec![RecoveredProject {
+                name: "api".to_owned(),
+                path: PathBuf::from("/code/api"),
+                links: 2,
+            }],
+            result.projects
+        );
+        assert_eq!(1, result.ambiguous.len());
+        assert_eq!("web", result.ambiguous[0].name);
+    }
+}
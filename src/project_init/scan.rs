@@ -0,0 +1,249 @@
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{git::GitRepository, marker::ProjectMarker, patterns::DEFAULT_EXCLUDED_DIRS, remotes};
+
+/// A directory found by `puff init --scan` that could be the checkout of a project
+pub struct CandidateDir {
+    pub path: PathBuf,
+    /// Project name from the directory's .puff marker file
+    pub marker: Option<String>,
+    /// Normalized remote URLs, for roots of git repositories
+    pub remotes: Vec<String>,
+}
+
+/// How an unassociated project was matched with a directory, from the most to the least reliable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchReason {
+    Marker,
+    GitRemote,
+    DirectoryName,
+}
+
+impl Display for MatchReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchReason::Marker => write!(f, ".puff marker"),
+            MatchReason::GitRemote => write!(f, "git remote"),
+            MatchReason::DirectoryName => write!(f, "directory name"),
+        }
+    }
+}
+
+pub struct ProjectMatch {
+    pub name: String,
+    pub path: PathBuf,
+    pub reason: MatchReason,
+}
+
+/// Projects that matched more than one directory equally well
+pub struct AmbiguousMatch {
+    pub name: String,
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+pub struct ScanResult {
+    pub matches: Vec<ProjectMatch>,
+    pub ambiguous: Vec<AmbiguousMatch>,
+}
+
+/// Walks `root` and returns the roots of git repositories along with all directories
+/// containing a .puff marker file. `excluded` directories (already associated projects)
+/// are skipped, but still searched for nested checkouts.
+pub fn find_candidate_dirs(root: &Path, excluded: &[PathBuf]) -> Result<Vec<CandidateDir>> {
+    let mut candidates = vec![];
+    let mut stack = vec![root.to_owned()];
+    while let Some(current) = stack.pop() {
+        let marker = ProjectMarker::read(&current).unwrap_or(None);
+        let is_repository = current.join(".git").exists();
+        if (marker.is_some() || is_repository) && !excluded.contains(&current) {
+            let remotes = match GitRepository::discover(&current)? {
+                Some(repository) if is_repository => repository
+                    .remote_urls()
+                    .iter()
+                    .map(|u| remotes::normalize_url(u))
+                    .collect(),
+                _ => vec![],
+            };
+            candidates.push(CandidateDir {
+                path: current.clone(),
+                marker: marker.map(|m| m.name),
+                remotes,
+            });
+        }
+
+        // Unreadable directories are not worth failing the whole scan for
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            let excluded = DEFAULT_EXCLUDED_DIRS
+                .iter()
+                .any(|e| entry.file_name() == *e);
+            if is_dir && !excluded {
+                stack.push(entry.path());
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(candidates)
+}
+
+/// Matches unassociated projects (name and recorded remote URLs) with candidate directories.
+/// Each project gets the directories matched by the most reliable reason; it's reported as
+/// ambiguous when there are several of them. A directory is never given to two projects.
+pub fn match_projects(
+    projects: &[(String, Vec<String>)],
+    candidates: &[CandidateDir],
+) -> ScanResult {
+    let mut result = ScanResult::default();
+    let mut claimed: HashMap<&Path, usize> = HashMap::new();
+
+    for (name, recorded_remotes) in projects {
+        let recorded: Vec<String> = recorded_remotes
+            .iter()
+            .map(|u| remotes::normalize_url(u))
+            .collect();
+
+        let mut best: Option<(MatchReason, Vec<&Path>)> = None;
+        for candidate in candidates {
+            let reason = if candidate.marker.as_ref() == Some(name) {
+                MatchReason::Marker
+            } else if candidate.marker.is_none()
+                && candidate.remotes.iter().any(|r| recorded.contains(r))
+            {
+                MatchReason::GitRemote
+            } else if candidate.marker.is_none()
+                && candidate
+                    .path
+                    .file_name()
+                    .is_some_and(|n| n == name.as_str())
+            {
+                MatchReason::DirectoryName
+            } else {
+                continue;
+            };
+
+            match &mut best {
+                Some((best_reason, paths)) if *best_reason == reason => paths.push(&candidate.path),
+                Some((best_reason, _)) if *best_reason < reason => {}
+                _ => best = Some((reason, vec![&candidate.path])),
+            }
+        }
+
+        match best {
+            None => {}
+            Some((reason, paths)) if paths.len() == 1 => {
+                *claimed.entry(paths[0]).or_default() += 1;
+                result.matches.push(ProjectMatch {
+                    name: name.clone(),
+                    path: paths[0].to_owned(),
+                    reason,
+                });
+            }
+            Some((_, paths)) => result.ambiguous.push(AmbiguousMatch {
+                name: name.clone(),
+                paths: paths.into_iter().map(Path::to_owned).collect(),
+            }),
+        }
+    }
+
+    // Directories matched by several projects (e.g. a monorepo's remote) can't be decided either
+    let (matches, conflicting): (Vec<_>, Vec<_>) = result
+        .matches
+        .into_iter()
+        .partition(|m| claimed[m.path.as_path()] == 1);
+    result.matches = matches;
+    result
+        .ambiguous
+        .extend(conflicting.into_iter().map(|m| AmbiguousMatch {
+            name: m.name,
+            paths: vec![m.path],
+        }));
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CandidateDir, MatchReason, match_projects};
+    use std::path::PathBuf;
+
+    fn candidate(path: &str, marker: Option<&str>, remotes: &[&str]) -> CandidateDir {
+        CandidateDir {
+            path: PathBuf::from(path),
+            marker: marker.map(str::to_owned),
+            remotes: remotes.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn match_projects_prefers_marker_over_remote_and_name() {
+        let projects = vec![(
+            "api".to_owned(),
+            vec!["git@github.com:org/api.git".to_owned()],
+        )];
+        let candidates = vec![
+            candidate("/code/api", None, &["github.com/org/api"]),
+            candidate("/code/api-copy", Some("api"), &[]),
+        ];
+
+        let result = match_projects(&projects, &candidates);
+
+        assert_eq!(1, result.matches.len());
+        assert_eq!(PathBuf::from("/code/api-copy"), result.matches[0].path);
+        assert_eq!(MatchReason::Marker, result.matches[0].reason);
+    }
+
+    #[test]
+    fn match_projects_matches_remote_and_directory_name() {
+        let projects = vec![
+            (
+                "backend".to_owned(),
+                vec!["https://github.com/org/api".to_owned()],
+            ),
+            ("web".to_owned(), vec![]),
+        ];
+        let candidates = vec![
+            candidate("/code/api", None, &["github.com/org/api"]),
+            candidate("/code/web", None, &["github.com/org/frontend"]),
+        ];
+
+        let result = match_projects(&projects, &candidates);
+
+        assert_eq!(2, result.matches.len());
+        assert_eq!(PathBuf::from("/code/api"), result.matches[0].path);
+        assert_eq!(MatchReason::GitRemote, result.matches[0].reason);
+        assert_eq!(PathBuf::from("/code/web"), result.matches[1].path);
+        assert_eq!(MatchReason::DirectoryName, result.matches[1].reason);
+    }
+
+    #[test]
+    fn match_projects_reports_ambiguous_matches() {
+        let projects = vec![
+            ("web".to_owned(), vec![]),
+            ("a".to_owned(), vec!["github.com/org/mono".to_owned()]),
+            ("b".to_owned(), vec!["github.com/org/mono".to_owned()]),
+        ];
+        let candidates = vec![
+            candidate("/code/mono", None, &["github.com/org/mono"]),
+            candidate("/code/old/web", None, &[]),
+            candidate("/code/web", None, &[]),
+        ];
+
+        let result = match_projects(&projects, &candidates);
+
+        assert!(result.matches.is_empty());
+        let mut names: Vec<&str> = result.ambiguous.iter().map(|a| a.name.as_str()).collect();
+        names.sort();
+        assert_eq!(vec!["a", "b", "web"], names);
+    }
+}
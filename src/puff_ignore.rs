@@ -0,0 +1,90 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+
+use crate::patterns::GlobPattern;
+
+/// Name of the file with exclude patterns. In a managed directory, it applies to the
+/// directory's content; in the root of a project's managed dir, to the whole project.
+pub(crate) const PUFF_IGNORE_FILE: &str = ".puffignore";
+
+/// A pattern of a `.puffignore` file
+struct ExcludePattern {
+    glob: GlobPattern,
+    /// Patterns without a `/` match the name of an entry at any depth
+    name_only: bool,
+    /// Patterns with a trailing `/` match directories only
+    dirs_only: bool,
+}
+
+/// Exclude patterns of a directory, read from its `.puffignore`. One glob pattern per
+/// line, relative to the directory; empty lines and lines starting with `#` are skipped.
+#[derive(Default)]
+pub struct PuffIgnore {
+    patterns: Vec<ExcludePattern>,
+}
+
+impl PuffIgnore {
+    /// Reads the patterns of `dir`. A missing `.puffignore` excludes nothing.
+    pub fn read(dir: &Path) -> Result<Self> {
+        let path = dir.join(PUFF_IGNORE_FILE);
+        if !path.is_file() {
+            return Ok(PuffIgnore::default());
+        }
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|line| {
+                let dirs_only = line.ends_with('/');
+                let pattern = line.trim_end_matches('/');
+                ExcludePattern {
+                    glob: GlobPattern::new(pattern),
+                    name_only: !pattern.contains('/'),
+                    dirs_only,
+                }
+            })
+            .collect();
+        PuffIgnore { patterns }
+    }
+
+    /// Checks whether the entry at `relative_path` (relative to the directory of the
+    /// `.puffignore`) is excluded. The `.puffignore` itself never is.
+    pub fn is_excluded(&self, relative_path: &Path, is_dir: bool) -> bool {
+        if relative_path == Path::new(PUFF_IGNORE_FILE) {
+            return false;
+        }
+        self.patterns.iter().any(|p| {
+            if p.dirs_only && !is_dir {
+                return false;
+            }
+            match (p.name_only, relative_path.file_name()) {
+                (true, Some(name)) => p.glob.matches(Path::new(name)),
+                _ => p.glob.matches(relative_path),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PuffIgnore;
+    use std::path::Path;
+
+    #[test]
+    fn is_excluded_matches_names_at_any_depth_and_anchors_paths() {
+        let excludes = PuffIgnore::parse("# generated\n*.cache\n\ntmp/\nlogs/*.log\n");
+
+        assert!(excludes.is_excluded(Path::new("a.cache"), false));
+        assert!(excludes.is_excluded(Path::new("nested/b.cache"), false));
+        assert!(excludes.is_excluded(Path::new("nested/tmp"), true));
+        assert!(!excludes.is_excluded(Path::new("tmp"), false));
+        assert!(excludes.is_excluded(Path::new("logs/app.log"), false));
+        assert!(!excludes.is_excluded(Path::new("nested/logs/app.log"), false));
+        assert!(!excludes.is_excluded(Path::new(".puffignore"), false));
+        assert!(!excludes.is_excluded(Path::new("db.env"), false));
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::Result;
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{managed_dirs, patterns::DEFAULT_EXCLUDED_DIRS};
+
+/// A symlink pointing into puff's data directory, found in a project directory
+#[derive(Debug, PartialEq)]
+pub struct PuffLink {
+    /// Location of the symlink
+    pub path: PathBuf,
+    /// Name of the project the target belongs to
+    pub project: String,
+    /// Path of the target relative to the project's managed dir
+    pub item: PathBuf,
+}
+
+impl PuffLink {
+    /// The project root the link implies: its location without the item's relative path.
+    /// `None` when the link's location doesn't end with that path (a link created by hand).
+    pub fn project_root(&self) -> Option<PathBuf> {
+        let depth = self.item.components().count();
+        let root = self.path.ancestors().nth(depth)?;
+        (root.join(&self.item) == self.path).then(|| root.to_owned())
+    }
+
+    /// Checks what the link's target is in the data directory at `projects_path`
+    pub fn classify(&self, projects_path: &Path) -> Result<LinkTarget> {
+        let managed_dir = projects_path.join(&self.project);
+        if !managed_dir.is_dir() {
+            return Ok(LinkTarget::RemovedProject);
+        }
+        let target = managed_dir.join(&self.item);
+        if target.symlink_metadata().is_err() {
+            return Ok(LinkTarget::ForgottenItem);
+        }
+        if managed_dirs::is_metadata_file(&managed_dir, &target) {
+            return Ok(LinkTarget::Unknown);
+        }
+
+        let managed = if target.is_dir() {
+            managed_dirs::read_managed_dirs_set(&managed_dir)?.contains(&self.item)
+        } else {
+            managed_dirs::is_inside_managed_dir(&managed_dir, &self.item)?.is_none()
+        };
+        Ok(if managed {
+            LinkTarget::Managed
+        } else {
+            LinkTarget::Unknown
+        })
+    }
+}
+
+/// What a symlink into the data directory points to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkTarget {
+    /// A managed file or directory
+    Managed,
+    /// Nothing, the project was removed from puff
+    RemovedProject,
+    /// Nothing, the project no longer manages the item
+    ForgottenItem,
+    /// Something in the data directory that isn't a managed item (e.g. a file inside a
+    /// managed directory, or puff's bookkeeping files)
+    Unknown,
+}
+
+/// Walks `root` and returns all symlinks whose targets are inside `projects_path`.
+/// Symlinked directories are not followed.
+pub fn find_puff_links(root: &Path, projects_path: &Path) -> Result<Vec<PuffLink>> {
+    let canonical_projects_path = fs::canonicalize(projects_path)?;
+
+    let mut links = vec![];
+    let mut stack = vec![root.to_owned()];
+    while let Some(current) = stack.pop() {
+        // Unreadable directories are not worth failing the whole scan for
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_symlink() {
+                let Ok(target) = fs::read_link(&path) else {
+                    continue;
+                };
+                let target = current.join(target);
+                let relative = target
+                    .strip_prefix(projects_path)
+                    .or_else(|_| target.strip_prefix(&canonical_projects_path));
+                if let Ok(relative) = relative
+                    && let Some(link) = to_puff_link(&path, relative)
+                {
+                    links.push(link);
+                }
+            } else if file_type.is_dir()
+                && path != projects_path
+                && !DEFAULT_EXCLUDED_DIRS
+                    .iter()
+                    .any(|e| entry.file_name() == *e)
+            {
+                stack.push(path);
+            }
+        }
+    }
+
+    links.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(links)
+}
+
+/// Splits a target path relative to the data directory into the project name and the
+/// item's path
+fn to_puff_link(path: &Path, relative: &Path) -> Option<PuffLink> {
+    let mut components = relative.components();
+    let Some(Component::Normal(project)) = components.next() else {
+        return None;
+    };
+    let item = components.as_path();
+    if item.as_os_str().is_empty() {
+        return None;
+    }
+    Some(PuffLink {
+        path: path.to_owned(),
+        project: project.to_str()?.to_owned(),
+        item: item.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LinkTarget, PuffLink};
+    use std::{fs, path::PathBuf};
+
+    fn link(path: &str, project: &str, item: &str) -> PuffLink {
+        PuffLink {
+            path: PathBuf::from(path),
+            project: project.to_owned(),
+            item: PathBuf::from(item),
+        }
+    }
+
+    #[test]
+    fn project_root_strips_item_path_from_link_location() {
+        assert_eq!(
+            Some(PathBuf::from("/code/api")),
+            link("/code/api/config/.env", "api", "config/.env").project_root()
+        );
+        assert_eq!(
+            None,
+            link("/code/api/.env", "api", "config/.env").project_root()
+        );
+    }
+
+    #[test]
+    fn classify_tells_managed_items_from_removed_ones() {
+        let projects = tempfile::tempdir().unwrap();
+        let managed_dir = projects.path().join("app");
+        fs::create_dir_all(managed_dir.join("config")).unwrap();
+        fs::write(managed_dir.join(".env"), "A=1").unwrap();
+        fs::write(managed_dir.join("config/db.env"), "B=1").unwrap();
+        fs::write(managed_dir.join(".puff_managed_dirs"), "config\n").unwrap();
+
+        let classify = |project: &str, item: &str| {
+            link("/code/app/x", project, item)
+                .classify(projects.path())
+                .unwrap()
+        };
+
+        assert_eq!(LinkTarget::Managed, classify("app", ".env"));
+        assert_eq!(LinkTarget::Managed, classify("app", "config"));
+        assert_eq!(LinkTarget::Unknown, classify("app", "config/db.env"));
+        assert_eq!(LinkTarget::ForgottenItem, classify("app", ".env.local"));
+        assert_eq!(LinkTarget::RemovedProject, classify("gone", ".env"));
+    }
+}
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use std::{fs, path::Path};
+
+use crate::git::GitRepository;
+
+/// File in the managed dir listing the git remote URLs of the project's repository,
+/// one per line. It travels with the data directory, so checkouts on other machines
+/// can be matched to the project (`puff init --scan`).
+pub(crate) const GIT_REMOTES_FILE: &str = ".puff_git_remotes";
+
+/// Returns the recorded remote URLs of a project
+pub fn read(managed_dir: &Path) -> Result<Vec<String>> {
+    let path = managed_dir.join(GIT_REMOTES_FILE);
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Adds the remote URLs of the repository containing `project_root` to the recorded ones.
+/// URLs recorded earlier (e.g. on another machine) are kept.
+pub fn record(managed_dir: &Path, project_root: &Path) -> Result<()> {
+    let Some(repository) = GitRepository::discover(project_root)? else {
+        return Ok(());
+    };
+
+    let mut urls = read(managed_dir)?;
+    let count = urls.len();
+    for url in repository.remote_urls() {
+        if !urls.iter().any(|u| normalize_url(u) == normalize_url(&url)) {
+            urls.push(url);
+        }
+    }
+    if urls.len() == count {
+        return Ok(());
+    }
+
+    let content: String = urls.iter().map(|u| format!("{u}\n")).collect();
+    fs::write(managed_dir.join(GIT_REMOTES_FILE), content)?;
+    Ok(())
+}
+
+/// Reduces a remote URL to "host/path", so that the SSH and HTTPS URLs of a repository
+/// are equal: `git@github.com:org/repo.git` and `https://github.com/org/repo` both
+/// become `github.com/org/repo`
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let (without_scheme, has_scheme) = match url.split_once("://") {
+        Some((_, rest)) => (rest, true),
+        None => (url, false),
+    };
+    let without_user = match without_scheme.split_once('@') {
+        Some((_, rest)) if !rest.contains('@') => rest,
+        _ => without_scheme,
+    };
+    // scp-like syntax separates the host with a colon
+    let (host, path) = match without_user.split_once(if has_scheme { '/' } else { ':' }) {
+        Some((host, path)) => (host, path),
+        None => {
+            let path = without_user.trim_end_matches('/');
+            return path.strip_suffix(".git").unwrap_or(path).to_owned();
+        }
+    };
+    // A port is irrelevant for identifying the repository
+    let host = host.split(':').next().unwrap_or(host);
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    format!("{}/{}", host.to_lowercase(), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_url;
+
+    #[test]
+    fn normalize_url_makes_ssh_and_https_urls_equal() {
+        let expected = "github.com/org/repo";
+
+        assert_eq!(expected, normalize_url("git@github.com:org/repo.git"));
+        assert_eq!(expected, normalize_url("https://github.com/org/repo"));
+        assert_eq!(
+            expected,
+            normalize_url("https://user@GitHub.com/org/repo.git/")
+        );
+        assert_eq!(
+            expected,
+            normalize_url("ssh://git@github.com:22/org/repo.git")
+        );
+    }
+
+    #[test]
+    fn normalize_url_keeps_local_paths() {
+        assert_eq!("/srv/git/repo", normalize_url("/srv/git/repo.git"));
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{Result, bail};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{backups::BackupStore, config::locations::LocationsProvider, trash::Trash};
+
+/// Size of the buffer that file contents get overwritten with
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Files shredded by an operation and the ones that could not be shredded securely
+#[derive(Default)]
+pub struct ShredReport {
+    pub shredded: usize,
+    pub insecure: Vec<(PathBuf, String)>,
+}
+
+impl ShredReport {
+    /// Prints the report and fails if anything could not be shredded securely
+    pub fn finish(self) -> Result<()> {
+        println!("Shredded {} file(s).", self.shredded);
+        for (path, reason) in &self.insecure {
+            eprintln!(
+                "Warning: {} was not shredded securely: {reason}",
+                path.display()
+            );
+        }
+        if !self.insecure.is_empty() {
+            bail!(
+                "{} file(s) could not be shredded securely.",
+                self.insecure.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Overwrites the content of the file at `path` (or of every file in the directory at
+/// `path`) with zeros, flushes it to disk and removes it. Copy-on-write and journaling file
+/// systems, as well as SSDs, may still keep the old content elsewhere.
+pub fn shred_path(path: &Path, report: &mut ShredReport) -> Result<()> {
+    let Ok(metadata) = path.symlink_metadata() else {
+        return Ok(());
+    };
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path)? {
+            shred_path(&entry?.path(), report)?;
+        }
+        fs::remove_dir(path)?;
+        return Ok(());
+    }
+
+    if metadata.is_file() {
+        match overwrite(path, metadata.len()) {
+            Ok(()) => {
+                report.shredded += 1;
+                if has_other_links(&metadata) {
+                    report.insecure.push((
+                        path.to_path_buf(),
+                        "other hard links to the file remain".to_owned(),
+                    ));
+                }
+            }
+            Err(e) => report.insecure.push((path.to_path_buf(), e.to_string())),
+        }
+    }
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+fn overwrite(path: &Path, len: u64) -> std::io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let zeros = vec![0u8; CHUNK_SIZE];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(CHUNK_SIZE as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    file.set_len(0)?;
+    file.sync_all()
+}
+
+#[cfg(unix)]
+fn has_other_links(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() > 1
+}
+
+#[cfg(not(unix))]
+fn has_other_links(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Shreds the copies that puff keeps of `item` of the project with `project_id` (of all of
+/// the project's items without one) in its backups and in the trash
+pub fn shred_copies(
+    locations_provider: &LocationsProvider,
+    project_id: &str,
+    item: Option<&Path>,
+    report: &mut ShredReport,
+) -> Result<()> {
+    // Path of the item's copy within a copy of `path`, if `path` contains the item
+    let copy_in = |path: Option<&Path>| -> Option<PathBuf> {
+        match (item, path) {
+            (Some(item), Some(path)) => item.strip_prefix(path).ok().map(Path::to_path_buf),
+            // A copy of the whole project
+            (Some(item), None) => Some(item.to_path_buf()),
+            (None, _) => None,
+        }
+    };
+    let is_whole_copy = |path: Option<&Path>| match (item, path) {
+        (None, _) => true,
+        (Some(item), Some(path)) => path.starts_with(item),
+        (Some(_), None) => false,
+    };
+
+    let store = BackupStore::new(&locations_provider.get_backups_path(), project_id);
+    for backup in store.list()? {
+        if is_whole_copy(Some(&backup.path)) {
+            shred_path(&store.stored_path(&backup), report)?;
+        } else if let Some(inner) = copy_in(Some(&backup.path)) {
+            shred_path(&store.stored_path(&backup).join(inner), report)?;
+        }
+    }
+    store.remove(|b| is_whole_copy(Some(&b.path)))?;
+
+    let trash = Trash::new(locations_provider.get_trash_path());
+    let in_project = trash
+        .list()?
+        .into_iter()
+        .filter(|e| e.project_id == project_id);
+    for entry in in_project {
+        if is_whole_copy(entry.item.as_deref()) {
+            shred_path(&trash.stored_path(&entry), report)?;
+        } else if let Some(inner) = copy_in(entry.item.as_deref()) {
+            shred_path(&trash.stored_path(&entry).join(inner), report)?;
+        }
+    }
+    trash.empty(|e| e.project_id == project_id && is_whole_copy(e.item.as_deref()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ShredReport, shred_copies, shred_path};
+    use crate::{backups::BackupStore, config::locations::LocationsProvider};
+    use std::{fs, path::Path};
+
+    #[test]
+    fn shred_path_overwrites_and_removes_files_of_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let secrets = dir.path().join("secrets");
+        fs::create_dir_all(secrets.join("nested")).unwrap();
+        fs::write(secrets.join("a.env"), "A=1").unwrap();
+        fs::write(secrets.join("nested/b.key"), "KEY").unwrap();
+        let mut report = ShredReport::default();
+
+        shred_path(&secrets, &mut report).unwrap();
+
+        assert!(!secrets.exists());
+        assert_eq!(2, report.shredded);
+        assert!(report.insecure.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn shred_path_reports_files_with_other_hard_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".env");
+        fs::write(&file, "A=1").unwrap();
+        fs::hard_link(&file, dir.path().join("copy")).unwrap();
+        let mut report = ShredReport::default();
+
+        shred_path(&file, &mut report).unwrap();
+
+        assert_eq!(1, report.insecure.len());
+        assert_eq!("", fs::read_to_string(dir.path().join("copy")).unwrap());
+    }
+
+    #[test]
+    fn shred_copies_removes_backups_of_the_item_only() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let data_dir = tempfile::tempdir().unwrap();
+        let locations_provider = LocationsProvider::new(
+            config_dir.path().to_path_buf(),
+            data_dir.path().to_path_buf(),
+        );
+        let original = data_dir.path().join("original");
+        fs::write(&original, "A=1").unwrap();
+        let store = BackupStore::new(&locations_provider.get_backups_path(), "app");
+        store.backup(&original, Path::new(".env")).unwrap();
+        let kept = store.backup(&original, Path::new(".secrets")).unwrap();
+        let mut report = ShredReport::default();
+
+        shred_copies(
+            &locations_provider,
+            "app",
+            Some(Path::new(".env")),
+            &mut report,
+        )
+        .unwrap();
+
+        assert_eq!(1, report.shredded);
+        assert_eq!(vec![kept], store.list().unwrap());
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{fs_utils::move_path, output::format_timestamp};
+
+/// Name of the directory in puff's data directory that holds deleted items
+pub(crate) const TRASH_DIR: &str = "trash";
+
+/// Name of the index of the trash
+const TRASH_INDEX_FILE: &str = "index.json";
+
+/// A managed item or a whole project that got deleted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrashEntry {
+    /// Name of the timestamped directory that holds the deleted content
+    pub id: String,
+    /// Name of the project the content belonged to
+    pub project: String,
+    /// Id of the project, which identifies it even after a rename
+    pub project_id: String,
+    /// Path of the deleted item, relative to the project's root. Not set when the whole
+    /// project was deleted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<PathBuf>,
+    /// Where the item (or the project's root) was on this machine, if it was associated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_path: Option<PathBuf>,
+    /// Unix timestamp (seconds) of the deletion
+    pub deleted_at: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub directory: bool,
+}
+
+/// Deleted managed items and projects, which can be restored until the trash is emptied.
+/// Content is moved to `<data>/trash/<id>/` (the item's relative path, or the project's
+/// managed dir under the project's name), where the id is the time of the deletion, and is
+/// indexed in `index.json`.
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    pub fn new(trash_path: PathBuf) -> Self {
+        Trash { dir: trash_path }
+    }
+
+    /// Moves the managed item at `managed_path` (`relative_path` in `project`) to the trash
+    pub fn trash_item(
+        &self,
+        project: &str,
+        project_id: &str,
+        managed_path: &Path,
+        relative_path: &Path,
+        original_path: Option<&Path>,
+    ) -> Result<TrashEntry> {
+        self.trash(
+            TrashEntry {
+                id: self.new_id(),
+                project: project.to_owned(),
+                project_id: project_id.to_owned(),
+                item: Some(relative_path.to_path_buf()),
+                original_path: original_path.map(Path::to_path_buf),
+                deleted_at: now(),
+                directory: managed_path.is_dir(),
+            },
+            managed_path,
+        )
+    }
+
+    /// Moves the managed dir of `project` (including its metadata) to the trash
+    pub fn trash_project(
+        &self,
+        project: &str,
+        project_id: &str,
+        managed_dir: &Path,
+        project_root: Option<&Path>,
+    ) -> Result<TrashEntry> {
+        self.trash(
+            TrashEntry {
+                id: self.new_id(),
+                project: project.to_owned(),
+                project_id: project_id.to_owned(),
+                item: None,
+                original_path: project_root.map(Path::to_path_buf),
+                deleted_at: now(),
+                directory: true,
+            },
+            managed_dir,
+        )
+    }
+
+    fn trash(&self, entry: TrashEntry, content: &Path) -> Result<TrashEntry> {
+        move_path(content, &self.stored_path(&entry))?;
+        let mut entries = self.list()?;
+        entries.push(entry.clone());
+        self.write_index(&entries)?;
+        Ok(entry)
+    }
+
+    /// All entries of the trash, oldest first
+    pub fn list(&self) -> Result<Vec<TrashEntry>> {
+        let path = self.dir.join(TRASH_INDEX_FILE);
+        if !path.is_file() {
+            return Ok(vec![]);
+        }
+        let mut entries: Vec<TrashEntry> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        entries.sort_by(|a, b| (a.deleted_at, &a.id).cmp(&(b.deleted_at, &b.id)));
+        Ok(entries)
+    }
+
+    /// Where the content of `entry` is stored
+    pub fn stored_path(&self, entry: &TrashEntry) -> PathBuf {
+        let dir = self.dir.join(&entry.id);
+        match &entry.item {
+            Some(item) => dir.join(item),
+            None => dir.join(&entry.project),
+        }
+    }
+
+    /// Moves the content of the entry with `id` to `destination` and removes the entry.
+    /// Existing files are never overwritten.
+    pub fn restore(&self, id: &str, destination: &Path) -> Result<TrashEntry> {
+        let entries = self.list()?;
+        let Some(entry) = entries.iter().find(|e| e.id == id) else {
+            bail!("No entry '{id}' in the trash. Run 'puff trash list' to see the deleted items.");
+        };
+        if destination.symlink_metadata().is_ok() {
+            bail!("'{}' already exists.", destination.display());
+        }
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        move_path(&self.stored_path(entry), destination)?;
+        let entry = entry.clone();
+        self.forget(&entry, entries)?;
+        Ok(entry)
+    }
+
+    /// Deletes the entries that match `predicate` for good and returns them
+    pub fn empty(&self, predicate: impl Fn(&TrashEntry) -> bool) -> Result<Vec<TrashEntry>> {
+        let entries = self.list()?;
+        let removed: Vec<TrashEntry> = entries.iter().filter(|e| predicate(e)).cloned().collect();
+        let mut kept = entries;
+        for entry in &removed {
+            kept = self.forget(entry, kept)?;
+        }
+        Ok(removed)
+    }
+
+    /// Removes the directory of `entry` and drops it from `entries`, the index
+    fn forget(&self, entry: &TrashEntry, mut entries: Vec<TrashEntry>) -> Result<Vec<TrashEntry>> {
+        let dir = self.dir.join(&entry.id);
+        if dir.symlink_metadata().is_ok() {
+            fs::remove_dir_all(dir)?;
+        }
+        entries.retain(|e| e.id != entry.id);
+        if entries.is_empty() {
+            fs::remove_file(self.dir.join(TRASH_INDEX_FILE)).ok();
+        } else {
+            self.write_index(&entries)?;
+        }
+        Ok(entries)
+    }
+
+    fn new_id(&self) -> String {
+        let base_id = format_timestamp(SystemTime::now());
+        let mut id = base_id.clone();
+        let mut n = 2;
+        while self.dir.join(&id).symlink_metadata().is_ok() {
+            id = format!("{base_id}-{n}");
+            n += 1;
+        }
+        id
+    }
+
+    fn write_index(&self, entries: &[TrashEntry]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(
+            self.dir.join(TRASH_INDEX_FILE),
+            serde_json::to_string_pretty(entries)?,
+        )?;
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Trash;
+    use std::{fs, path::Path};
+
+    #[test]
+    fn trash_item_moves_content_and_restore_brings_it_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let managed_file = dir.path().join("projects/app/config/.env");
+        fs::create_dir_all(managed_file.parent().unwrap()).unwrap();
+        fs::write(&managed_file, "A=1").unwrap();
+        let trash = Trash::new(dir.path().join("trash"));
+
+        let entry = trash
+            .trash_item("app", "1", &managed_file, Path::new("config/.env"), None)
+            .unwrap();
+
+        assert!(!managed_file.exists());
+        assert_eq!(vec![entry.clone()], trash.list().unwrap());
+        assert_eq!(
+            "A=1",
+            fs::read_to_string(trash.stored_path(&entry)).unwrap()
+        );
+
+        let destination = dir.path().join("restored.env");
+        trash.restore(&entry.id, &destination).unwrap();
+
+        assert_eq!("A=1", fs::read_to_string(&destination).unwrap());
+        assert!(trash.list().unwrap().is_empty());
+        assert!(!dir.path().join("trash").join(&entry.id).exists());
+    }
+
+    #[test]
+    fn empty_removes_only_matching_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let managed_dir = dir.path().join("projects/app");
+        fs::create_dir_all(&managed_dir).unwrap();
+        fs::write(managed_dir.join(".env"), "A=1").unwrap();
+        fs::write(managed_dir.join(".secrets"), "B=1").unwrap();
+        let trash = Trash::new(dir.path().join("trash"));
+        let first = trash
+            .trash_item(
+                "app",
+                "1",
+                &managed_dir.join(".env"),
+                Path::new(".env"),
+                None,
+            )
+            .unwrap();
+        let second = trash.trash_project("app", "1", &managed_dir, None).unwrap();
+
+        let removed = trash.empty(|e| e.item.is_some()).unwrap();
+
+        assert_eq!(vec![first], removed);
+        assert_eq!(vec![second.clone()], trash.list().unwrap());
+        assert_eq!(
+            "B=1",
+            fs::read_to_string(trash.stored_path(&second).join(".secrets")).unwrap()
+        );
+    }
+}
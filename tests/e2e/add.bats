@@ -125,3 +125,194 @@ teardown() { teardown_puff_env; }
   assert_symlink "$PROJECT_DIR/.secrets"
   assert_output_contains "Error:"
 }
+
+@test "add: glob pattern adds matching files in subdirectories" {
+  puff_init "myproject"
+  mkdir -p apps/web apps/api
+  echo "root=1" >.env
+  echo "web=1" >apps/web/.env
+  echo "api=1" >apps/api/.env
+  run puff add '**/.env'
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  assert_symlink "$PROJECT_DIR/apps/web/.env"
+  assert_symlink "$PROJECT_DIR/apps/api/.env"
+  assert_output_contains "3 added"
+}
+
+@test "add: glob pattern skips node_modules, target and .git by default" {
+  puff_init "myproject"
+  mkdir -p node_modules/pkg target
+  echo "a=1" >node_modules/pkg/.env
+  echo "b=1" >target/.env
+  echo "c=1" >.env
+  run puff add '**/.env'
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  assert_not_symlink "$PROJECT_DIR/node_modules/pkg/.env"
+  assert_not_symlink "$PROJECT_DIR/target/.env"
+}
+
+@test "add: glob pattern with --no-default-excludes searches excluded directories" {
+  puff_init "myproject"
+  mkdir -p target
+  echo "b=1" >target/.env
+  run puff add --no-default-excludes '**/.env'
+  assert_success
+  assert_symlink "$PROJECT_DIR/target/.env"
+}
+
+@test "add: glob pattern skips already managed files" {
+  puff_init "myproject"
+  echo "a=1" >.env
+  puff add .env
+  run puff add '**/.env'
+  assert_success
+  assert_output_contains "1 skipped"
+}
+
+@test "add: glob pattern skips files inside managed directory" {
+  puff_init "myproject"
+  mkdir -p config
+  echo "db=1" >config/db.env
+  puff add config/
+  run puff add '**/*.env'
+  assert_success
+  assert_output_contains "0 added"
+}
+
+@test "add: glob pattern is relative to the project root" {
+  puff_init "myproject"
+  mkdir -p apps/web
+  echo "root=1" >.env
+  echo "web=1" >apps/web/.env
+  cd apps/web
+  run puff add '**/.env'
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  assert_symlink "$PROJECT_DIR/apps/web/.env"
+}
+
+@test "add: existing path with glob characters is added literally" {
+  puff_init "myproject"
+  echo "a=1" >'secret[1].env'
+  echo "b=1" >secret1.env
+  run puff add 'secret[1].env'
+  assert_success
+  assert_symlink "$PROJECT_DIR/secret[1].env"
+  assert_not_symlink "$PROJECT_DIR/secret1.env"
+}
+
+@test "add: --dir with a glob pattern fails" {
+  puff_init "myproject"
+  echo "a=1" >.env
+  run puff add --dir '**/.env'
+  assert_failure
+  assert_output_contains "can't be used with the glob pattern"
+  assert_not_symlink "$PROJECT_DIR/.env"
+}
+
+@test "add: glob pattern without matches fails" {
+  puff_init "myproject"
+  run puff add '**/*.nothing'
+  assert_failure
+  assert_output_contains "did not match any files"
+}
+
+@test "add: --git-ignore writes path relative to project root" {
+  puff_init "myproject"
+  mkdir -p config
+  echo "db=1" >config/database.env
+  run puff add --git-ignore config/database.env
+  assert_success
+  assert_not_exists "config/.gitignore"
+  run grep -qx '/config/database.env' .gitignore
+  assert_success
+}
+
+@test "add: --git-ignore does not duplicate entries" {
+  puff_init "myproject"
+  echo ".env" >.gitignore
+  echo "a=1" >.env
+  run puff add --git-ignore .env
+  assert_success
+  [ "$(grep -c '\.env' .gitignore)" -eq 1 ]
+}
+
+@test "add: --git-ignore skips paths already ignored by a pattern" {
+  puff_init "myproject"
+  mkdir -p apps
+  echo "*.env" >.gitignore
+  echo "a=1" >apps/local.env
+  run puff add --git-ignore apps/local.env
+  assert_success
+  assert_file_content ".gitignore" "*.env"
+}
+
+@test "add: --git-ignore does not add blank lines" {
+  puff_init "myproject"
+  echo "node_modules" >.gitignore
+  puff add --git-ignore .env
+  puff add --git-ignore .secrets
+  [ "$(grep -c '^$' .gitignore)" -eq 0 ]
+}
+
+@test "add: --git-exclude writes to .git/info/exclude instead of .gitignore" {
+  git init -q
+  puff_init "myproject"
+  echo "secret=123" >.env
+  run puff add --git-exclude .env
+  assert_success
+  assert_not_exists ".gitignore"
+  run grep -qx '/\.env' .git/info/exclude
+  assert_success
+}
+
+@test "add: --git-exclude in a worktree writes to the main repository's exclude file" {
+  git init -q
+  git -c user.name=puff -c user.email=puff@example.com commit -q --allow-empty -m init
+  local worktree
+  worktree="$(mktemp -d)/feature"
+  git worktree add -q "$worktree"
+  cd "$worktree"
+  puff_init "myproject"
+  echo "secret=123" >.env
+  run puff add --git-exclude .env
+  assert_success
+  run grep -qx '/\.env' "$PROJECT_DIR/.git/info/exclude"
+  assert_success
+  rm -rf "$(dirname "$worktree")"
+}
+
+@test "add: --git-exclude outside of a git repository fails" {
+  puff_init "myproject"
+  echo "secret=123" >.env
+  run puff add --git-exclude .env
+  assert_failure
+  assert_output_contains "not inside a git repository"
+}
+
+@test "add: --git-ignore uses .git/info/exclude when configured" {
+  git init -q
+  puff_init "myproject"
+  puff config set git-ignore-target exclude
+  echo "secret=123" >.env
+  run puff add --git-ignore .env
+  assert_success
+  assert_not_exists ".gitignore"
+  run grep -qx '/\.env' .git/info/exclude
+  assert_success
+}
+
+@test "add: stores managed files readable by the user only" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  chmod 644 .env
+  mkdir certs
+  echo "pem" >certs/server.pem
+  puff add .env
+  puff add certs
+  run stat -c %a "$PUFF_DATA_PATH/projects/myproject/.env" "$PUFF_DATA_PATH/projects/myproject/certs/server.pem"
+  assert_output_contains "600"
+  assert_output_not_contains "644"
+}
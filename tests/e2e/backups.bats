@@ -0,0 +1,142 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+# Creates a backup of a local .env (local=1) by adding it while puff has another copy
+setup_backup() {
+  puff_init "myproject"
+  echo "local=1" >.env
+  echo "managed=2" >"$PUFF_DATA_PATH/projects/myproject/.env"
+  puff add .env --on-conflict backup
+}
+
+@test "backups: list shows backups of the current project" {
+  setup_backup
+  run puff backups list
+  assert_success
+  assert_output_contains "Project 'myproject':"
+  assert_output_contains ".env"
+  assert_output_contains "$PROJECT_DIR/.env"
+}
+
+@test "backups: list without backups" {
+  puff_init "myproject"
+  run puff backups list
+  assert_success
+  assert_output_contains "No backups."
+}
+
+@test "backups: restore refuses to overwrite and restores elsewhere with --to" {
+  setup_backup
+  run puff backups restore .env
+  assert_failure
+  assert_output_contains "exists"
+
+  run puff backups restore .env --to .env.local
+  assert_success
+  assert_file_content "$PROJECT_DIR/.env.local" "local=1"
+  assert_file_content "$PROJECT_DIR/.env" "managed=2"
+}
+
+@test "backups: restore of a forgotten file puts it back" {
+  setup_backup
+  puff forget -d .env
+  run puff backups restore .env
+  assert_success
+  assert_file_content "$PROJECT_DIR/.env" "local=1"
+}
+
+@test "backups: restore of an unknown path fails" {
+  setup_backup
+  run puff backups restore other.env --to other.env
+  assert_failure
+  assert_output_contains "No backup of 'other.env'"
+}
+
+@test "backups: clean removes backups" {
+  setup_backup
+  run puff backups clean --older-than 1 -y
+  assert_success
+  assert_output_contains "No backups to remove."
+
+  run puff backups clean -y
+  assert_success
+  assert_output_contains "Removed 1 backup(s)."
+  [ -z "$(ls -A "$PUFF_DATA_PATH/backups")" ]
+}
+
+# Makes the backups of setup_backup older than the retention of 7 days
+expire_backups() {
+  puff config set backup-retention-days 7
+  sed -i 's/"created_at": [0-9]*/"created_at": 1/' "$PUFF_DATA_PATH"/backups/*/index.json
+}
+
+@test "backups: expired backups are kept by commands that make no backups" {
+  setup_backup
+  expire_backups
+  puff status
+  puff list
+  run puff backups list
+  assert_success
+  assert_output_contains ".env"
+}
+
+@test "backups: expired backups are removed after adding files" {
+  setup_backup
+  expire_backups
+  echo "other=1" >other.env
+  puff add other.env
+  run puff backups list
+  assert_success
+  assert_output_contains "No backups."
+}
+
+@test "backups: expired backups are removed when adding some of the files fails" {
+  setup_backup
+  expire_backups
+  echo "other=1" >other.env
+  run puff add other.env "$(mktemp -d)/outside.env"
+  assert_failure
+  assert_symlink "$PROJECT_DIR/other.env"
+  run puff backups list
+  assert_success
+  assert_output_contains "No backups."
+}
+
+@test "backups: clean removes expired backups" {
+  setup_backup
+  expire_backups
+  run puff backups clean --older-than 365 -y
+  assert_success
+  assert_output_contains "Removed 1 expired backup(s)."
+}
+
+@test "backups: a corrupt index does not break other commands" {
+  setup_backup
+  echo "not json" >"$(echo "$PUFF_DATA_PATH"/backups/*/index.json)"
+  run puff status
+  assert_success
+  echo "other=1" >other.env
+  run puff add other.env
+  assert_success
+  assert_output_contains "could not remove expired backups"
+}
+
+@test "backups: project forget backs up puff's copy instead of overwriting a local file" {
+  puff_init "myproject"
+  echo "managed=1" >.env
+  puff add .env
+  rm .env
+  echo "local=2" >.env
+  run puff project forget -y myproject
+  assert_success
+  assert_file_content "$PROJECT_DIR/.env" "local=2"
+  assert_not_exists "$PROJECT_DIR/.env.bak"
+  run puff backups list -p myproject
+  assert_output_contains ".env"
+  run puff backups restore .env -p myproject --to "$PROJECT_DIR/.env.puff"
+  assert_success
+  assert_file_content "$PROJECT_DIR/.env.puff" "managed=1"
+}
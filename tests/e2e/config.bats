@@ -0,0 +1,25 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "config: list shows default values" {
+  run puff config list
+  assert_success
+  assert_output_contains "git-ignore-target = gitignore"
+}
+
+@test "config: set changes the value" {
+  run puff config set git-ignore-target exclude
+  assert_success
+  run puff config get git-ignore-target
+  assert_success
+  assert_output_contains "exclude"
+}
+
+@test "config: set rejects invalid values" {
+  run puff config set git-ignore-target nowhere
+  assert_failure
+  assert_output_contains "Allowed values: gitignore, exclude"
+}
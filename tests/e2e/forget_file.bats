@@ -81,3 +81,36 @@ teardown() { teardown_puff_env; }
   assert_not_symlink "$PROJECT_DIR/.secrets"
   assert_output_contains "Error:"
 }
+
+@test "forget: removes the .gitignore entry added by puff" {
+  puff_init "myproject"
+  echo "node_modules" >.gitignore
+  echo "secret=123" >.env
+  puff add --git-ignore .env
+  run puff forget .env
+  assert_success
+  assert_output_contains "from .gitignore"
+  assert_file_content ".gitignore" "node_modules"
+}
+
+@test "forget: keeps .gitignore entries written by the user" {
+  puff_init "myproject"
+  echo ".env" >.gitignore
+  echo "secret=123" >.env
+  puff add --git-ignore .env
+  run puff forget .env
+  assert_success
+  assert_file_content ".gitignore" ".env"
+}
+
+@test "forget: removes the .git/info/exclude entry added by puff" {
+  git init -q
+  puff_init "myproject"
+  echo "secret=123" >.env
+  puff add --git-exclude .env
+  run puff forget .env
+  assert_success
+  assert_output_contains "from .git/info/exclude"
+  run grep -qx '\.env' .git/info/exclude
+  assert_failure
+}
@@ -0,0 +1,48 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "gitignore fix: adds missing entries to .gitignore" {
+  git init -q
+  puff_init "myproject"
+  echo "secret=123" >.env
+  puff add .env
+  run puff gitignore fix
+  assert_success
+  assert_output_contains "Added '.env' to .gitignore"
+  assert_file_content ".gitignore" "/.env"
+  run puff status
+  [[ "$output" != *"WARNING"* ]]
+}
+
+@test "gitignore fix: -x covers linked worktrees" {
+  git init -q
+  git -c user.name=puff -c user.email=puff@example.com commit -q --allow-empty -m init
+  local worktree
+  worktree="$(mktemp -d)/feature"
+  git worktree add -q "$worktree"
+  puff_init "myproject"
+  echo "secret=123" >.env
+  puff add .env
+  (cd "$worktree" && puff link myproject)
+  run puff status
+  assert_output_contains "feature (not ignored by git)"
+  run puff gitignore fix -x
+  assert_success
+  run grep -qx '/\.env' .git/info/exclude
+  assert_success
+  run puff status
+  [[ "$output" != *"WARNING"* ]]
+  rm -rf "$(dirname "$worktree")"
+}
+
+@test "gitignore fix: reports nothing to do" {
+  git init -q
+  puff_init "myproject"
+  puff add --git-ignore .env
+  run puff gitignore fix
+  assert_success
+  assert_output_contains "are ignored by git"
+}
@@ -0,0 +1,123 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() {
+  setup_puff_env
+  git init -q
+  git config user.name puff
+  git config user.email puff@example.com
+}
+teardown() { teardown_puff_env; }
+
+@test "hooks install: writes an executable pre-commit hook" {
+  puff_init "myproject"
+  run puff hooks install
+  assert_success
+  [ -x .git/hooks/pre-commit ]
+  run grep -q 'check-staged' .git/hooks/pre-commit
+  assert_success
+}
+
+@test "hooks install: does not overwrite a foreign hook" {
+  puff_init "myproject"
+  mkdir -p .git/hooks
+  printf '#!/bin/sh\nmake lint\n' >.git/hooks/pre-commit
+  run puff hooks install
+  assert_failure
+  assert_output_contains "already exists"
+  run grep -q 'make lint' .git/hooks/pre-commit
+  assert_success
+}
+
+@test "hooks: commit of a managed file is blocked" {
+  puff_init "myproject"
+  echo "API_KEY=supersecret123" >.env
+  puff add .env
+  puff hooks install
+  git add .env
+  run git commit -m "leak"
+  assert_failure
+  assert_output_contains "'.env' is managed by puff"
+}
+
+@test "hooks: commit containing a managed dotenv value is blocked" {
+  puff_init "myproject"
+  echo "API_KEY=supersecret123" >.env
+  puff add --git-ignore .env
+  puff hooks install
+  echo 'const key = "supersecret123";' >app.js
+  git add app.js
+  run git commit -m "leak"
+  assert_failure
+  assert_output_contains "contains the value of API_KEY"
+}
+
+@test "hooks: commit of unrelated changes succeeds" {
+  puff_init "myproject"
+  echo "API_KEY=supersecret123" >.env
+  puff add --git-ignore .env
+  puff hooks install
+  echo 'console.log("hi");' >app.js
+  git add app.js .gitignore
+  run git commit -m "ok"
+  assert_success
+}
+
+@test "hooks: staged submodules don't block a commit" {
+  puff_init "myproject"
+  echo "API_KEY=supersecret123" >.env
+  puff add --git-ignore .env
+  puff hooks install
+  git update-index --add --cacheinfo 160000,1111111111111111111111111111111111111111,vendor/lib
+  echo 'console.log("hi");' >app.js
+  git add app.js .gitignore
+  run git commit -m "ok"
+  assert_success
+  assert_output_not_contains "could not check"
+}
+
+@test "check-staged: succeeds when nothing is staged" {
+  puff_init "myproject"
+  run puff check-staged
+  assert_success
+}
+
+@test "hooks install --worktrees: new worktrees get linked automatically" {
+  git commit -q --allow-empty -m init
+  puff_init "myproject"
+  echo "secret=123" >.env
+  puff add --git-ignore .env
+  run puff hooks install --worktrees
+  assert_success
+  [ -x .git/hooks/post-checkout ]
+
+  local worktree
+  worktree="$(mktemp -d)/feature"
+  run git worktree add "$worktree"
+  assert_success
+  assert_symlink "$worktree/.env"
+  assert_file_content "$worktree/.env" "secret=123"
+  rm -rf "$(dirname "$worktree")"
+}
+
+@test "hooks install --worktrees: regular checkouts don't link anything" {
+  git commit -q --allow-empty -m init
+  puff_init "myproject"
+  puff hooks install --worktrees
+  run git checkout -b feature
+  assert_success
+  [[ "$output" != *"Linked"* ]]
+}
+
+@test "hooks post-checkout: warns instead of failing the checkout" {
+  puff_init "myproject"
+  local broken
+  broken="$(mktemp -d)"
+  echo "not a git file" >"$broken/.git"
+  cd "$broken"
+  run puff hooks post-checkout 0000000000000000000000000000000000000000 abc 1
+  assert_success
+  assert_output_contains "could not link projects into the new worktree"
+  cd "$PROJECT_DIR"
+  rm -rf "$broken"
+}
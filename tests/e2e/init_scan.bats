@@ -0,0 +1,95 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() {
+  setup_puff_env
+  export GIT_CONFIG_GLOBAL="$PROJECT_DIR/.gitconfig"
+  git config --global init.defaultBranch main
+}
+teardown() { teardown_puff_env; }
+
+# Creates a git checkout with the given remote URL
+make_checkout() {
+  mkdir -p "$1"
+  git -C "$1" init -q
+  if [ -n "${2:-}" ]; then
+    git -C "$1" remote add origin "$2"
+  fi
+}
+
+# Simulates a new machine: the data directory is synced, config.json is not
+forget_associations() {
+  echo '{"projects":[]}' >"$PUFF_CONFIG_PATH/config.json"
+}
+
+@test "init --scan: init records the git remote of the project" {
+  make_checkout "$PROJECT_DIR/api" "git@github.com:org/api.git"
+  cd "$PROJECT_DIR/api"
+  run puff init --name api
+  assert_success
+  assert_file_content "$PUFF_DATA_PATH/projects/api/.puff_git_remotes" "git@github.com:org/api.git"
+}
+
+@test "init --scan: associates projects by remote and directory name" {
+  make_checkout "$PROJECT_DIR/old/api" "git@github.com:org/api.git"
+  cd "$PROJECT_DIR/old/api"
+  puff init --name backend
+  echo "KEY=1" >.env
+  puff add .env
+  mkdir -p "$PROJECT_DIR/old/web"
+  cd "$PROJECT_DIR/old/web"
+  puff init --name web
+  echo "KEY=2" >.env
+  puff add .env
+  forget_associations
+
+  make_checkout "$PROJECT_DIR/code/api" "https://github.com/org/api"
+  make_checkout "$PROJECT_DIR/code/web" "https://github.com/org/frontend"
+  cd "$PROJECT_DIR"
+  run puff init --scan "$PROJECT_DIR/code" -y
+  assert_success
+  assert_output_contains "backend -> "
+  assert_output_contains "(git remote)"
+  assert_output_contains "(directory name)"
+  assert_output_contains "Associated 2 project(s)."
+  assert_symlink "$PROJECT_DIR/code/api/.env"
+  assert_file_content "$PROJECT_DIR/code/api/.env" "KEY=1"
+  assert_symlink "$PROJECT_DIR/code/web/.env"
+}
+
+@test "init --scan: associates projects by marker file" {
+  mkdir -p "$PROJECT_DIR/old/app"
+  cd "$PROJECT_DIR/old/app"
+  puff init --name app --marker
+  echo "KEY=1" >.env
+  puff add .env
+  forget_associations
+
+  mkdir -p "$PROJECT_DIR/code/renamed"
+  cp "$PROJECT_DIR/old/app/.puff" "$PROJECT_DIR/code/renamed/.puff"
+  cd "$PROJECT_DIR"
+  run puff init --scan "$PROJECT_DIR/code" -y
+  assert_success
+  assert_output_contains "(.puff marker)"
+  assert_symlink "$PROJECT_DIR/code/renamed/.env"
+}
+
+@test "init --scan: skips projects matching several directories" {
+  mkdir -p "$PROJECT_DIR/old/web"
+  cd "$PROJECT_DIR/old/web"
+  puff init --name web
+  forget_associations
+
+  make_checkout "$PROJECT_DIR/code/a/web"
+  make_checkout "$PROJECT_DIR/code/b/web"
+  cd "$PROJECT_DIR"
+  run puff init --scan "$PROJECT_DIR/code" -y
+  assert_success
+  assert_output_contains "Skipping 'web', it matches more than one directory"
+  assert_output_contains "No checkouts of unassociated projects found"
+}
+
+@test "init --scan: --yes requires --scan" {
+  run puff init --yes
+  assert_failure
+}
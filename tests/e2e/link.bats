@@ -50,8 +50,8 @@ teardown() { teardown_puff_env; }
   assert_success
   assert_symlink "$other_dir/.env"
   assert_file_content "$other_dir/.env" "managed=1"
-  assert_file_exists "$other_dir/.env.bak"
-  assert_file_content "$other_dir/.env.bak" "local=2"
+  assert_not_exists "$other_dir/.env.bak"
+  assert_file_content "$(echo "$PUFF_DATA_PATH"/backups/*/*/.env)" "local=2"
 
   rm -rf "$other_dir"
 }
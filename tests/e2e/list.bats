@@ -63,3 +63,85 @@ teardown() { teardown_puff_env; }
   assert_success
   assert_output_not_contains "myproject"
 }
+
+@test "list --format json: includes associated and unassociated projects" {
+  puff_init "myproject"
+  mkdir -p "$PUFF_DATA_PATH/projects/orphan"
+  run puff list --format json
+  assert_success
+  assert_output_contains '"name": "myproject"'
+  assert_output_contains '"associated": true'
+  assert_output_contains '"name": "orphan"'
+  assert_output_contains '"associated": false'
+  assert_output_not_contains "ASSOCIATED PROJECTS:"
+}
+
+@test "list: shows path, item counts and health columns" {
+  puff_init "myproject"
+  echo "a=1" >.env
+  puff add .env
+  mkdir -p "$PUFF_DATA_PATH/projects/orphan"
+  run puff list
+  assert_success
+  assert_output_contains "HEALTH"
+  assert_output_contains "$PROJECT_DIR"
+  assert_output_contains "unassociated"
+  assert_output_contains "ok"
+}
+
+@test "list --unhealthy: shows only projects with broken links" {
+  puff_init "myproject"
+  puff add .env
+  local project2
+  project2="$(mktemp -d)"
+  cd "$project2"
+  puff_init "healthy"
+  cd "$PROJECT_DIR"
+  rm .env
+  run puff list --unhealthy
+  assert_success
+  assert_output_contains "myproject"
+  assert_output_contains "broken links"
+  assert_output_not_contains "healthy "
+  rm -rf "$project2"
+}
+
+@test "list --filter: shows only matching project names" {
+  puff_init "myproject"
+  mkdir -p "$PUFF_DATA_PATH/projects/orphan"
+  run puff list --filter orph
+  assert_success
+  assert_output_contains "orphan"
+  assert_output_not_contains "myproject"
+}
+
+@test "list --sort size: largest project first" {
+  puff_init "small"
+  mkdir -p "$PUFF_DATA_PATH/projects/big"
+  echo "0123456789" >"$PUFF_DATA_PATH/projects/big/.env"
+  run puff list --sort size
+  assert_success
+  [ "$(echo "$output" | sed -n 2p | cut -d' ' -f1)" = "big" ]
+}
+
+@test "list: shows a project whose data can't be read as unhealthy" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  # Paths longer than PATH_MAX can't be read, not even by root
+  local name
+  name="$(printf 'd%.0s' $(seq 200))"
+  (
+    cd "$PUFF_DATA_PATH/projects/myproject"
+    for _ in $(seq 25); do
+      mkdir "$name"
+      cd "$name"
+    done
+  )
+  run puff list
+  assert_success
+  assert_output_contains "unreadable data"
+  run puff list --unhealthy
+  assert_output_contains "myproject"
+  rm -rf "$PUFF_DATA_PATH/projects/myproject/$name"
+}
@@ -0,0 +1,125 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "marker: init --marker writes .puff with name and id" {
+  run puff init --name myproject --marker
+  assert_success
+  assert_file_exists "$PROJECT_DIR/.puff"
+  grep -q '"name": "myproject"' "$PROJECT_DIR/.puff"
+  grep -q '"id": ' "$PROJECT_DIR/.puff"
+}
+
+@test "marker: project mark writes .puff into the project root" {
+  puff_init "myproject"
+  mkdir -p src
+  cd src
+  run puff project mark
+  assert_success
+  assert_output_contains "Commit it"
+  assert_file_exists "$PROJECT_DIR/.puff"
+}
+
+@test "marker: link and status without arguments work in another checkout" {
+  puff init --name myproject --marker
+  echo "secret=123" >.env
+  puff add .env
+
+  local clone
+  clone="$(mktemp -d)"
+  cp "$PROJECT_DIR/.puff" "$clone/.puff"
+  mkdir -p "$clone/src"
+  cd "$clone/src"
+
+  run puff status
+  assert_success
+  assert_output_contains "Project: myproject"
+
+  run puff link
+  assert_success
+  assert_symlink "$clone/.env"
+  assert_file_content "$clone/.env" "secret=123"
+
+  rm -rf "$clone"
+}
+
+@test "marker: init links another checkout of an associated project" {
+  puff init --name myproject --marker
+  echo "secret=123" >.env
+  puff add .env
+
+  local clone
+  clone="$(mktemp -d)"
+  cp "$PROJECT_DIR/.puff" "$clone/.puff"
+  cd "$clone"
+
+  run puff init
+  assert_success
+  assert_output_contains "Linking its managed files here"
+  assert_symlink "$clone/.env"
+
+  rm -rf "$clone"
+}
+
+@test "marker: init moves the association when the old directory is gone" {
+  puff init --name myproject --marker
+  echo "secret=123" >.env
+  puff add .env
+
+  local moved
+  moved="$(mktemp -d)"
+  cp "$PROJECT_DIR/.puff" "$moved/.puff"
+  rm -rf "$PROJECT_DIR"
+  cd "$moved"
+
+  run puff init
+  assert_success
+  assert_output_contains "Moving it here"
+  assert_symlink "$moved/.env"
+
+  run puff status
+  assert_output_contains "Project: myproject"
+
+  rm -rf "$moved"
+}
+
+@test "marker: init associates an unassociated project in a fresh clone" {
+  puff init --name myproject --marker
+  echo "secret=123" >.env
+  puff add .env
+  local marker
+  marker="$(cat .puff)"
+  # A new machine: the data directory is synced, config.json is not
+  echo '{"projects":[]}' >"$PUFF_CONFIG_PATH/config.json"
+
+  local clone
+  clone="$(mktemp -d)"
+  printf '%s\n' "$marker" >"$clone/.puff"
+  cd "$clone"
+
+  run puff init
+  assert_success
+  assert_output_contains "initialized from its .puff file"
+  assert_symlink "$clone/.env"
+  assert_file_content "$clone/.env" "secret=123"
+
+  rm -rf "$clone"
+}
+
+@test "marker: init creates a new project with the marker's name" {
+  printf '{\n  "name": "fromclone",\n  "id": "0b7d6f3e-6f3a-4c1e-9a7e-2a4b1c9d8e7f"\n}\n' >.puff
+
+  run puff init
+  assert_success
+
+  run puff list
+  assert_output_contains "fromclone"
+}
+
+@test "marker: link without arguments fails without a registered marker" {
+  run puff link
+  assert_failure
+  assert_output_contains "not associated with any puff project"
+}
@@ -0,0 +1,79 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "mv: renames a managed file" {
+  puff_init "myproject"
+  mkdir config
+  echo "KEY=1" >config/dev.env
+  puff add config/dev.env
+  run puff mv config/dev.env config/local.env
+  assert_success
+  assert_output_contains "Moved 'config/dev.env' to 'config/local.env' in project 'myproject'."
+  assert_not_exists "$PROJECT_DIR/config/dev.env"
+  assert_symlink "$PROJECT_DIR/config/local.env"
+  assert_file_content "$PROJECT_DIR/config/local.env" "KEY=1"
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject/config/dev.env"
+}
+
+@test "mv: moves a managed directory and updates .puff_managed_dirs" {
+  puff_init "myproject"
+  mkdir secrets
+  echo "KEY=1" >secrets/key
+  puff add secrets --dir
+  run puff mv secrets config/secrets
+  assert_success
+  assert_symlink "$PROJECT_DIR/config/secrets"
+  assert_file_content "$PROJECT_DIR/config/secrets/key" "KEY=1"
+  run grep -qx 'config/secrets' "$PUFF_DATA_PATH/projects/myproject/.puff_managed_dirs"
+  assert_success
+  run grep -qx 'secrets' "$PUFF_DATA_PATH/projects/myproject/.puff_managed_dirs"
+  assert_failure
+}
+
+@test "mv: replaces the gitignore entry added by puff" {
+  puff_init "myproject"
+  puff add --git-ignore .env
+  run puff mv .env .env.local
+  assert_success
+  run grep -qx '/\.env\.local' .gitignore
+  assert_success
+  run grep -qx '/\.env' .gitignore
+  assert_failure
+}
+
+@test "mv: moves the links in other worktrees" {
+  git init -q
+  git -c user.name=puff -c user.email=puff@example.com commit -q --allow-empty -m init
+  puff_init "myproject"
+  echo "KEY=1" >.env
+  puff add .env
+  local worktree
+  worktree="$(mktemp -d)/feature"
+  git worktree add -q "$worktree"
+  (cd "$worktree" && puff link myproject)
+
+  run puff mv .env .env.local
+  assert_success
+  assert_symlink "$worktree/.env.local"
+  assert_not_exists "$worktree/.env"
+  assert_file_content "$worktree/.env.local" "KEY=1"
+  rm -rf "$(dirname "$worktree")"
+}
+
+@test "mv: fails for unmanaged files and existing destinations" {
+  puff_init "myproject"
+  echo "KEY=1" >.env
+  echo "X=1" >other
+  run puff mv other other2
+  assert_failure
+  assert_output_contains "is not managed by project 'myproject'"
+
+  puff add .env
+  run puff mv .env other
+  assert_failure
+  assert_output_contains "'other' already exists."
+  assert_symlink "$PROJECT_DIR/.env"
+}
@@ -0,0 +1,152 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+# Creates project 'myproject' whose managed .env differs from the local one
+setup_add_conflict() {
+  puff_init "myproject"
+  echo "local=1" >.env
+  echo "managed=2" >"$PUFF_DATA_PATH/projects/myproject/.env"
+}
+
+@test "on-conflict: add fails by default outside of a terminal" {
+  setup_add_conflict
+  run puff add .env
+  assert_failure
+  assert_output_contains "Use --on-conflict"
+  assert_file_content "$PROJECT_DIR/.env" "local=1"
+}
+
+@test "on-conflict: add --on-conflict adopt keeps the local copy" {
+  setup_add_conflict
+  run puff add .env --on-conflict adopt
+  assert_success
+  assert_output_contains "Added"
+  assert_symlink "$PROJECT_DIR/.env"
+  assert_file_content "$PUFF_DATA_PATH/projects/myproject/.env" "local=1"
+}
+
+@test "on-conflict: add --on-conflict overwrite keeps puff's copy" {
+  setup_add_conflict
+  run puff add .env --on-conflict overwrite
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  assert_file_content "$PROJECT_DIR/.env" "managed=2"
+  assert_not_exists "$PROJECT_DIR/.env.bak"
+}
+
+@test "on-conflict: add --on-conflict backup keeps both" {
+  setup_add_conflict
+  run puff add .env --on-conflict backup
+  assert_success
+  assert_file_content "$PROJECT_DIR/.env" "managed=2"
+  assert_not_exists "$PROJECT_DIR/.env.bak"
+  assert_file_content "$(echo "$PUFF_DATA_PATH"/backups/*/*/.env)" "local=1"
+}
+
+@test "on-conflict: add --on-conflict skip leaves the local copy alone" {
+  setup_add_conflict
+  run puff add .env --on-conflict skip
+  assert_success
+  assert_output_contains "Skipped"
+  assert_output_not_contains "Added"
+  [ ! -L "$PROJECT_DIR/.env" ]
+  assert_file_content "$PROJECT_DIR/.env" "local=1"
+}
+
+@test "on-conflict: identical copies are linked without a backup" {
+  puff_init "myproject"
+  echo "same=1" >.env
+  echo "same=1" >"$PUFF_DATA_PATH/projects/myproject/.env"
+  run puff add .env
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  assert_not_exists "$PROJECT_DIR/.env.bak"
+}
+
+@test "on-conflict: link --on-conflict skip and fail" {
+  puff_init "myproject"
+  echo "managed=1" >.env
+  puff add .env
+  local other_dir
+  other_dir="$(mktemp -d)"
+  echo "local=2" >"$other_dir/.env"
+  cd "$other_dir"
+
+  run puff link myproject --on-conflict fail
+  assert_failure
+  assert_output_contains "Conflict"
+
+  run puff link myproject --on-conflict skip
+  assert_success
+  assert_file_content "$other_dir/.env" "local=2"
+  assert_not_exists "$other_dir/.env.bak"
+  rm -rf "$other_dir"
+}
+
+@test "on-conflict: link honours --on-conflict for managed directories" {
+  puff_init "myproject"
+  mkdir config
+  echo "managed=1" >config/db.env
+  puff add config
+  local other_dir
+  other_dir="$(mktemp -d)"
+  mkdir "$other_dir/config"
+  echo "local=2" >"$other_dir/config/db.env"
+  cd "$other_dir"
+
+  run puff link myproject --on-conflict fail
+  assert_failure
+  assert_output_contains "Conflict"
+  assert_not_symlink "$other_dir/config"
+
+  run puff link myproject --on-conflict skip
+  assert_success
+  assert_not_symlink "$other_dir/config"
+  assert_file_content "$other_dir/config/db.env" "local=2"
+
+  run puff link myproject --on-conflict adopt
+  assert_success
+  assert_symlink "$other_dir/config"
+  assert_file_content "$PUFF_DATA_PATH/projects/myproject/config/db.env" "local=2"
+  rm -rf "$other_dir"
+}
+
+@test "on-conflict: a file in place of a managed directory fails without changes" {
+  puff_init "myproject"
+  mkdir config
+  echo "managed=1" >config/db.env
+  puff add config
+  local other_dir
+  other_dir="$(mktemp -d)"
+  echo "local=2" >"$other_dir/config"
+  cd "$other_dir"
+
+  run puff link myproject --on-conflict overwrite
+  assert_failure
+  assert_output_contains "is a file in the project directory but a directory"
+  assert_file_content "$other_dir/config" "local=2"
+  rm -rf "$other_dir"
+}
+
+@test "on-conflict: init --associate --on-conflict adopt" {
+  mkdir -p "$PUFF_DATA_PATH/projects/stored"
+  echo "managed=1" >"$PUFF_DATA_PATH/projects/stored/.env"
+  local other_dir
+  other_dir="$(mktemp -d)"
+  echo "local=2" >"$other_dir/.env"
+  cd "$other_dir"
+  run puff init --associate stored --on-conflict adopt
+  assert_success
+  assert_symlink "$other_dir/.env"
+  assert_file_content "$PUFF_DATA_PATH/projects/stored/.env" "local=2"
+  rm -rf "$other_dir"
+}
+
+@test "on-conflict: invalid values are rejected" {
+  setup_add_conflict
+  run puff add .env --on-conflict whatever
+  assert_failure
+}
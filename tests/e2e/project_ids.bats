@@ -0,0 +1,86 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+project_id() {
+  grep -o '"id": "[^"]*"' "$PUFF_DATA_PATH/projects/$1/.puff_project.json" | cut -d'"' -f4
+}
+
+@test "project ids: placeholder ids of old configs are replaced" {
+  mkdir -p "$PUFF_DATA_PATH/projects/app" "$PROJECT_DIR/app"
+  echo "KEY=1" >"$PUFF_DATA_PATH/projects/app/.env"
+  echo '{"name":"app","id":"1"}' >"$PROJECT_DIR/app/.puff"
+  cat >"$PUFF_CONFIG_PATH/config.json" <<EOF
+{"projects":[{"name":"app","id":"1","path":"$PROJECT_DIR/app"}]}
+EOF
+
+  run puff list
+  assert_success
+  id=$(project_id app)
+  [ "$id" != "1" ]
+  run grep -qF "\"id\": \"$id\"" "$PUFF_CONFIG_PATH/config.json"
+  assert_success
+  run grep -qF "\"id\": \"$id\"" "$PROJECT_DIR/app/.puff"
+  assert_success
+}
+
+@test "project ids: a project renamed on another machine keeps its association" {
+  cd "$PROJECT_DIR"
+  puff init --name old
+  echo "KEY=1" >.env
+  puff add .env
+  mv "$PUFF_DATA_PATH/projects/old" "$PUFF_DATA_PATH/projects/new"
+
+  run puff status
+  assert_success
+  assert_output_contains "Project: new"
+  run puff list --unassociated
+  assert_output_not_contains "new"
+}
+
+@test "project ids: forgetting a project removes its registry entry" {
+  cd "$PROJECT_DIR"
+  puff init --name app
+  run puff project forget app -y
+  assert_success
+  run grep -q '"app"' "$PUFF_CONFIG_PATH/config.json"
+  assert_failure
+}
+
+@test "project ids: marker of a renamed project associates its data" {
+  mkdir -p "$PROJECT_DIR/first"
+  cd "$PROJECT_DIR/first"
+  puff init --name old --marker
+  echo "KEY=1" >.env
+  puff add .env
+  echo '{"projects":[]}' >"$PUFF_CONFIG_PATH/config.json"
+  mv "$PUFF_DATA_PATH/projects/old" "$PUFF_DATA_PATH/projects/new"
+
+  mkdir -p "$PROJECT_DIR/clone"
+  cp "$PROJECT_DIR/first/.puff" "$PROJECT_DIR/clone/.puff"
+  cd "$PROJECT_DIR/clone"
+  run puff init
+  assert_success
+  assert_symlink "$PROJECT_DIR/clone/.env"
+  assert_not_exists "$PUFF_DATA_PATH/projects/old"
+}
+
+@test "project ids: import matches projects by id" {
+  mkdir -p "$PROJECT_DIR/app"
+  cd "$PROJECT_DIR/app"
+  puff init --name old
+  id=$(project_id old)
+  echo '{"projects":[]}' >"$PUFF_CONFIG_PATH/config.json"
+  mv "$PUFF_DATA_PATH/projects/old" "$PUFF_DATA_PATH/projects/new"
+  cat >"$PROJECT_DIR/other.json" <<EOF
+{"projects":[{"name":"old","id":"$id","path":"$PROJECT_DIR/app"}]}
+EOF
+
+  run puff config import "$PROJECT_DIR/other.json"
+  assert_success
+  assert_output_contains "Imported 'old' as 'new'"
+  run puff status
+  assert_output_contains "Project: new"
+}
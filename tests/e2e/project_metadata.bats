@@ -0,0 +1,111 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "project metadata: init creates the metadata file with the project id" {
+  cd "$PROJECT_DIR"
+  run puff init --name app
+  assert_success
+  assert_file_exists "$PUFF_DATA_PATH/projects/app/.puff_project.json"
+  id=$(grep -o '"id": "[^"]*"' "$PUFF_CONFIG_PATH/config.json")
+  run grep -qF "$id" "$PUFF_DATA_PATH/projects/app/.puff_project.json"
+  assert_success
+}
+
+@test "project metadata: id is kept when the project is associated on another machine" {
+  cd "$PROJECT_DIR"
+  puff init --name app
+  id=$(grep -o '"id": "[^"]*"' "$PUFF_DATA_PATH/projects/app/.puff_project.json")
+  echo '{"projects":[]}' >"$PUFF_CONFIG_PATH/config.json"
+
+  mkdir -p "$PROJECT_DIR/elsewhere"
+  cd "$PROJECT_DIR/elsewhere"
+  run puff init --associate app
+  assert_success
+  run grep -qF "$id" "$PUFF_CONFIG_PATH/config.json"
+  assert_success
+}
+
+@test "project metadata: metadata is created for data dirs without it" {
+  mkdir -p "$PUFF_DATA_PATH/projects/old"
+  run puff list
+  assert_success
+  assert_file_exists "$PUFF_DATA_PATH/projects/old/.puff_project.json"
+}
+
+@test "project metadata: show prints id, creation date and when items were added" {
+  cd "$PROJECT_DIR"
+  puff init --name app
+  echo "KEY=1" >.env
+  puff add .env
+  run puff project show
+  assert_success
+  assert_output_contains "Id: "
+  assert_output_contains "Created: "
+  assert_output_contains ".env (linked), added "
+}
+
+@test "project metadata: the metadata file is not a managed item" {
+  cd "$PROJECT_DIR"
+  puff init --name app
+  run puff project show
+  assert_output_contains "(none)"
+  assert_output_not_contains ".puff_project.json"
+}
+
+@test "project metadata: describe sets and removes the description" {
+  cd "$PROJECT_DIR"
+  puff init --name app
+  run puff project describe "Main API"
+  assert_success
+  run puff project show app
+  assert_output_contains "Description: Main API"
+  run puff project show app --format json
+  assert_output_contains '"description": "Main API"'
+
+  run puff project describe --project app
+  assert_success
+  assert_output_contains "Removed the description of project 'app'"
+  run puff project show app
+  assert_output_not_contains "Description:"
+}
+
+@test "project metadata: project setting overrides the global one" {
+  cd "$PROJECT_DIR"
+  git init -q
+  puff init --name app
+  run puff config set git-ignore-target exclude --project app
+  assert_success
+  assert_output_contains "git-ignore-target = exclude (project 'app')"
+  run puff config get git-ignore-target --project app
+  assert_output_contains "exclude"
+  run puff config get git-ignore-target
+  assert_output_contains "gitignore"
+
+  echo "KEY=1" >.env
+  run puff add .env -g
+  assert_success
+  run grep -qx "/.env" "$PROJECT_DIR/.git/info/exclude"
+  assert_success
+  assert_not_exists "$PROJECT_DIR/.gitignore"
+}
+
+@test "project metadata: unset removes the project's override" {
+  cd "$PROJECT_DIR"
+  puff init --name app
+  puff config set git-ignore-target exclude --project app
+  run puff config list --project app
+  assert_output_contains "git-ignore-target = exclude (project)"
+  run puff config unset git-ignore-target --project app
+  assert_success
+  run puff config get git-ignore-target --project app
+  assert_output_contains "gitignore"
+}
+
+@test "project metadata: settings of an unknown project fail" {
+  run puff config set git-ignore-target exclude --project nope
+  assert_failure
+  assert_output_contains "Project 'nope' was not found"
+}
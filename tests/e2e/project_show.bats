@@ -0,0 +1,47 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "project show: defaults to the project of the current directory" {
+  puff_init "myproject"
+  puff add .env
+  run puff project show
+  assert_success
+  assert_output_contains "Project: myproject"
+  assert_output_contains ".env (linked)"
+}
+
+@test "project show: reports missing links" {
+  puff_init "myproject"
+  puff add .env
+  rm .env
+  run puff project show myproject
+  assert_success
+  assert_output_contains ".env (missing)"
+}
+
+@test "project show: shows unassociated project" {
+  mkdir -p "$PUFF_DATA_PATH/projects/orphan"
+  run puff project show orphan
+  assert_success
+  assert_output_contains "Path: (unassociated)"
+}
+
+@test "project show: nonexistent project fails" {
+  run puff project show nonexistent
+  assert_failure
+  assert_output_contains "not found"
+}
+
+@test "project show --format json: prints project details" {
+  puff_init "myproject"
+  mkdir -p config
+  puff add config/
+  run puff project show --format json
+  assert_success
+  assert_output_contains '"kind": "directory"'
+  assert_output_contains '"path": "config"'
+  assert_output_contains '"managed_path":'
+}
@@ -0,0 +1,82 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+# Creates project 'app' in $PROJECT_DIR/app with .env, linked into $PROJECT_DIR/wt
+setup_linked_copy() {
+  mkdir -p "$PROJECT_DIR/app" "$PROJECT_DIR/wt"
+  cd "$PROJECT_DIR/app"
+  puff init --name app
+  echo "KEY=1" >.env
+  puff add .env
+  cd "$PROJECT_DIR/wt"
+  puff link app
+}
+
+@test "prune-links: deletes links to forgotten items" {
+  setup_linked_copy
+  cd "$PROJECT_DIR/app"
+  puff forget .env
+
+  cd "$PROJECT_DIR/wt"
+  run puff prune-links --delete
+  assert_success
+  assert_output_contains "'.env' is no longer managed by project 'app'"
+  assert_output_contains "Deleted 1 and restored 0 link(s)."
+  assert_not_exists "$PROJECT_DIR/wt/.env"
+  assert_file_content "$PROJECT_DIR/app/.env" "KEY=1"
+}
+
+@test "prune-links: restores copies of forgotten items" {
+  setup_linked_copy
+  cd "$PROJECT_DIR/app"
+  puff forget .env
+
+  run puff prune-links "$PROJECT_DIR/wt" --restore
+  assert_success
+  assert_output_contains "Deleted 0 and restored 1 link(s)."
+  assert_file_content "$PROJECT_DIR/wt/.env" "KEY=1"
+  [ ! -L "$PROJECT_DIR/wt/.env" ]
+}
+
+@test "prune-links: finds links of removed projects" {
+  setup_linked_copy
+  cd "$PROJECT_DIR/app"
+  puff project forget app -y
+
+  run puff prune-links "$PROJECT_DIR/wt" --restore
+  assert_success
+  assert_output_contains "project 'app' was removed from puff"
+  assert_output_contains "Nothing to restore it from"
+  assert_symlink "$PROJECT_DIR/wt/.env"
+
+  run puff prune-links "$PROJECT_DIR/wt" --delete
+  assert_success
+  assert_not_exists "$PROJECT_DIR/wt/.env"
+}
+
+@test "prune-links: --list changes nothing" {
+  setup_linked_copy
+  cd "$PROJECT_DIR/app"
+  puff forget .env
+
+  run puff prune-links "$PROJECT_DIR/wt" --list
+  assert_success
+  assert_output_contains "$PROJECT_DIR/wt/.env"
+  assert_output_not_contains "Deleted"
+  assert_symlink "$PROJECT_DIR/wt/.env"
+}
+
+@test "prune-links: leaves healthy links alone and reports unclassified ones" {
+  setup_linked_copy
+  ln -s "$PUFF_DATA_PATH/projects/app/.puff_project.json" "$PROJECT_DIR/wt/meta"
+
+  run puff prune-links "$PROJECT_DIR/wt" --delete
+  assert_success
+  assert_output_contains "Could not classify '$PROJECT_DIR/wt/meta'"
+  assert_output_contains "No orphaned links found."
+  assert_symlink "$PROJECT_DIR/wt/.env"
+  assert_symlink "$PROJECT_DIR/wt/meta"
+}
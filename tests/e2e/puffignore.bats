@@ -0,0 +1,113 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "puffignore: add refuses a directory with excluded entries and keeps them" {
+  puff_init "myproject"
+  mkdir -p config/local/cache
+  echo "KEY=1" >config/local/dev.env
+  echo "x" >config/local/cache/blob
+  echo "x" >config/local/build.tmp
+  printf '# generated\ncache/\n*.tmp\n' >config/local/.puffignore
+  run puff add config/local --dir
+  assert_failure
+  assert_output_contains "contains 2 entries excluded by its .puffignore"
+  assert_not_symlink "$PROJECT_DIR/config/local"
+  assert_file_content "$PROJECT_DIR/config/local/build.tmp" "x"
+  assert_file_content "$PROJECT_DIR/config/local/cache/blob" "x"
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject/config/local"
+}
+
+@test "puffignore: add keeps the .puffignore of a directory without excluded entries" {
+  puff_init "myproject"
+  mkdir -p config/local
+  echo "KEY=1" >config/local/dev.env
+  echo "*.tmp" >config/local/.puffignore
+  run puff add config/local --dir
+  assert_success
+  assert_symlink "$PROJECT_DIR/config/local"
+  assert_file_exists "$PUFF_DATA_PATH/projects/myproject/config/local/dev.env"
+  assert_file_exists "$PUFF_DATA_PATH/projects/myproject/config/local/.puffignore"
+}
+
+@test "puffignore: forget restores excluded entries written through the symlink" {
+  puff_init "myproject"
+  mkdir -p config/local
+  echo "KEY=1" >config/local/dev.env
+  echo "*.tmp" >config/local/.puffignore
+  puff add config/local --dir
+  echo "x" >config/local/build.tmp
+  run puff forget config/local
+  assert_success
+  assert_not_symlink "$PROJECT_DIR/config/local"
+  assert_file_content "$PROJECT_DIR/config/local/dev.env" "KEY=1"
+  assert_file_content "$PROJECT_DIR/config/local/build.tmp" "x"
+}
+
+@test "puffignore: project forget restores excluded entries of managed directories" {
+  puff_init "myproject"
+  mkdir -p config
+  echo "KEY=1" >config/dev.env
+  echo "*.tmp" >config/.puffignore
+  puff add config
+  echo "x" >config/build.tmp
+  run puff project forget -y myproject
+  assert_success
+  assert_file_content "$PROJECT_DIR/config/build.tmp" "x"
+}
+
+@test "puffignore: link skips entries excluded by the project's .puffignore" {
+  puff_init "myproject"
+  echo "KEY=1" >.env
+  puff add .env
+  echo "x" >"$PUFF_DATA_PATH/projects/myproject/.DS_Store"
+  echo ".DS_Store" >"$PUFF_DATA_PATH/projects/myproject/.puffignore"
+  local other_dir
+  other_dir="$(mktemp -d)"
+  cd "$other_dir"
+  run puff link myproject
+  assert_success
+  assert_symlink "$other_dir/.env"
+  assert_not_exists "$other_dir/.DS_Store"
+  rm -rf "$other_dir"
+}
+
+@test "puffignore: the project's .puffignore hides items" {
+  puff_init "myproject"
+  echo "KEY=1" >.env
+  puff add .env
+  echo "x" >"$PUFF_DATA_PATH/projects/myproject/.DS_Store"
+  echo ".DS_Store" >"$PUFF_DATA_PATH/projects/myproject/.puffignore"
+  run puff project show myproject
+  assert_success
+  assert_output_contains ".env"
+  assert_output_not_contains ".DS_Store"
+  assert_output_not_contains ".puffignore"
+}
+
+@test "puffignore: merge previews excluded entries" {
+  puff_init "myproject"
+  mkdir config
+  echo "KEY=1" >config/dev.env
+  echo "x" >config/build.tmp
+  echo "*.tmp" >config/.puffignore
+  run puff merge config --dry-run
+  assert_success
+  assert_output_contains "config/build.tmp (excluded by .puffignore, move or delete it first)"
+  assert_output_contains "config/dev.env (not managed yet, gets added)"
+}
+
+@test "puffignore: merge refuses a directory with excluded entries" {
+  puff_init "myproject"
+  mkdir config
+  echo "KEY=1" >config/dev.env
+  echo "x" >config/build.tmp
+  echo "*.tmp" >config/.puffignore
+  run puff merge config -y
+  assert_failure
+  assert_output_contains "contains 1 entries excluded by its .puffignore"
+  assert_not_symlink "$PROJECT_DIR/config"
+  assert_file_content "$PROJECT_DIR/config/build.tmp" "x"
+}
@@ -0,0 +1,62 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+# Registers project "app" in $PROJECT_DIR and deletes its data directory
+make_entry_without_data() {
+  cd "$PROJECT_DIR"
+  puff init --name app
+  rm -rf "$PUFF_DATA_PATH/projects/app"
+}
+
+@test "reconcile: list warns about entries without data and continues" {
+  make_entry_without_data
+  mkdir -p "$PUFF_DATA_PATH/projects/other"
+  run puff list
+  assert_success
+  assert_output_contains "data directory of these registered projects is missing: app"
+  assert_output_contains "other"
+}
+
+@test "reconcile: init --associate works despite entries without data" {
+  make_entry_without_data
+  mkdir -p "$PUFF_DATA_PATH/projects/other" "$PROJECT_DIR/other"
+  cd "$PROJECT_DIR/other"
+  run puff init --associate other
+  assert_success
+}
+
+@test "reconcile: --list shows both kinds of mismatches" {
+  make_entry_without_data
+  mkdir -p "$PUFF_DATA_PATH/projects/other"
+  run puff reconcile --list
+  assert_success
+  assert_output_contains "Project 'app' ($PROJECT_DIR) is registered, but its data directory is missing."
+  assert_output_contains "Project 'other' is stored in puff, but not associated"
+}
+
+@test "reconcile: --missing recreate creates an empty data directory" {
+  make_entry_without_data
+  run puff reconcile --missing recreate
+  assert_success
+  assert_output_contains "Recreated an empty data directory for 'app'"
+  assert_file_exists "$PUFF_DATA_PATH/projects/app/.puff_project.json"
+
+  run puff status
+  assert_success
+  assert_output_contains "Project: app"
+}
+
+@test "reconcile: --missing unregister removes the entry" {
+  make_entry_without_data
+  run puff reconcile --missing unregister
+  assert_success
+  assert_output_contains "Unregistered 'app'"
+
+  run puff list
+  assert_output_not_contains "WARNING"
+  run puff reconcile
+  assert_output_contains "The registry and the data directory are consistent."
+}
@@ -0,0 +1,82 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+# Simulates a lost config.json: the data directory and the symlinks survived
+lose_config() {
+  echo '{"projects":[]}' >"$PUFF_CONFIG_PATH/config.json"
+}
+
+@test "recover: registers projects found through their symlinks" {
+  mkdir -p "$PROJECT_DIR/code/api/config" "$PROJECT_DIR/code/web"
+  cd "$PROJECT_DIR/code/api"
+  puff init --name api
+  echo "KEY=1" >.env
+  echo "DB=1" >config/db.env
+  puff add .env config/db.env
+  cd "$PROJECT_DIR/code/web"
+  puff init --name web
+  echo "KEY=2" >.env
+  puff add .env
+  lose_config
+
+  cd "$PROJECT_DIR"
+  run puff recover --scan "$PROJECT_DIR/code" -y
+  assert_success
+  assert_output_contains "api -> $PROJECT_DIR/code/api (2 link(s))"
+  assert_output_contains "web -> $PROJECT_DIR/code/web (1 link(s))"
+  assert_output_contains "Recovered 2 project(s)."
+
+  cd "$PROJECT_DIR/code/api/config"
+  run puff status
+  assert_success
+  assert_output_contains "Project: api"
+}
+
+@test "recover: managed directories count as links" {
+  mkdir -p "$PROJECT_DIR/app/secrets"
+  cd "$PROJECT_DIR/app"
+  puff init --name app
+  echo "KEY=1" >secrets/key
+  puff add secrets --dir
+  lose_config
+
+  run puff recover --scan "$PROJECT_DIR" -y
+  assert_success
+  assert_output_contains "app -> $PROJECT_DIR/app"
+}
+
+@test "recover: keeps existing associations" {
+  mkdir -p "$PROJECT_DIR/app"
+  cd "$PROJECT_DIR/app"
+  puff init --name app
+  echo "KEY=1" >.env
+  puff add .env
+
+  run puff recover --scan "$PROJECT_DIR" -y
+  assert_success
+  assert_output_contains "No associations to recover."
+}
+
+@test "recover: skips projects whose links are spread over several directories" {
+  mkdir -p "$PROJECT_DIR/a" "$PROJECT_DIR/b"
+  cd "$PROJECT_DIR/a"
+  puff init --name app
+  echo "KEY=1" >.env
+  puff add .env
+  cd "$PROJECT_DIR/b"
+  puff link app
+  lose_config
+
+  run puff recover --scan "$PROJECT_DIR" -y
+  assert_success
+  assert_output_contains "Skipping 'app', its links are spread over several directories"
+  assert_output_contains "No associations to recover."
+}
+
+@test "recover: --scan is required" {
+  run puff recover
+  assert_failure
+}
@@ -0,0 +1,101 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() {
+  setup_puff_env
+  export HOME="$PROJECT_DIR/home"
+  mkdir -p "$HOME/src/app"
+}
+teardown() { teardown_puff_env; }
+
+@test "roots: paths inside the home directory are stored relative to it" {
+  cd "$HOME/src/app"
+  run puff init --name app
+  assert_success
+  run grep -qF '"path": "~/src/app"' "$PUFF_CONFIG_PATH/config.json"
+  assert_success
+
+  run puff status
+  assert_output_contains "Project: app"
+}
+
+@test "roots: paths inside a root are stored relative to it" {
+  cd "$HOME/src/app"
+  puff init --name app
+  run puff config roots add code "$HOME/src"
+  assert_success
+  assert_output_contains "code = ~/src"
+  run grep -qF '"path": "{code}/app"' "$PUFF_CONFIG_PATH/config.json"
+  assert_success
+
+  run puff config roots list
+  assert_output_contains "code = ~/src"
+
+  run puff status
+  assert_output_contains "Project: app"
+}
+
+@test "roots: removing a root stores full paths again" {
+  cd "$HOME/src/app"
+  puff init --name app
+  puff config roots add code "$HOME/src"
+  run puff config roots remove code
+  assert_success
+  run grep -qF '"path": "~/src/app"' "$PUFF_CONFIG_PATH/config.json"
+  assert_success
+
+  run puff config roots remove code
+  assert_failure
+  assert_output_contains "Root 'code' is not defined"
+}
+
+@test "roots: invalid root name is rejected" {
+  run puff config roots add "a/b" "$HOME/src"
+  assert_failure
+  assert_output_contains "Invalid root name"
+}
+
+@test "roots: import rewrites paths from another machine" {
+  mkdir -p "$PUFF_DATA_PATH/projects/mac" "$PUFF_DATA_PATH/projects/work-app"
+  echo "KEY=1" >"$PUFF_DATA_PATH/projects/mac/.env"
+  mkdir -p "$HOME/code/mac" "$HOME/work/work-app"
+  cat >"$PROJECT_DIR/other.json" <<'EOF'
+{"projects":[
+  {"name":"mac","id":"1","path":"/Users/me/code/mac"},
+  {"name":"work-app","id":"2","path":"{work}/work-app"},
+  {"name":"nodata","id":"3","path":"/Users/me/code/nodata"}
+],"roots":{"work":"/Users/me/work"}}
+EOF
+
+  run puff config import "$PROJECT_DIR/other.json" --rewrite "/Users/me=$HOME"
+  assert_success
+  assert_output_contains "Imported 'mac' ($HOME/code/mac)"
+  assert_output_contains "Imported 'work-app' ($HOME/work/work-app)"
+  assert_output_contains "Skipped 'nodata'"
+  assert_output_contains "Imported 2 project(s)."
+
+  run puff config roots list
+  assert_output_contains "work = ~/work"
+
+  run puff project show mac
+  assert_output_contains "Path: $HOME/code/mac"
+}
+
+@test "roots: import skips projects registered already" {
+  cd "$HOME/src/app"
+  puff init --name app
+  cat >"$PROJECT_DIR/other.json" <<'EOF'
+{"projects":[{"name":"app","id":"1","path":"/elsewhere/app"}]}
+EOF
+
+  run puff config import "$PROJECT_DIR/other.json"
+  assert_success
+  assert_output_contains "Skipped 'app', a project with this name is already registered"
+}
+
+@test "roots: import with invalid rewrite rule fails" {
+  echo '{"projects":[]}' >"$PROJECT_DIR/other.json"
+  run puff config import "$PROJECT_DIR/other.json" --rewrite "/only-from"
+  assert_failure
+  assert_output_contains "Expected FROM=TO"
+}
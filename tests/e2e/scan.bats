@@ -0,0 +1,68 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "scan: lists ignored secret files" {
+  puff_init "myproject"
+  printf '.env*\n*.pem\n' >.gitignore
+  echo "a=1" >.env
+  echo "pem" >server.pem
+  echo "not ignored" >private.key
+  run puff scan --format json
+  assert_success
+  assert_output_contains '".env"'
+  assert_output_contains '"server.pem"'
+  assert_output_not_contains "private.key"
+}
+
+@test "scan -y: adds all found files" {
+  puff_init "myproject"
+  mkdir -p apps/web
+  printf '.env\n' >.gitignore
+  echo "a=1" >apps/web/.env
+  run puff scan -y
+  assert_success
+  assert_symlink "$PROJECT_DIR/apps/web/.env"
+  assert_file_content "$PROJECT_DIR/apps/web/.env" "a=1"
+}
+
+@test "scan: excludes files already managed" {
+  puff_init "myproject"
+  printf '.env\n' >.gitignore
+  echo "a=1" >.env
+  puff add .env
+  run puff scan -y
+  assert_success
+  assert_output_contains "No unmanaged secret files found"
+}
+
+@test "scan: respects negated gitignore rules" {
+  puff_init "myproject"
+  printf '.env*\n!.env.example\n' >.gitignore
+  echo "a=" >.env.example
+  run puff scan --format json
+  assert_success
+  assert_output_not_contains ".env.example"
+}
+
+@test "scan: asks git, so core.excludesFile counts and status agrees" {
+  git init -q
+  puff_init "myproject"
+  echo ".env" >.git/puff-test-excludes
+  git config core.excludesFile "$PROJECT_DIR/.git/puff-test-excludes"
+  echo "a=1" >.env
+  run puff scan -y
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  run puff status
+  assert_success
+  assert_output_not_contains "git could commit"
+}
+
+@test "scan: fails outside of a project" {
+  run puff scan
+  assert_failure
+  assert_output_contains "not associated"
+}
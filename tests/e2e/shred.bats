@@ -0,0 +1,83 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "shred: forget -d --shred deletes the file and its backups instead of trashing it" {
+  puff_init "myproject"
+  echo "local=1" >.env
+  echo "managed=2" >"$PUFF_DATA_PATH/projects/myproject/.env"
+  puff add .env --on-conflict backup
+  echo "other=1" >.secrets
+  puff add .secrets
+  puff forget -d .secrets
+
+  run puff forget -d --shred .env
+  assert_success
+  assert_output_contains "Shredded 2 file(s)."
+  assert_not_exists "$PROJECT_DIR/.env"
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject/.env"
+  run puff backups list
+  assert_output_contains "No backups."
+  run puff trash list
+  assert_output_contains "myproject  .secrets"
+}
+
+@test "shred: forget -d --shred also shreds earlier copies in the trash" {
+  puff_init "myproject"
+  echo "old=1" >.env
+  puff add .env
+  puff forget -d .env
+  echo "new=1" >.env
+  puff add .env
+  run puff forget -d --shred .env
+  assert_success
+  assert_output_contains "Shredded 2 file(s)."
+  run puff trash list
+  assert_output_contains "The trash is empty."
+}
+
+@test "shred: --shred requires --delete" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  run puff forget --shred .env
+  assert_failure
+  assert_symlink "$PROJECT_DIR/.env"
+}
+
+@test "shred: project forget -d --shred removes the project without trashing it" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  run puff project forget -y -d --shred myproject
+  assert_success
+  assert_output_contains "Shredded"
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject"
+  run puff trash list
+  assert_output_contains "The trash is empty."
+}
+
+@test "shred: trash empty --shred" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  puff forget -d .env
+  run puff trash empty -y --shred
+  assert_success
+  assert_output_contains "Shredded 1 file(s)."
+  run puff trash list
+  assert_output_contains "The trash is empty."
+}
+
+@test "shred: files with other hard links are reported" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  ln "$PUFF_DATA_PATH/projects/myproject/.env" "$PUFF_DATA_PATH/hardlink"
+  run puff forget -d --shred .env
+  assert_failure
+  assert_output_contains "not shredded securely"
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject/.env"
+}
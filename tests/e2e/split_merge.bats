@@ -0,0 +1,93 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+@test "split: turns a managed directory into individually managed files" {
+  puff_init "myproject"
+  mkdir -p config/nested
+  echo "KEY=1" >config/dev.env
+  echo "DB=1" >config/nested/db.env
+  puff add config --dir
+  run puff split config
+  assert_success
+  assert_output_contains "Split 'config/' into 2 individually managed file(s)"
+  [ ! -L "$PROJECT_DIR/config" ]
+  assert_symlink "$PROJECT_DIR/config/dev.env"
+  assert_symlink "$PROJECT_DIR/config/nested/db.env"
+  assert_file_content "$PROJECT_DIR/config/nested/db.env" "DB=1"
+  run grep -qx 'config' "$PUFF_DATA_PATH/projects/myproject/.puff_managed_dirs"
+  assert_failure
+
+  echo "local" >config/unmanaged.txt
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject/config/unmanaged.txt"
+  run puff forget config/dev.env
+  assert_success
+}
+
+@test "split: replaces the gitignore entry of the directory" {
+  puff_init "myproject"
+  mkdir config
+  echo "KEY=1" >config/dev.env
+  puff add config --dir --git-ignore
+  run puff split config
+  assert_success
+  run grep -qx '/config/dev\.env' .gitignore
+  assert_success
+  run grep -qx '/config' .gitignore
+  assert_failure
+}
+
+@test "split: fails for directories that aren't managed" {
+  puff_init "myproject"
+  mkdir config
+  run puff split config
+  assert_failure
+  assert_output_contains "is not a managed directory"
+}
+
+@test "merge: --dry-run previews without changing anything" {
+  puff_init "myproject"
+  mkdir config
+  echo "KEY=1" >config/dev.env
+  echo "X=1" >config/other.env
+  puff add config/dev.env
+  run puff merge config --dry-run
+  assert_success
+  assert_output_contains "config/dev.env (managed, becomes part of the directory)"
+  assert_output_contains "config/other.env (not managed yet, gets added)"
+  [ ! -L "$PROJECT_DIR/config" ]
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject/config/other.env"
+}
+
+@test "merge: absorbs managed and unmanaged files into a managed directory" {
+  puff_init "myproject"
+  mkdir config
+  echo "KEY=1" >config/dev.env
+  echo "X=1" >config/other.env
+  puff add --git-ignore config/dev.env
+  run puff merge config -y
+  assert_success
+  assert_symlink "$PROJECT_DIR/config"
+  assert_file_content "$PROJECT_DIR/config/dev.env" "KEY=1"
+  assert_file_content "$PUFF_DATA_PATH/projects/myproject/config/other.env" "X=1"
+  run grep -qx 'config' "$PUFF_DATA_PATH/projects/myproject/.puff_managed_dirs"
+  assert_success
+  run grep -qx '/config' .gitignore
+  assert_success
+  run grep -qx '/config/dev\.env' .gitignore
+  assert_failure
+}
+
+@test "merge: split and merge round-trip" {
+  puff_init "myproject"
+  mkdir config
+  echo "KEY=1" >config/dev.env
+  puff add config --dir
+  puff split config
+  run puff merge config -y
+  assert_success
+  assert_symlink "$PROJECT_DIR/config"
+  assert_file_content "$PROJECT_DIR/config/dev.env" "KEY=1"
+}
@@ -51,3 +51,59 @@ teardown() { teardown_puff_env; }
   assert_success
   assert_output_contains "myproject"
 }
+
+@test "status --format json: reports managed items with link state" {
+  puff_init "myproject"
+  puff add .env
+  run puff status --format json
+  assert_success
+  assert_output_contains '"managed": true'
+  assert_output_contains '"name": "myproject"'
+  assert_output_contains '"kind": "file"'
+  assert_output_contains '"link_state": "linked"'
+}
+
+@test "status --format json: reports unmanaged directory" {
+  run puff status --format json
+  assert_success
+  assert_output_contains '"managed": false'
+  assert_output_contains '"project": null'
+}
+
+@test "status: --format is rejected by commands without machine-readable output" {
+  puff_init "myproject"
+  run puff add .env --format json
+  assert_failure
+  assert_not_exists "$PROJECT_DIR/.env"
+}
+
+@test "status: warns about managed items that git does not ignore" {
+  git init -q
+  puff_init "myproject"
+  echo "secret=123" >.env
+  puff add .env
+  run puff status
+  assert_success
+  assert_output_contains "WARNING"
+  assert_output_contains ".env (not ignored by git)"
+}
+
+@test "status: warns about managed items that git tracks" {
+  git init -q
+  echo "secret=123" >.env
+  git add .env
+  puff_init "myproject"
+  puff add --git-ignore .env
+  run puff status
+  assert_success
+  assert_output_contains ".env (tracked by git)"
+}
+
+@test "status: no warning when managed items are ignored" {
+  git init -q
+  puff_init "myproject"
+  puff add --git-ignore .env
+  run puff status
+  assert_success
+  [[ "$output" != *"WARNING"* ]]
+}
@@ -0,0 +1,138 @@
+#!/usr/bin/env bats
+load helpers
+
+setup() { setup_puff_env; }
+teardown() { teardown_puff_env; }
+
+# Prints the id of the newest trash entry
+latest_trash_id() {
+  puff trash list | tail -n 1 | cut -d ' ' -f 1
+}
+
+@test "trash: forget -d moves the managed file to the trash" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  run puff forget -d .env
+  assert_success
+  assert_output_contains "trash"
+  assert_not_exists "$PROJECT_DIR/.env"
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject/.env"
+
+  run puff trash list
+  assert_success
+  assert_output_contains "ID"
+  assert_output_contains "PROJECT"
+  assert_output_contains "myproject  .env"
+  assert_output_contains "$PROJECT_DIR/.env"
+}
+
+@test "trash: restore puts a deleted file back under puff's management" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  puff forget -d .env
+  run puff trash restore "$(latest_trash_id)"
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  assert_file_content "$PROJECT_DIR/.env" "secret=1"
+  run puff trash list
+  assert_output_contains "The trash is empty."
+}
+
+@test "trash: restore into a git repository ignores the file again" {
+  git init -q
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add -g .env
+  puff forget -d .env
+  run grep -qx '/\.env' .gitignore
+  assert_failure
+  run puff trash restore "$(latest_trash_id)"
+  assert_success
+  assert_symlink "$PROJECT_DIR/.env"
+  run grep -qx '/\.env' .gitignore
+  assert_success
+}
+
+@test "trash: restore refuses a git repository outside of any project" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  puff forget -d .env
+  local repo
+  repo="$(mktemp -d)"
+  git -C "$repo" init -q
+  run puff trash restore "$(latest_trash_id)" --to "$repo/.env"
+  assert_failure
+  assert_output_contains "inside a git repository"
+  assert_not_exists "$repo/.env"
+  rm -rf "$repo"
+}
+
+@test "trash: restore never overwrites and --to restores elsewhere" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  puff forget -d .env
+  echo "new=1" >.env
+  local id
+  id="$(latest_trash_id)"
+  run puff trash restore "$id"
+  assert_failure
+  assert_output_contains "already exists"
+
+  run puff trash restore "$id" --to .env.old
+  assert_success
+  assert_file_content "$PROJECT_DIR/.env.old" "secret=1"
+  assert_file_content "$PROJECT_DIR/.env" "new=1"
+}
+
+@test "trash: forget -d of a managed directory" {
+  puff_init "myproject"
+  mkdir -p config
+  echo "db=1" >config/db.env
+  puff add config
+  run puff forget -d config
+  assert_success
+  assert_not_exists "$PROJECT_DIR/config"
+  run puff trash list
+  assert_output_contains "myproject  config/"
+  run puff trash restore "$(latest_trash_id)"
+  assert_success
+  assert_file_content "$PROJECT_DIR/config/db.env" "db=1"
+}
+
+@test "trash: project forget -d moves the project to the trash and restore brings it back" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  run puff project forget -y -d myproject
+  assert_success
+  assert_output_contains "trash"
+  assert_not_exists "$PUFF_DATA_PATH/projects/myproject"
+  run puff trash list
+  assert_output_contains "(whole project)"
+
+  run puff trash restore "$(latest_trash_id)"
+  assert_success
+  assert_file_content "$PUFF_DATA_PATH/projects/myproject/.env" "secret=1"
+  run puff list -u
+  assert_output_contains "myproject"
+}
+
+@test "trash: empty deletes entries permanently" {
+  puff_init "myproject"
+  echo "secret=1" >.env
+  puff add .env
+  puff forget -d .env
+  run puff trash empty --older-than 1 -y
+  assert_success
+  assert_output_contains "Nothing to remove"
+
+  run puff trash empty -y
+  assert_success
+  assert_output_contains "Permanently deleted 1 item(s)."
+  run puff trash list
+  assert_output_contains "The trash is empty."
+}